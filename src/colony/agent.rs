@@ -45,6 +45,16 @@ pub struct Agent {
     pub status: AgentStatus,
     /// Process ID (if running)
     pub pid: Option<u32>,
+    /// Model the agent was last launched with
+    pub model: Option<String>,
+    /// tmux pane ID (e.g. `%3`) the agent is running in
+    pub pane_id: Option<String>,
+    /// Number of times the agent was relaunched since the colony started
+    pub restarts: u32,
+    /// When the agent was last relaunched (RFC 3339)
+    pub last_restart: Option<String>,
+    /// Configuration the agent was last launched with
+    pub applied_config: Option<AgentConfig>,
 }
 
 impl Agent {
@@ -63,6 +73,11 @@ impl Agent {
             process: None,
             status: AgentStatus::Idle,
             pid: None,
+            model: None,
+            pane_id: None,
+            restarts: 0,
+            last_restart: None,
+            applied_config: None,
         }
     }
 
@@ -80,6 +95,23 @@ impl Agent {
     pub fn set_status(&mut self, status: AgentStatus) {
         self.status = status;
     }
+
+    /// Path of the file the launch command writes the model in use to
+    pub fn model_file(&self) -> PathBuf {
+        self.project_path.join("active_model")
+    }
+
+    /// Get the model the agent is actually running with
+    ///
+    /// Prefers the model recorded by the launch command, which reflects any
+    /// fallback that was needed, over the model the agent was launched with.
+    pub fn active_model(&self) -> Option<String> {
+        std::fs::read_to_string(self.model_file())
+            .ok()
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .or_else(|| self.model.clone())
+    }
 }
 
 /// Persistent state for tracking agents across CLI invocations
@@ -91,6 +123,21 @@ pub struct AgentState {
     pub worktree_path: PathBuf,
     pub project_path: PathBuf,
     pub log_path: PathBuf,
+    /// Model actually in use (after any fallbacks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// tmux pane ID the agent is running in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane_id: Option<String>,
+    /// Number of times the agent was relaunched since the colony started
+    #[serde(default)]
+    pub restarts: u32,
+    /// When the agent was last relaunched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_restart: Option<String>,
+    /// Configuration the agent was last launched with, used by `colony apply`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<AgentConfig>,
 }
 
 impl From<&Agent> for AgentState {
@@ -102,6 +149,11 @@ impl From<&Agent> for AgentState {
             worktree_path: agent.worktree_path.clone(),
             project_path: agent.project_path.clone(),
             log_path: agent.log_path.clone(),
+            model: agent.active_model(),
+            pane_id: agent.pane_id.clone(),
+            restarts: agent.restarts,
+            last_restart: agent.last_restart.clone(),
+            config: agent.applied_config.clone(),
         }
     }
 }
@@ -17,6 +17,18 @@ const GH_CLI: &str = include_str!("../../.claude/skills/colony-agent-skills/gh-c
 const CURL_API: &str = include_str!("../../.claude/skills/colony-agent-skills/curl-api.md");
 const JQ_JSON: &str = include_str!("../../.claude/skills/colony-agent-skills/jq-json.md");
 
+/// Names of the skills every agent gets
+pub const SKILL_NAMES: &[&str] = &[
+    "tmux-pane-tools",
+    "nvim-pane-editing",
+    "ollama-local-llm",
+    "bash-scripting",
+    "git-workflow",
+    "gh-cli",
+    "curl-api",
+    "jq-json",
+];
+
 /// Install agent skills to both project and worktree directories
 pub fn install_agent_skills(project_path: &Path, worktree_path: &Path) -> ColonyResult<()> {
     // Install to project directory
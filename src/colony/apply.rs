@@ -0,0 +1,351 @@
+//! Reconcile a running colony with an edited colony.yml
+//!
+//! The configuration is compared against the saved agent state, which records
+//! the configuration each agent was last launched with. New agents get a pane,
+//! removed agents are stopped and their pane closed, and running agents are
+//! relaunched only when a setting that reaches Claude Code changed.
+
+use colored::Colorize;
+
+use crate::colony::agent::AgentState;
+use crate::colony::config::AgentConfig;
+use crate::colony::multiplexer::PaneState;
+use crate::colony::{start, AgentStatus, ColonyConfig, ColonyController};
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+
+/// What applying the configuration does to one agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// New agent, started in a new pane
+    Add,
+    /// Running agent whose settings changed, relaunched in its pane
+    Restart(Vec<&'static str>),
+    /// Agent no longer in the configuration, stopped and its pane closed
+    Remove { pane_id: Option<String> },
+}
+
+/// Changes needed to bring the running colony in line with the configuration
+#[derive(Debug, Default)]
+pub struct Plan {
+    /// (agent ID, change), in configuration order with removals last
+    pub changes: Vec<(String, Change)>,
+}
+
+impl Plan {
+    /// Compare the configuration against the saved agent states
+    pub fn new(config: &ColonyConfig, states: &[AgentState]) -> Self {
+        let mut changes = Vec::new();
+
+        for agent in &config.agents {
+            match states.iter().find(|s| s.id == agent.id) {
+                None => changes.push((agent.id.clone(), Change::Add)),
+                Some(state) if state.status == AgentStatus::Running => {
+                    // Agents launched before their configuration was recorded are left alone
+                    let fields = state
+                        .config
+                        .as_ref()
+                        .map(|applied| changed_fields(applied, agent))
+                        .unwrap_or_default();
+                    if !fields.is_empty() {
+                        changes.push((agent.id.clone(), Change::Restart(fields)));
+                    }
+                }
+                // Stopped agents pick up the new configuration when they are next started
+                Some(_) => {}
+            }
+        }
+
+        for state in states {
+            if !config.agents.iter().any(|a| a.id == state.id) {
+                changes.push((
+                    state.id.clone(),
+                    Change::Remove {
+                        pane_id: state.pane_id.clone(),
+                    },
+                ));
+            }
+        }
+
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn count(&self, matches: fn(&Change) -> bool) -> usize {
+        self.changes.iter().filter(|(_, c)| matches(c)).count()
+    }
+
+    /// Print the plan, one line per agent
+    pub fn print(&self) {
+        let width = self
+            .changes
+            .iter()
+            .map(|(id, _)| id.len())
+            .max()
+            .unwrap_or(0);
+
+        for (agent_id, change) in &self.changes {
+            let (symbol, detail) = match change {
+                Change::Add => ("+".green().bold(), "new agent".to_string()),
+                Change::Restart(fields) => (
+                    "~".yellow().bold(),
+                    format!("restart ({} changed)", fields.join(", ")),
+                ),
+                Change::Remove { .. } => ("-".red().bold(), "removed, will be stopped".to_string()),
+            };
+            println!(
+                "  {} {:<width$}  {}",
+                symbol,
+                agent_id,
+                detail,
+                width = width
+            );
+        }
+
+        println!(
+            "\nPlan: {} to add, {} to restart, {} to stop.",
+            self.count(|c| matches!(c, Change::Add)),
+            self.count(|c| matches!(c, Change::Restart(_))),
+            self.count(|c| matches!(c, Change::Remove { .. }))
+        );
+    }
+}
+
+/// Settings that differ between the configuration an agent was launched with and its new one
+fn changed_fields(applied: &AgentConfig, new: &AgentConfig) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    if applied.role != new.role {
+        fields.push("role");
+    }
+    if applied.focus != new.focus {
+        fields.push("focus");
+    }
+    if applied.env != new.env {
+        fields.push("env");
+    }
+    if applied.model != new.model || applied.fallback_models != new.fallback_models {
+        fields.push("model");
+    }
+    if applied.mcp_servers != new.mcp_servers {
+        fields.push("mcp_servers");
+    }
+    fields
+}
+
+/// Show what applying colony.yml would change and apply it after confirmation
+pub async fn run(yes: bool) -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    let config = ColonyConfig::load(config_path)?;
+    config.validate()?;
+
+    let mut controller = ColonyController::new(config)?;
+    controller.initialize_agents()?;
+    controller.load_state()?;
+
+    let session_name = controller.config().session_name();
+    if !controller.multiplexer().session_exists(&session_name) {
+        return Err(ColonyError::Colony(format!(
+            "Colony session '{}' is not running. Start it with 'colony start'",
+            session_name
+        )));
+    }
+
+    let plan = Plan::new(controller.config(), &controller.saved_states()?);
+
+    utils::header("Colony Plan");
+    if plan.is_empty() {
+        utils::success("No changes. The running colony matches colony.yml");
+        return Ok(());
+    }
+    plan.print();
+    println!();
+
+    if !yes && !utils::confirm("Apply these changes?") {
+        utils::info("Apply cancelled");
+        return Ok(());
+    }
+
+    apply(&mut controller, &plan).await?;
+
+    utils::success(&format!("Applied {} change(s)", plan.changes.len()));
+    Ok(())
+}
+
+/// Carry out a plan against the running session
+async fn apply(controller: &mut ColonyController, plan: &Plan) -> ColonyResult<()> {
+    let mux = controller.multiplexer();
+
+    // New agents need a worktree, an inbox and message helper scripts before they launch
+    if plan.changes.iter().any(|(_, c)| *c == Change::Add) {
+        controller.create_worktrees()?;
+        start::setup_messaging_infrastructure(controller)?;
+    }
+
+    for (agent_id, change) in &plan.changes {
+        match change {
+            Change::Remove { pane_id } => {
+                if let Some(pane_id) = pane_id {
+                    if mux.pane_state(pane_id) != PaneState::Missing {
+                        mux.kill_pane(pane_id)?;
+                    }
+                }
+                utils::success(&format!("Stopped agent '{}'", agent_id));
+            }
+            Change::Add => {
+                let pane_id = controller.start_agent(agent_id).await?;
+                utils::success(&format!("Started agent '{}' in pane {}", agent_id, pane_id));
+            }
+            Change::Restart(_) => {
+                let pane_id = controller.restart_agent(agent_id).await?;
+                utils::success(&format!(
+                    "Restarted agent '{}' in pane {}",
+                    agent_id, pane_id
+                ));
+            }
+        }
+    }
+
+    // Drops removed agents from the saved state
+    controller.save_state()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::multiplexer::fake::FakeMultiplexer;
+    use crate::colony::multiplexer::Multiplexer;
+    use std::path::Path;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    fn config(dir: &Path, agents: &str) -> ColonyConfig {
+        let yaml = format!("name: test\nagents:\n{}", agents);
+        let mut config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+        for agent in &mut config.agents {
+            agent.directory = Some(dir.display().to_string());
+        }
+        config
+    }
+
+    fn state(agent: &AgentConfig, status: AgentStatus, pane_id: &str) -> AgentState {
+        AgentState {
+            id: agent.id.clone(),
+            status,
+            pid: None,
+            worktree_path: Default::default(),
+            project_path: Default::default(),
+            log_path: Default::default(),
+            model: None,
+            pane_id: Some(pane_id.to_string()),
+            restarts: 0,
+            last_restart: None,
+            config: Some(agent.clone()),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let dir = tempdir().unwrap();
+        let old = config(
+            dir.path(),
+            "  - {id: backend, role: Backend, focus: API}\n  - {id: docs, role: Writer, focus: Docs}\n  - {id: qa, role: QA, focus: Tests}\n  - {id: legacy, role: Old, focus: Old}\n",
+        );
+        let states: Vec<AgentState> = old
+            .agents
+            .iter()
+            .map(|a| {
+                let status = if a.id == "qa" {
+                    AgentStatus::Failed
+                } else {
+                    AgentStatus::Running
+                };
+                state(a, status, "%1")
+            })
+            .collect();
+
+        let new = config(
+            dir.path(),
+            "  - {id: backend, role: Backend, focus: API, model: claude-opus-4-20250514}\n  - {id: docs, role: Writer, focus: Docs}\n  - {id: qa, role: QA, focus: Everything}\n  - {id: frontend, role: Frontend, focus: UI}\n",
+        );
+        let plan = Plan::new(&new, &states);
+
+        assert_eq!(
+            plan.changes,
+            vec![
+                ("backend".to_string(), Change::Restart(vec!["model"])),
+                ("frontend".to_string(), Change::Add),
+                (
+                    "legacy".to_string(),
+                    Change::Remove {
+                        pane_id: Some("%1".to_string())
+                    }
+                ),
+            ]
+        );
+
+        assert!(Plan::new(&old, &states).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply() {
+        let dir = tempdir().unwrap();
+        let fake = Arc::new(FakeMultiplexer::default());
+        fake.create_session("colony-test").unwrap();
+        let backend = fake
+            .split_window_with_id("colony-test:0", "claude")
+            .unwrap();
+        let legacy = fake
+            .split_window_with_id("colony-test:0", "claude")
+            .unwrap();
+
+        let old = config(
+            dir.path(),
+            "  - {id: backend, role: Backend, focus: API}\n  - {id: legacy, role: Old, focus: Old}\n",
+        );
+        let states = vec![
+            state(&old.agents[0], AgentStatus::Running, &backend),
+            state(&old.agents[1], AgentStatus::Running, &legacy),
+        ];
+        std::fs::create_dir_all(dir.path().join(".colony")).unwrap();
+        std::fs::write(
+            dir.path().join(".colony/state.json"),
+            serde_json::to_string(&states).unwrap(),
+        )
+        .unwrap();
+
+        let new = config(
+            dir.path(),
+            "  - {id: backend, role: Backend, focus: Database}\n  - {id: frontend, role: Frontend, focus: UI}\n",
+        );
+        let mut controller = ColonyController::with_root(new, dir.path().join(".colony"))
+            .unwrap()
+            .with_multiplexer(fake.clone());
+        controller.initialize_agents().unwrap();
+        controller.load_state().unwrap();
+
+        let plan = Plan::new(controller.config(), &controller.saved_states().unwrap());
+        apply(&mut controller, &plan).await.unwrap();
+
+        assert_eq!(fake.pane_state(&legacy), PaneState::Missing);
+        assert_eq!(fake.pane(&backend).unwrap().commands.len(), 2);
+        let frontend = controller.get_agent("frontend").unwrap();
+        assert_eq!(frontend.status, AgentStatus::Running);
+        assert!(fake.pane(frontend.pane_id.as_ref().unwrap()).is_some());
+
+        let saved = controller.saved_states().unwrap();
+        assert_eq!(saved.len(), 2);
+        let backend_state = saved.iter().find(|s| s.id == "backend").unwrap();
+        assert_eq!(backend_state.config.as_ref().unwrap().focus, "Database");
+        assert!(Plan::new(controller.config(), &saved).is_empty());
+    }
+}
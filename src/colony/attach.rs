@@ -1,12 +1,10 @@
-use std::path::Path;
-
-use crate::colony::{tmux, ColonyConfig};
+use crate::colony::{multiplexer, ColonyConfig};
 use crate::error::ColonyResult;
 use crate::utils;
 
-/// Attach to an existing tmux session
+/// Attach to an existing multiplexer session
 pub async fn run() -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
@@ -14,30 +12,45 @@ pub async fn run() -> ColonyResult<()> {
         ));
     }
 
-    // Check if tmux is available
-    if !tmux::is_tmux_available() {
-        return Err(crate::error::ColonyError::Colony(
-            "tmux is not installed. Please install tmux to use this feature.".to_string(),
-        ));
-    }
-
     // Load configuration to get session name
     let config = ColonyConfig::load(config_path)?;
     let session_name = config.session_name();
+    let mux = multiplexer::from_kind(config.multiplexer);
+
+    // Check if the multiplexer is available
+    if !mux.is_available() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "{} is not installed. Please install {} to use this feature.",
+            mux.name(),
+            mux.name()
+        )));
+    }
 
     // Check if session exists
-    if !tmux::session_exists(&session_name) {
+    if !mux.session_exists(&session_name) {
         return Err(crate::error::ColonyError::Colony(format!(
-            "No tmux session '{}' found. Start the colony with 'colony start' first.",
+            "No {} session '{}' found. Start the colony with 'colony start' first.",
+            mux.name(),
             session_name
         )));
     }
 
-    utils::info(&format!("Attaching to tmux session '{}'...", session_name));
-    utils::info("Press Ctrl+B then D to detach from the session");
+    utils::info(&format!(
+        "Attaching to {} session '{}'...",
+        mux.name(),
+        session_name
+    ));
+    match config.multiplexer {
+        multiplexer::MultiplexerKind::Tmux => {
+            utils::info("Press Ctrl+B then D to detach from the session")
+        }
+        multiplexer::MultiplexerKind::Zellij => {
+            utils::info("Press Ctrl+O then D to detach from the session")
+        }
+    }
 
     // Attach to the session
-    tmux::attach_session(&session_name)?;
+    mux.attach_session(&session_name)?;
 
     Ok(())
 }
@@ -3,9 +3,10 @@ pub mod providers;
 pub mod token_store;
 
 use crate::error::ColonyResult;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "provider", rename_all = "kebab-case")]
 pub enum AuthProvider {
     /// Direct API key
@@ -38,7 +39,7 @@ impl Default for AuthProvider {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuthConfig {
     #[serde(flatten)]
     pub provider: AuthProvider,
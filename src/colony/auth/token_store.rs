@@ -136,6 +136,21 @@ impl TokenStore {
     }
 }
 
+/// Read a secret stored in the system keyring, if there is an entry for it
+pub fn read_keyring_secret(service: &str, name: &str) -> ColonyResult<Option<String>> {
+    let entry = keyring::Entry::new(service, name)
+        .map_err(|e| crate::error::ColonyError::Auth(format!("Keyring error: {}", e)))?;
+
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(crate::error::ColonyError::Auth(format!(
+            "Failed to read from keyring: {}",
+            e
+        ))),
+    }
+}
+
 /// Check if keyring is available on this system
 fn is_keyring_available() -> bool {
     // Try to create a test entry
@@ -1,5 +1,5 @@
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use crate::colony::auth::{oauth::OAuthFlow, providers, token_store::TokenStore, AuthProvider};
 use crate::colony::config::ColonyConfig;
@@ -132,7 +132,7 @@ pub async fn status() -> ColonyResult<()> {
     println!("🔐 Authentication Status\n");
 
     // Try to load colony.yml
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
     if !config_path.exists() {
         println!("❌ No colony.yml found in current directory");
         println!("   Run 'colony init' to create a new colony");
@@ -225,7 +225,7 @@ pub async fn status() -> ColonyResult<()> {
 pub async fn logout() -> ColonyResult<()> {
     println!("🔐 Logging out...\n");
 
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
     if !config_path.exists() {
         println!("No colony.yml found");
         return Ok(());
@@ -254,7 +254,7 @@ pub async fn logout() -> ColonyResult<()> {
 pub async fn refresh() -> ColonyResult<()> {
     println!("🔄 Refreshing authentication token...\n");
 
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Auth(
             "No colony.yml found".to_string(),
@@ -0,0 +1,166 @@
+//! Built-in colony blueprints
+
+pub const FULLSTACK_SQUAD_BLUEPRINT: &str = r#"
+name: fullstack-squad
+version: 1.0.0
+author: Colony Community
+description: "Backend and frontend developers with a test engineer and a code reviewer"
+
+parameters:
+  - name: repo_name
+    description: Repository name
+  - name: language
+    description: Primary language
+    default: typescript
+  - name: test_command
+    description: Command that runs the test suite
+    default: npm test
+
+colony:
+  name: "{{repo_name}}"
+  repository:
+    repo_type: source
+    purpose: "Build and ship {{repo_name}}"
+  capabilities:
+    tools: [git, gh]
+  agents:
+    - id: backend
+      role: Backend Engineer
+      focus: "APIs, data models and services in {{language}}"
+    - id: frontend
+      role: Frontend Engineer
+      focus: "User interface and client-side code in {{language}}"
+    - id: tester
+      template: test-engineer
+      template_params:
+        test_command: "`{{test_command}}`"
+    - id: reviewer
+      template: code-reviewer
+  layout:
+    type: custom
+    windows:
+      - name: build
+        panes:
+          - type: agent
+            agent_id: backend
+          - type: agent
+            agent_id: frontend
+      - name: quality
+        panes:
+          - type: agent
+            agent_id: tester
+          - type: agent
+            agent_id: reviewer
+          - type: tui
+
+workflows:
+  - name: feature-delivery
+    description: Build a feature end to end, then test and review it
+    steps:
+      - name: api
+        agent: backend
+        instructions: Implement the backend changes for the feature.
+      - name: ui
+        agent: frontend
+        depends_on: [api]
+        instructions: Build the user interface on top of the new API.
+      - name: test
+        agent: tester
+        depends_on: [api, ui]
+        instructions: "Cover the feature with tests and run `{{test_command}}`."
+      - name: review
+        agent: reviewer
+        depends_on: [test]
+        instructions: Review the feature's changes and report any issues.
+
+tasks:
+  - id: project-layout
+    title: Document the project layout
+    description: "Write down where the backend and frontend code of {{repo_name}} live."
+    assigned_to: backend
+    priority: high
+  - id: test-baseline
+    title: Establish a test baseline
+    description: "Run `{{test_command}}` and file tasks for any failures."
+    assigned_to: tester
+    dependencies: [project-layout]
+"#;
+
+pub const SECURITY_REVIEW_CREW_BLUEPRINT: &str = r#"
+name: security-review-crew
+version: 1.0.0
+author: Colony Community
+description: "Security auditor, dependency auditor and code reviewer for a security review"
+
+parameters:
+  - name: repo_name
+    description: Repository name
+  - name: language
+    description: Primary language
+    default: typescript
+
+colony:
+  name: "{{repo_name}}-security"
+  repository:
+    repo_type: source
+    purpose: "Security review of {{repo_name}}"
+  agents:
+    - id: auditor
+      template: security-auditor
+      focus: "Vulnerabilities in the {{language}} code of {{repo_name}}"
+    - id: dependencies
+      role: Dependency Auditor
+      focus: "Third-party {{language}} dependencies and their known vulnerabilities"
+    - id: reviewer
+      template: code-reviewer
+      focus: Verify the auditors' findings and review their fixes
+  layout:
+    type: custom
+    windows:
+      - name: review
+        panes:
+          - type: agent
+            agent_id: auditor
+          - type: agent
+            agent_id: dependencies
+          - type: agent
+            agent_id: reviewer
+
+workflows:
+  - name: security-review
+    description: Audit the code and its dependencies, then verify the findings
+    steps:
+      - name: audit-code
+        agent: auditor
+        instructions: Audit the code for the OWASP Top 10 and record each finding with a severity.
+      - name: audit-dependencies
+        agent: dependencies
+        instructions: Check every dependency for known vulnerabilities and outdated versions.
+      - name: verify
+        agent: reviewer
+        depends_on: [audit-code, audit-dependencies]
+        instructions: Verify the findings and write a summary report.
+
+tasks:
+  - id: threat-model
+    title: Write a threat model
+    description: "List the entry points, assets and trust boundaries of {{repo_name}}."
+    assigned_to: auditor
+    priority: high
+  - id: dependency-inventory
+    title: Inventory dependencies
+    description: List direct and transitive dependencies with their versions.
+    assigned_to: dependencies
+  - id: findings-report
+    title: Write the findings report
+    assigned_to: reviewer
+    dependencies: [threat-model, dependency-inventory]
+"#;
+
+/// Get all builtin blueprint definitions
+pub fn get_builtin_blueprints() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("fullstack-squad", FULLSTACK_SQUAD_BLUEPRINT),
+        ("security-review-crew", SECURITY_REVIEW_CREW_BLUEPRINT),
+    ]
+}
@@ -0,0 +1,364 @@
+//! Colony blueprints: whole-team starting points for `colony init --blueprint`
+//!
+//! A blueprint bundles a colony.yml (agents, layout, shared capabilities),
+//! workflow definitions and starter tasks. Its parameters are substituted for
+//! `{{name}}` in every string before anything is parsed, so other `{{...}}`
+//! placeholders, like those in prompt templates, are left alone.
+
+pub mod builtin;
+pub mod types;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_yaml::Value;
+
+use crate::colony::tasks::Task;
+use crate::colony::template::params::substitute;
+use crate::colony::workflow::definition::validate_workflow_definition;
+use crate::colony::workflow::types::WorkflowDefinition;
+use crate::colony::ColonyConfig;
+use crate::error::{ColonyError, ColonyResult};
+
+pub use builtin::get_builtin_blueprints;
+pub use types::{Blueprint, StarterTask};
+
+/// Directory under the colony root that blueprints are installed in
+pub const BLUEPRINTS_DIR: &str = "blueprints";
+
+/// File a blueprint directory or repository keeps its blueprint in
+const MANIFEST: &str = "blueprint.yaml";
+
+/// A blueprint with its parameters filled in
+#[derive(Debug)]
+pub struct ExpandedBlueprint {
+    /// The colony.yml to write, with agents' template references kept as-is
+    pub colony_yaml: Value,
+    /// The configuration colony.yml loads as
+    pub config: ColonyConfig,
+    pub workflows: Vec<WorkflowDefinition>,
+    pub tasks: Vec<Task>,
+}
+
+/// Load a blueprint from a file or directory path, a git URL, a blueprint
+/// installed in `.colony/blueprints/`, or a built-in, in that order
+pub fn load_blueprint(source: &str, repo_root: &Path) -> ColonyResult<Blueprint> {
+    let path = Path::new(source);
+    if path.exists() {
+        return load_path(path);
+    }
+
+    if is_git_url(source) {
+        return load_git(source);
+    }
+
+    let installed = repo_root
+        .join(".colony")
+        .join(BLUEPRINTS_DIR)
+        .join(source)
+        .join(MANIFEST);
+    if installed.is_file() {
+        return load_path(&installed);
+    }
+
+    let (_, yaml) = get_builtin_blueprints()
+        .into_iter()
+        .find(|(name, _)| *name == source)
+        .ok_or_else(|| {
+            ColonyError::Colony(format!(
+                "Blueprint '{}' not found. Use a path, a git URL, or a built-in: {}",
+                source,
+                get_builtin_blueprints()
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?;
+    Ok(serde_yaml::from_str(yaml)?)
+}
+
+pub(crate) fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "ssh://", "git@", "file://"]
+        .iter()
+        .any(|scheme| source.starts_with(scheme))
+        || source.ends_with(".git")
+}
+
+/// Shallow-clone a repository into a new temporary directory, which the caller
+/// removes when done with it
+pub(crate) fn clone_shallow(url: &str) -> ColonyResult<PathBuf> {
+    let checkout = std::env::temp_dir().join(format!("colony-clone-{}", uuid::Uuid::new_v4()));
+
+    // `--` so that a URL starting with `-` isn't taken for an option
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", "--quiet", "--"])
+        .arg(url)
+        .arg(&checkout)
+        .output()?;
+    if !output.status.success() {
+        let _ = fs::remove_dir_all(&checkout);
+        return Err(ColonyError::Colony(format!(
+            "Failed to clone {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(checkout)
+}
+
+/// Load `blueprint.yaml` from a directory, or a blueprint file itself
+fn load_path(path: &Path) -> ColonyResult<Blueprint> {
+    let path = if path.is_dir() {
+        path.join(MANIFEST)
+    } else {
+        path.to_path_buf()
+    };
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        ColonyError::Colony(format!(
+            "Failed to read blueprint {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    serde_yaml::from_str(&content).map_err(|e| {
+        ColonyError::Colony(format!(
+            "Failed to parse blueprint {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Shallow-clone a repository and load the `blueprint.yaml` at its root
+fn load_git(url: &str) -> ColonyResult<Blueprint> {
+    let checkout = clone_shallow(url)?;
+    let blueprint = load_path(&checkout);
+    let _ = fs::remove_dir_all(&checkout);
+    blueprint
+}
+
+/// Fill in a blueprint's parameters and check the result
+///
+/// Parameters without a value fall back to their default. Agents' templates are
+/// resolved against `.colony/templates/` under `repo_root`, and workflow steps
+/// and starter tasks must refer to the blueprint's own agents.
+pub fn expand(
+    blueprint: &Blueprint,
+    values: &HashMap<String, String>,
+    repo_root: &Path,
+) -> ColonyResult<ExpandedBlueprint> {
+    let mut resolved = HashMap::new();
+    for parameter in &blueprint.parameters {
+        let value = values
+            .get(&parameter.name)
+            .or(parameter.default.as_ref())
+            .ok_or_else(|| {
+                ColonyError::Colony(format!(
+                    "Blueprint '{}' needs a value for '{}'",
+                    blueprint.name, parameter.name
+                ))
+            })?;
+        resolved.insert(parameter.name.clone(), value.clone());
+    }
+
+    let mut colony_yaml = blueprint.colony.clone();
+    substitute(&mut colony_yaml, &resolved);
+    let config = ColonyConfig::from_value(colony_yaml.clone(), repo_root)?;
+    config.validate()?;
+
+    let has_agent = |id: &str| config.agents.iter().any(|a| a.id == id);
+
+    let mut workflows = Vec::new();
+    for workflow in &blueprint.workflows {
+        let mut workflow = workflow.clone();
+        substitute(&mut workflow, &resolved);
+        let workflow: WorkflowDefinition = serde_yaml::from_value(workflow)?;
+        validate_workflow_definition(&workflow)?;
+        if let Some(step) = workflow.steps.iter().find(|s| !has_agent(&s.agent)) {
+            return Err(ColonyError::Colony(format!(
+                "Workflow '{}' step '{}' uses unknown agent '{}'",
+                workflow.name, step.name, step.agent
+            )));
+        }
+        workflows.push(workflow);
+    }
+
+    let mut tasks = Vec::new();
+    for task in &blueprint.tasks {
+        let mut task = task.clone();
+        substitute(&mut task, &resolved);
+        let starter: StarterTask = serde_yaml::from_value(task)?;
+        // Task IDs become file names in the task queue
+        let valid_id = !starter.id.is_empty()
+            && starter
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err(ColonyError::Colony(format!(
+                "Invalid task ID '{}'. Task IDs must contain only alphanumeric characters, hyphens, and underscores.",
+                starter.id
+            )));
+        }
+        if let Some(agent) = starter
+            .assigned_to
+            .as_deref()
+            .filter(|a| *a != "auto" && !has_agent(a))
+        {
+            return Err(ColonyError::Colony(format!(
+                "Task '{}' is assigned to unknown agent '{}'",
+                starter.id, agent
+            )));
+        }
+        if let Some(dependency) = starter
+            .dependencies
+            .iter()
+            .find(|d| !blueprint_has_task(blueprint, d))
+        {
+            return Err(ColonyError::Colony(format!(
+                "Task '{}' depends on unknown task '{}'",
+                starter.id, dependency
+            )));
+        }
+
+        let mut task = Task::new(starter.id, starter.title, starter.description);
+        task.assigned_to = starter.assigned_to;
+        task.priority = starter.priority;
+        task.dependencies = starter.dependencies;
+        task.tags = starter.tags;
+        tasks.push(task);
+    }
+
+    Ok(ExpandedBlueprint {
+        colony_yaml,
+        config,
+        workflows,
+        tasks,
+    })
+}
+
+fn blueprint_has_task(blueprint: &Blueprint, id: &str) -> bool {
+    blueprint
+        .tasks
+        .iter()
+        .any(|t| t.get("id").and_then(Value::as_str) == Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_blueprints_expand() {
+        let dir = tempdir().unwrap();
+        for (name, _) in get_builtin_blueprints() {
+            let blueprint = load_blueprint(name, dir.path()).unwrap();
+            let expanded = expand(&blueprint, &values(&[("repo_name", "shop")]), dir.path())
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert!(!expanded.workflows.is_empty());
+            assert!(!expanded.tasks.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = tempdir().unwrap();
+        let blueprint = load_blueprint("fullstack-squad", dir.path()).unwrap();
+
+        assert!(expand(&blueprint, &HashMap::new(), dir.path()).is_err());
+
+        let expanded = expand(
+            &blueprint,
+            &values(&[("repo_name", "shop"), ("language", "rust")]),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(expanded.config.name.as_deref(), Some("shop"));
+        assert_eq!(
+            expanded.config.agents[0].focus,
+            "APIs, data models and services in rust"
+        );
+
+        // Template references are kept in colony.yml but resolved in the config
+        let tester = &expanded.colony_yaml["agents"][2];
+        assert_eq!(tester["template"].as_str(), Some("test-engineer"));
+        assert!(tester.get("role").is_none());
+        assert_eq!(expanded.config.agents[2].role, "Test Engineer");
+        assert!(expanded.config.agents[2]
+            .instructions
+            .as_ref()
+            .unwrap()
+            .contains("`npm test`"));
+
+        assert!(expanded.workflows[0].steps[2]
+            .instructions
+            .contains("`npm test`"));
+        let baseline = &expanded.tasks[1];
+        assert_eq!(baseline.assigned_to.as_deref(), Some("tester"));
+        assert_eq!(baseline.dependencies, vec!["project-layout".to_string()]);
+    }
+
+    #[test]
+    fn test_clone_shallow_url_is_not_an_option() {
+        let dir = tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let url = format!("--upload-pack=touch {};.git", marker.display());
+        assert!(is_git_url(&url));
+        assert!(clone_shallow(&url).is_err());
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_load_blueprint_from_path() {
+        let dir = tempdir().unwrap();
+        let blueprint_dir = dir.path().join("crew");
+        fs::create_dir_all(&blueprint_dir).unwrap();
+        fs::write(
+            blueprint_dir.join(MANIFEST),
+            r#"
+name: crew
+version: 0.1.0
+colony:
+  agents:
+    - id: solo
+      role: Developer
+      focus: "{{area}}, then {{agent.id}}'s own tasks"
+tasks:
+  - id: start
+    title: Start
+    assigned_to: someone-else
+"#,
+        )
+        .unwrap();
+
+        let mut blueprint = load_blueprint(blueprint_dir.to_str().unwrap(), dir.path()).unwrap();
+        blueprint.parameters.push(types::BlueprintParameter {
+            name: "area".to_string(),
+            description: None,
+            default: Some("Everything".to_string()),
+        });
+
+        // Tasks must be assigned to one of the blueprint's agents
+        assert!(expand(&blueprint, &HashMap::new(), dir.path()).is_err());
+
+        blueprint.tasks.clear();
+        let expanded = expand(&blueprint, &HashMap::new(), dir.path()).unwrap();
+        assert_eq!(
+            expanded.config.agents[0].focus,
+            "Everything, then {{agent.id}}'s own tasks"
+        );
+    }
+}
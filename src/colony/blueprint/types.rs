@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colony::tasks::TaskPriority;
+
+/// A blueprint for a whole colony: its agents, layout, shared capabilities,
+/// workflows and starter tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub name: String,
+    pub version: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// Values asked for when the blueprint is expanded
+    #[serde(default)]
+    pub parameters: Vec<BlueprintParameter>,
+    /// The colony.yml to write, before parameters are substituted
+    pub colony: serde_yaml::Value,
+    /// Workflow definitions to write to `.colony/workflows/`
+    #[serde(default)]
+    pub workflows: Vec<serde_yaml::Value>,
+    /// Tasks to add to the task queue
+    #[serde(default)]
+    pub tasks: Vec<serde_yaml::Value>,
+}
+
+/// A value substituted for `{{name}}` wherever it appears in the blueprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlueprintParameter {
+    pub name: String,
+    pub description: Option<String>,
+    pub default: Option<String>,
+}
+
+/// A task a blueprint adds to the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StarterTask {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// Agent the task is assigned to, or "auto" for any agent to claim
+    pub assigned_to: Option<String>,
+    #[serde(default)]
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
@@ -1,12 +1,10 @@
-use std::path::Path;
-
 use crate::colony::{messaging, ColonyConfig, ColonyController};
 use crate::error::ColonyResult;
 use crate::utils;
 
 /// Broadcast a message to all agents
 pub async fn run(message: String) -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
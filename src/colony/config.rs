@@ -1,12 +1,48 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
+use crate::colony::messaging::MessageType;
+use crate::colony::multiplexer::MultiplexerKind;
+use crate::colony::{config_layers, prompt, secrets, template};
 use crate::error::ColonyResult;
 
+/// Default configuration file, in the current directory
+pub const CONFIG_FILE: &str = "colony.yml";
+
+/// Where configuration is loaded from, set once from the global `--config`
+/// and `--profile` flags
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOptions {
+    /// Base configuration file instead of `colony.yml`
+    pub path: Option<PathBuf>,
+    /// Profile whose `colony.<profile>.yml` overlay is applied
+    pub profile: Option<String>,
+}
+
+static OPTIONS: OnceLock<ConfigOptions> = OnceLock::new();
+
+/// Set the configuration options for this process; only the first call has
+/// an effect
+pub fn set_options(options: ConfigOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+/// The configuration options for this process
+pub fn options() -> ConfigOptions {
+    OPTIONS.get().cloned().unwrap_or_default()
+}
+
+/// Path of the base configuration file
+pub fn config_path() -> PathBuf {
+    options().path.unwrap_or_else(|| PathBuf::from(CONFIG_FILE))
+}
+
 /// Configuration for telemetry collection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[derive(Default)]
 pub struct TelemetryConfig {
     /// Whether telemetry is enabled (opt-in, defaults to false)
@@ -42,7 +78,7 @@ impl TelemetryConfig {
 }
 
 /// Configuration for a colony of Claude Code agents
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ColonyConfig {
     /// Optional name for this colony (defaults to directory name)
     #[serde(default)]
@@ -69,10 +105,22 @@ pub struct ColonyConfig {
     /// Optional custom layout configuration
     #[serde(default)]
     pub layout: Option<LayoutConfig>,
+    /// Terminal multiplexer to run the agents in (tmux or zellij)
+    #[serde(default, skip_serializing_if = "MultiplexerKind::is_default")]
+    pub multiplexer: MultiplexerKind,
+    /// Named groups of agent IDs, addressed as `group:<name>` when messaging.
+    /// A member may be an agent with replicas, standing for all of them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Directory of the colony.yml this was loaded from, whose `.colony/`
+    /// holds the installed prompts and templates
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub repo_root: PathBuf,
 }
 
 /// Configuration for a single agent
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentConfig {
     /// Unique identifier for this agent
     pub id: String,
@@ -83,6 +131,9 @@ pub struct AgentConfig {
     /// Claude model to use (e.g., "claude-opus-4-20250514")
     #[serde(default = "default_model")]
     pub model: String,
+    /// Models to fall back to, in order, if the primary model is rejected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_models: Vec<String>,
     /// Optional custom directory/repo path for this agent
     /// If not specified, agent will work in a Git worktree of the current repo
     #[serde(default)]
@@ -92,7 +143,8 @@ pub struct AgentConfig {
     /// Multiple agents can specify the same worktree name to share a worktree
     #[serde(default)]
     pub worktree: Option<String>,
-    /// Optional environment variables for this agent's pane
+    /// Optional environment variables for this agent's pane. Values may refer
+    /// to secrets as `${env:NAME}`, `${file:path}` or `${keyring:service/name}`
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
     /// Optional MCP servers configuration for this agent
@@ -106,15 +158,41 @@ pub struct AgentConfig {
     pub capabilities: Option<CapabilitiesConfig>,
     #[serde(default)]
     pub nudge: Option<NudgeConfig>,
+    /// Optional push delivery of new messages into this agent's pane
+    #[serde(default)]
+    pub delivery: Option<DeliveryConfig>,
     /// Optional completely custom startup prompt
     /// If provided, this replaces the entire generated startup prompt
     /// Use this for complete control over the agent's initial instructions
     #[serde(default)]
     pub startup_prompt: Option<String>,
+    /// Optional agent template (`name` or `name@version`) the agent's settings
+    /// are based on. Fields set on the agent override the template's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Values for the template's parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<HashMap<String, serde_json::Value>>")]
+    pub template_params: Option<HashMap<String, serde_yaml::Value>>,
+    /// How the agent works: initiative, communication style and thoroughness
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<BehaviorConfig>,
+    /// Run this many identical agents, `<id>-1` to `<id>-N`, instead of one.
+    /// Each gets its own worktree unless `worktree` is set, and messages sent
+    /// to `<id>` reach all of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<u32>,
+    /// Labels to address the agent by as `tag:<tag>` when messaging
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// ID of the agent this one is a replica of
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub replica_of: Option<String>,
 }
 
 /// Repository type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 #[derive(Default)]
 pub enum RepositoryType {
@@ -131,7 +209,6 @@ pub enum RepositoryType {
     Documentation,
 }
 
-
 impl RepositoryType {
     /// Get a human-readable description of this repository type
     pub fn description(&self) -> &str {
@@ -146,7 +223,7 @@ impl RepositoryType {
 }
 
 /// Configuration for repository purpose and role
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RepositoryConfig {
     /// Type of repository (source, memory, application, research, documentation)
     #[serde(default)]
@@ -163,23 +240,51 @@ pub struct RepositoryConfig {
     /// Optional nudge configuration for this agent
     #[serde(default)]
     pub nudge: Option<NudgeConfig>,
+    /// Optional message delivery configuration for this agent
+    #[serde(default)]
+    pub delivery: Option<DeliveryConfig>,
 }
 
 /// Configuration for an MCP server
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct McpServerConfig {
     /// Command to execute to start the MCP server
     pub command: String,
     /// Optional arguments to pass to the command
     #[serde(default)]
     pub args: Option<Vec<String>>,
-    /// Optional environment variables for the MCP server
+    /// Optional environment variables for the MCP server. Values may refer to
+    /// secrets as `${env:NAME}`, `${file:path}` or `${keyring:service/name}`
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
+    /// Write resolved secrets into settings.json instead of exporting them
+    /// when the agent launches (defaults to false)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inline_secrets: bool,
+}
+
+impl McpServerConfig {
+    /// The environment as written to settings.json. Values that refer to
+    /// secrets become `${VAR}` references to the variables they are exported
+    /// as, unless `inline_secrets` is set.
+    pub fn settings_env(&self, server: &str) -> ColonyResult<HashMap<String, String>> {
+        let mut env = HashMap::new();
+        for (key, value) in self.env.iter().flatten() {
+            let value = if !secrets::has_references(value) {
+                value.clone()
+            } else if self.inline_secrets {
+                secrets::resolve(value, &format!("mcp_servers.{}.env.{}", server, key))?
+            } else {
+                format!("${{{}}}", secrets::secret_var(server, key))
+            };
+            env.insert(key.clone(), value);
+        }
+        Ok(env)
+    }
 }
 
 /// Configuration for the MCP executor pane
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExecutorConfig {
     /// Whether the executor is enabled (defaults to false)
     #[serde(default)]
@@ -194,6 +299,12 @@ pub struct ExecutorConfig {
     /// Defaults to ["typescript", "python"]
     #[serde(default = "default_languages")]
     pub languages: Vec<String>,
+    /// Claude model for the executor (defaults to the Claude Code default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Models to fall back to, in order, if the primary model is rejected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_models: Vec<String>,
 }
 
 fn default_executor_id() -> String {
@@ -211,11 +322,24 @@ impl Default for ExecutorConfig {
             agent_id: default_executor_id(),
             mcp_servers: None,
             languages: default_languages(),
+            model: None,
+            fallback_models: Vec::new(),
         }
     }
 }
 
 impl ExecutorConfig {
+    /// Models to launch the executor with, in order
+    ///
+    /// Empty when no model is configured, in which case Claude Code picks
+    /// its default model.
+    pub fn model_candidates(&self) -> Vec<&str> {
+        match &self.model {
+            Some(model) => dedup_models(Some(model.as_str()), &self.fallback_models),
+            None => Vec::new(),
+        }
+    }
+
     /// Generate Claude Code settings.json content with MCP server configuration
     /// Uses the same logic as AgentConfig
     pub fn generate_settings_json(&self) -> ColonyResult<String> {
@@ -240,8 +364,8 @@ impl ExecutorConfig {
                 }
 
                 // Add env if present
-                if let Some(env) = &server_config.env {
-                    server["env"] = json!(env);
+                if server_config.env.is_some() {
+                    server["env"] = json!(server_config.settings_env(name)?);
                 }
 
                 mcp_config.insert(name.clone(), server);
@@ -283,12 +407,34 @@ impl AgentConfig {
         })
     }
 
+    /// Models to launch with, in order: the configured model followed by
+    /// any fallbacks (duplicates removed)
+    pub fn model_candidates(&self) -> Vec<&str> {
+        dedup_models(Some(self.model.as_str()), &self.fallback_models)
+    }
+
     /// Get the worktree name for this agent
     /// Returns the configured worktree name, or the agent ID if not specified
     pub fn worktree_name(&self) -> &str {
         self.worktree.as_deref().unwrap_or(&self.id)
     }
 
+    /// The agents this entry runs: itself, or one copy per replica
+    pub fn instances(&self) -> Vec<AgentConfig> {
+        let Some(replicas) = self.replicas else {
+            return vec![self.clone()];
+        };
+
+        (1..=replicas)
+            .map(|n| AgentConfig {
+                id: format!("{}-{}", self.id, n),
+                replicas: None,
+                replica_of: Some(self.id.clone()),
+                ..self.clone()
+            })
+            .collect()
+    }
+
     /// Generate Claude Code settings.json content with MCP server configuration
     pub fn generate_settings_json(&self) -> ColonyResult<String> {
         use serde_json::{json, Value};
@@ -312,8 +458,8 @@ impl AgentConfig {
                 }
 
                 // Add env if present
-                if let Some(env) = &server_config.env {
-                    server["env"] = json!(env);
+                if server_config.env.is_some() {
+                    server["env"] = json!(server_config.settings_env(name)?);
                 }
 
                 mcp_config.insert(name.clone(), server);
@@ -353,21 +499,133 @@ impl AgentConfig {
     }
 }
 
+fn dedup_models<'a>(primary: Option<&'a str>, fallbacks: &'a [String]) -> Vec<&'a str> {
+    let mut models: Vec<&str> = Vec::new();
+    for model in primary
+        .into_iter()
+        .chain(fallbacks.iter().map(String::as_str))
+    {
+        if !model.is_empty() && !models.contains(&model) {
+            models.push(model);
+        }
+    }
+    models
+}
+
 fn default_model() -> String {
     "claude-sonnet-4-20250514".to_string()
 }
 
 impl ColonyConfig {
-    /// Load colony configuration from a YAML file
+    /// Load colony configuration from a YAML file, with its local overlay and
+    /// the active profile's overlay merged over it (see [`config_layers`])
     pub fn load(path: &Path) -> ColonyResult<Self> {
+        let layers = config_layers::load_layers(path, options().profile.as_deref())?;
+        let merged = config_layers::merge_layers(&layers);
+        Self::from_value(merged.value, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Load a single configuration file without its overlays, for commands
+    /// that edit and save it
+    pub fn load_file(path: &Path) -> ColonyResult<Self> {
         let contents = fs::read_to_string(path)?;
-        let config: ColonyConfig = serde_yaml::from_str(&contents)?;
+        let value: serde_yaml::Value = serde_yaml::from_str(&contents)?;
+        Self::from_value(value, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Build the configuration from parsed colony.yml contents, filling in
+    /// agents' settings from the templates they reference. Installed templates
+    /// are looked up in `.colony/templates/` under `repo_root`.
+    pub fn from_value(mut value: serde_yaml::Value, repo_root: &Path) -> ColonyResult<Self> {
+        let templates_dir = repo_root.join(".colony").join(template::TEMPLATES_DIR);
+        template::resolve_agent_templates(&mut value, &templates_dir)?;
+
+        // serde_yaml doesn't say which agent a field error is in
+        let mut config: ColonyConfig = serde_yaml::from_value(value.clone()).map_err(|e| {
+            match Self::agent_load_issues(&value, &templates_dir)
+                .into_iter()
+                .next()
+            {
+                Some(issue) => crate::error::ColonyError::InvalidConfig(format!(
+                    "{}: {}",
+                    issue.path, issue.message
+                )),
+                None => e.into(),
+            }
+        })?;
+        config.repo_root = repo_root.to_path_buf();
         Ok(config)
     }
 
+    /// Problems that keep parsed colony.yml contents from loading, each with
+    /// the setting it is about
+    pub fn load_issues(value: &serde_yaml::Value, repo_root: &Path) -> Vec<ConfigIssue> {
+        let templates_dir = repo_root.join(".colony").join(template::TEMPLATES_DIR);
+        let mut issues = Self::agent_load_issues(value, &templates_dir);
+        if issues.is_empty() {
+            if let Err(e) = Self::from_value(value.clone(), repo_root) {
+                issues.push(ConfigIssue::new("", e.to_string()));
+            }
+        }
+        issues
+    }
+
+    /// Load every agent on its own, so each broken agent is reported by ID
+    fn agent_load_issues(value: &serde_yaml::Value, templates_dir: &Path) -> Vec<ConfigIssue> {
+        let agents = value
+            .get("agents")
+            .and_then(serde_yaml::Value::as_sequence)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut issues = Vec::new();
+        for (i, agent) in agents.iter().enumerate() {
+            let mut single = serde_yaml::Mapping::new();
+            single.insert(
+                "agents".into(),
+                serde_yaml::Value::Sequence(vec![agent.clone()]),
+            );
+            let mut single = serde_yaml::Value::Mapping(single);
+
+            let result =
+                template::resolve_agent_templates(&mut single, templates_dir).and_then(|()| {
+                    Ok(serde_yaml::from_value::<AgentConfig>(
+                        single["agents"][0].clone(),
+                    )?)
+                });
+            if let Err(e) = result {
+                let message = match e {
+                    crate::error::ColonyError::InvalidConfig(message) => message,
+                    crate::error::ColonyError::Yaml(e) => e.to_string(),
+                    other => other.to_string(),
+                };
+                issues.push(ConfigIssue::new(
+                    config_layers::item_path("agents", i, agent),
+                    message,
+                ));
+            }
+        }
+        issues
+    }
+
     /// Save colony configuration to a YAML file
+    ///
+    /// When overwriting an existing file, fields agents take from their
+    /// templates are left out again unless they were changed, so that editing a
+    /// configuration loaded with [`Self::load_file`] doesn't copy template
+    /// settings into it.
     pub fn save(&self, path: &Path) -> ColonyResult<()> {
-        let yaml = serde_yaml::to_string(&self)?;
+        let mut value = serde_yaml::to_value(self)?;
+        if path.exists() {
+            let original: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+            let templates_dir = path
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(".colony")
+                .join(template::TEMPLATES_DIR);
+            template::unresolve_agent_templates(&mut value, &original, &templates_dir)?;
+        }
+        let yaml = serde_yaml::to_string(&value)?;
         fs::write(path, yaml)?;
         Ok(())
     }
@@ -383,39 +641,78 @@ impl ColonyConfig {
             telemetry: Default::default(), // Telemetry disabled by default (opt-in)
             capabilities: None,
             layout: None,
+            multiplexer: MultiplexerKind::default(),
+            groups: BTreeMap::new(),
+            repo_root: PathBuf::new(),
             agents: vec![
                 AgentConfig {
                     id: "backend-1".to_string(),
                     role: "Backend Engineer".to_string(),
                     focus: "API endpoints and server logic".to_string(),
                     model: "claude-opus-4-20250514".to_string(),
+                    fallback_models: Vec::new(),
                     directory: None, // Uses Git worktree
                     worktree: None,  // Uses agent ID as worktree name
                     env: None,       // No custom environment variables
                     mcp_servers: None,
                     instructions: None,   // No custom instructions
                     startup_prompt: None, // Use default generated prompt
+                    template: None,
+                    template_params: None,
+                    behavior: None,
+                    replicas: None,
+                    tags: Vec::new(),
+                    replica_of: None,
                     capabilities: None,
                     nudge: None,
+                    delivery: None,
                 },
                 AgentConfig {
                     id: "frontend-1".to_string(),
                     role: "Frontend Engineer".to_string(),
                     focus: "React components and UI implementation".to_string(),
                     model: "claude-sonnet-4-20250514".to_string(),
+                    fallback_models: Vec::new(),
                     directory: None, // Uses Git worktree
                     worktree: None,  // Uses agent ID as worktree name
                     env: None,       // No custom environment variables
                     mcp_servers: None,
                     instructions: None,   // No custom instructions
                     startup_prompt: None, // Use default generated prompt
+                    template: None,
+                    template_params: None,
+                    behavior: None,
+                    replicas: None,
+                    tags: Vec::new(),
+                    replica_of: None,
                     capabilities: None,
                     nudge: None,
+                    delivery: None,
                 },
             ],
         }
     }
 
+    /// Resolve the nudge configuration for an agent
+    /// Agent-level settings take precedence over repository-level settings
+    pub fn nudge_config_for(&self, agent: &AgentConfig) -> NudgeConfig {
+        agent
+            .nudge
+            .clone()
+            .or_else(|| self.repository.as_ref().and_then(|r| r.nudge.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Resolve the message delivery configuration for an agent
+    /// Agent-level settings take precedence over repository-level settings
+    pub fn delivery_config_for(&self, agent: &AgentConfig) -> DeliveryConfig {
+        agent
+            .delivery
+            .clone()
+            .or_else(|| self.repository.as_ref().and_then(|r| r.delivery.clone()))
+            .unwrap_or_default()
+    }
+
     /// Get the tmux session name for this colony
     /// Uses config name if set, otherwise falls back to current directory name
     pub fn session_name(&self) -> String {
@@ -446,38 +743,347 @@ impl ColonyConfig {
         }
     }
 
-    /// Validate the configuration
+    /// Agents with their replicas expanded, in configuration order
+    pub fn expanded_agents(&self) -> Vec<AgentConfig> {
+        self.agents
+            .iter()
+            .flat_map(AgentConfig::instances)
+            .collect()
+    }
+
+    /// Replica sets by the ID they are addressed as, with their members
+    pub fn replica_sets(&self) -> BTreeMap<String, Vec<String>> {
+        let mut sets = BTreeMap::new();
+        for agent in self.expanded_agents() {
+            if let Some(set) = agent.replica_of {
+                sets.entry(set).or_insert_with(Vec::new).push(agent.id);
+            }
+        }
+        sets
+    }
+
+    /// Every address that reaches more than one agent, with its members:
+    /// replica sets by ID, `group:<name>` for configured groups, and
+    /// `role:<role>` and `tag:<tag>` for the agents' roles and tags
+    pub fn message_groups(&self) -> BTreeMap<String, Vec<String>> {
+        let replica_sets = self.replica_sets();
+        let mut groups = replica_sets.clone();
+
+        for (name, members) in &self.groups {
+            let mut ids: Vec<String> = Vec::new();
+            for member in members {
+                for id in replica_sets
+                    .get(member)
+                    .cloned()
+                    .unwrap_or_else(|| vec![member.clone()])
+                {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            groups.insert(format!("group:{}", name), ids);
+        }
+
+        for agent in self.expanded_agents() {
+            let addresses = std::iter::once(format!("role:{}", agent.role))
+                .chain(agent.tags.iter().map(|tag| format!("tag:{}", tag)));
+            for address in addresses {
+                groups.entry(address).or_default().push(agent.id.clone());
+            }
+        }
+
+        groups
+    }
+
+    /// Validate the configuration, failing on the first problem found
     pub fn validate(&self) -> ColonyResult<()> {
+        match self.issues().into_iter().next() {
+            Some(issue) => Err(crate::error::ColonyError::Colony(issue.message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every problem with the configuration, each with the setting it is about
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let agents = self.expanded_agents();
+        // Replicas are reported at the entry they come from
+        let agent_path = |agent: &AgentConfig| {
+            format!(
+                "agents[{}]",
+                agent.replica_of.as_deref().unwrap_or(&agent.id)
+            )
+        };
+
         if self.agents.is_empty() {
-            return Err(crate::error::ColonyError::Colony(
+            issues.push(ConfigIssue::new(
+                "agents",
                 "At least one agent must be defined".to_string(),
             ));
         }
 
+        for agent in self.agents.iter().filter(|a| a.replicas == Some(0)) {
+            issues.push(ConfigIssue::new(
+                format!("{}.replicas", agent_path(agent)),
+                format!("Agent '{}' must have at least 1 replica", agent.id),
+            ));
+        }
+
         // Check for duplicate IDs and validate ID format
         let mut ids = std::collections::HashSet::new();
-        for agent in &self.agents {
+        for agent in &agents {
             // Validate agent ID for security (prevent path traversal and shell injection)
             if !Self::is_valid_agent_id(&agent.id) {
-                return Err(crate::error::ColonyError::Colony(format!(
-                    "Invalid agent ID '{}'. Agent IDs must contain only alphanumeric characters, hyphens, and underscores.",
-                    agent.id
-                )));
+                issues.push(ConfigIssue::new(
+                    format!("{}.id", agent_path(agent)),
+                    format!(
+                        "Invalid agent ID '{}'. Agent IDs must contain only alphanumeric characters, hyphens, and underscores.",
+                        agent.id
+                    ),
+                ));
+            } else if !ids.insert(&agent.id) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.id", agent_path(agent)),
+                    format!("Duplicate agent ID: {}", agent.id),
+                ));
             }
+        }
 
-            if !ids.insert(&agent.id) {
-                return Err(crate::error::ColonyError::Colony(format!(
-                    "Duplicate agent ID: {}",
-                    agent.id
-                )));
+        let replica_sets = self.replica_sets();
+        for (name, members) in &self.groups {
+            if !Self::is_valid_agent_id(name) {
+                issues.push(ConfigIssue::new(
+                    format!("groups.{}", name),
+                    format!(
+                        "Invalid group name '{}'. Group names must contain only alphanumeric characters, hyphens, and underscores.",
+                        name
+                    ),
+                ));
+            }
+            for (i, member) in members.iter().enumerate() {
+                if !ids.contains(member) && !replica_sets.contains_key(member) {
+                    issues.push(ConfigIssue::new(
+                        format!("groups.{}[{}]", name, i),
+                        format!("Group '{}' lists unknown agent '{}'", name, member),
+                    ));
+                }
             }
         }
 
-        Ok(())
+        for agent in &self.agents {
+            for (i, tag) in agent.tags.iter().enumerate() {
+                if !Self::is_valid_agent_id(tag) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.tags[{}]", agent_path(agent), i),
+                        format!(
+                            "Invalid tag '{}' on agent '{}'. Tags must contain only alphanumeric characters, hyphens, and underscores.",
+                            tag, agent.id
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let executor = self.executor.as_ref().filter(|e| e.enabled);
+        if let Some(executor) = executor {
+            if ids.contains(&executor.agent_id) {
+                issues.push(ConfigIssue::new(
+                    "executor.agent_id",
+                    format!("Executor ID '{}' is also an agent's ID", executor.agent_id),
+                ));
+            }
+        }
+
+        if let Some(layout) = self.layout.as_ref().filter(|l| l.is_custom()) {
+            issues.extend(layout.issues(&agents, executor.is_some()));
+        }
+
+        for agent in &self.agents {
+            let path = agent_path(agent);
+
+            // Custom directories must exist; they are used as they are
+            if let Some(directory) = agent.working_directory() {
+                if !directory.is_dir() {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.directory", path),
+                        format!(
+                            "Directory '{}' for agent '{}' does not exist",
+                            directory.display(),
+                            agent.id
+                        ),
+                    ));
+                }
+            }
+
+            // Worktree names become directory and branch names, and are
+            // shared by every agent that names them
+            if let Some(worktree) = &agent.worktree {
+                let field = format!("{}.worktree", path);
+                if agent.uses_custom_directory() {
+                    let sharers: Vec<&str> = agents
+                        .iter()
+                        .filter(|other| {
+                            other.id != agent.id
+                                && !other.uses_custom_directory()
+                                && other.worktree_name() == worktree
+                        })
+                        .map(|other| other.id.as_str())
+                        .collect();
+                    let message = if sharers.is_empty() {
+                        format!(
+                            "Agent '{}' sets both directory and worktree; the worktree is never used",
+                            agent.id
+                        )
+                    } else {
+                        format!(
+                            "Agent '{}' shares worktree '{}' with {}, but works in its custom directory instead",
+                            agent.id,
+                            worktree,
+                            sharers.join(", ")
+                        )
+                    };
+                    issues.push(ConfigIssue::new(field, message));
+                } else if !Self::is_valid_worktree_name(worktree) {
+                    issues.push(ConfigIssue::new(
+                        field,
+                        format!(
+                            "Invalid worktree name '{}'. Worktree names are '/'-separated parts of alphanumeric characters, hyphens, and underscores.",
+                            worktree
+                        ),
+                    ));
+                } else if let Some(owner) = agents.iter().find(|other| {
+                    other.id == *worktree
+                        && other.id != agent.id
+                        && (other.uses_custom_directory() || other.worktree_name() != worktree)
+                }) {
+                    issues.push(ConfigIssue::new(
+                        field,
+                        format!(
+                            "Agent '{}' shares worktree '{}', which is named after agent '{}', but '{}' works in {}",
+                            agent.id,
+                            worktree,
+                            owner.id,
+                            owner.id,
+                            if owner.uses_custom_directory() {
+                                "a custom directory".to_string()
+                            } else {
+                                format!("worktree '{}'", owner.worktree_name())
+                            }
+                        ),
+                    ));
+                } else if let Some(outer) = agents.iter().find(|other| {
+                    !other.uses_custom_directory()
+                        && worktree
+                            .strip_prefix(other.worktree_name())
+                            .is_some_and(|rest| rest.starts_with('/'))
+                }) {
+                    issues.push(ConfigIssue::new(
+                        field,
+                        format!(
+                            "Worktree '{}' of agent '{}' would be inside worktree '{}' of agent '{}'",
+                            worktree,
+                            agent.id,
+                            outer.worktree_name(),
+                            outer.id
+                        ),
+                    ));
+                }
+            }
+
+            // Capabilities can only name MCP servers the agent or the executor runs
+            if let Some(capabilities) = &agent.capabilities {
+                for (i, server) in capabilities.mcp_servers.iter().enumerate() {
+                    let defined = agent
+                        .mcp_servers
+                        .as_ref()
+                        .is_some_and(|s| s.contains_key(server))
+                        || executor
+                            .and_then(|e| e.mcp_servers.as_ref())
+                            .is_some_and(|s| s.contains_key(server));
+                    if !defined {
+                        issues.push(ConfigIssue::new(
+                            format!("{}.capabilities.mcp_servers[{}]", path, i),
+                            format!(
+                                "Agent '{}' lists capability MCP server '{}', which neither it nor the executor defines",
+                                agent.id, server
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if let Some(capabilities) = &self.capabilities {
+            for (i, server) in capabilities.mcp_servers.iter().enumerate() {
+                let defined = self
+                    .agents
+                    .iter()
+                    .filter_map(|a| a.mcp_servers.as_ref())
+                    .chain(executor.and_then(|e| e.mcp_servers.as_ref()))
+                    .any(|s| s.contains_key(server));
+                if !defined {
+                    issues.push(ConfigIssue::new(
+                        format!("capabilities.mcp_servers[{}]", i),
+                        format!(
+                            "Capability MCP server '{}' is not defined by any agent or the executor",
+                            server
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Prompt templates, with includes resolved against the colony next to colony.yml
+        let prompts_dir = self.repo_root.join(".colony").join(prompt::PROMPTS_DIR);
+        for agent in &self.agents {
+            let templates = [
+                ("startup_prompt", &agent.startup_prompt),
+                ("instructions", &agent.instructions),
+            ];
+            for (field, source) in templates {
+                if let Some(source) = source {
+                    if let Err(e) = prompt::PromptTemplate::parse(source)
+                        .and_then(|template| template.validate(&prompts_dir))
+                    {
+                        issues.push(ConfigIssue::new(
+                            format!("{}.{}", agent_path(agent), field),
+                            format!("Invalid {} for agent '{}': {}", field, agent.id, e),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Requirements of the templates agents are based on
+        let templates_dir = self.repo_root.join(".colony").join(template::TEMPLATES_DIR);
+        for agent in &self.agents {
+            if let Some(reference) = &agent.template {
+                if let Err(e) = template::find_template(reference, &templates_dir)
+                    .and_then(|t| template::check_requirements(&t, agent, self))
+                {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.template", agent_path(agent)),
+                        format!(
+                            "Agent '{}' can't use template '{}': {}",
+                            agent.id, reference, e
+                        ),
+                    ));
+                }
+            }
+        }
+
+        issues
     }
 
     /// Check if an agent ID is valid (only alphanumeric, hyphens, and underscores)
     /// This prevents path traversal attacks and shell injection
+    /// Worktree names may nest, like `agent/backend`, but can't leave
+    /// `.colony/worktrees/`
+    fn is_valid_worktree_name(name: &str) -> bool {
+        name.split('/').all(Self::is_valid_agent_id)
+    }
+
     fn is_valid_agent_id(id: &str) -> bool {
         if id.is_empty() {
             return false;
@@ -489,8 +1095,25 @@ impl ColonyConfig {
     }
 }
 
+/// A problem with the configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Setting the problem is about, like `agents[backend].directory`
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn new(path: impl Into<String>, message: String) -> Self {
+        Self {
+            path: path.into(),
+            message,
+        }
+    }
+}
+
 /// Nudge configuration for periodic message checking
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct NudgeConfig {
     /// Whether nudging is enabled for this agent
     #[serde(default = "default_nudge_enabled")]
@@ -521,8 +1144,142 @@ impl Default for NudgeConfig {
     }
 }
 
+/// Push delivery of new messages into an agent's pane
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeliveryConfig {
+    /// Whether new messages are announced in the agent's pane as they arrive
+    #[serde(default)]
+    pub enabled: bool,
+    /// Message types that are announced (others wait for the agent to read them)
+    #[serde(default = "default_delivery_types")]
+    pub types: Vec<MessageType>,
+    /// Seconds to wait after a message arrives so messages sent together are
+    /// announced together
+    #[serde(default = "default_delivery_batch_window")]
+    pub batch_window: u64,
+    /// Seconds the agent's pane must be idle before it is interrupted
+    #[serde(default = "default_delivery_quiet_period")]
+    pub quiet_period: u64,
+}
+
+fn default_delivery_types() -> Vec<MessageType> {
+    vec![MessageType::Task, MessageType::Question]
+}
+
+fn default_delivery_batch_window() -> u64 {
+    5
+}
+
+fn default_delivery_quiet_period() -> u64 {
+    10
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            types: default_delivery_types(),
+            batch_window: default_delivery_batch_window(),
+            quiet_period: default_delivery_quiet_period(),
+        }
+    }
+}
+
+/// How an agent works, rendered as guidance in its startup prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BehaviorConfig {
+    /// How much the agent acts without asking first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiative_level: Option<BehaviorLevel>,
+    /// How the agent writes messages to its teammates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub communication_style: Option<CommunicationStyle>,
+    /// How carefully the agent checks its work
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thoroughness: Option<BehaviorLevel>,
+}
+
+/// Level of a behavior setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BehaviorLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl BehaviorLevel {
+    pub const ALL: [BehaviorLevel; 3] = [Self::Low, Self::Medium, Self::High];
+}
+
+impl std::fmt::Display for BehaviorLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BehaviorLevel::Low => write!(f, "low"),
+            BehaviorLevel::Medium => write!(f, "medium"),
+            BehaviorLevel::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Style of an agent's messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CommunicationStyle {
+    Terse,
+    Direct,
+    Detailed,
+}
+
+impl CommunicationStyle {
+    pub const ALL: [CommunicationStyle; 3] = [Self::Terse, Self::Direct, Self::Detailed];
+}
+
+impl std::fmt::Display for CommunicationStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommunicationStyle::Terse => write!(f, "terse"),
+            CommunicationStyle::Direct => write!(f, "direct"),
+            CommunicationStyle::Detailed => write!(f, "detailed"),
+        }
+    }
+}
+
+impl BehaviorConfig {
+    /// Prompt guidance for each level that is set
+    pub fn guidance(&self) -> Vec<&'static str> {
+        let mut guidance = Vec::new();
+
+        if let Some(level) = self.initiative_level {
+            guidance.push(match level {
+                BehaviorLevel::Low => "Ask before starting work nobody assigned to you and before any large refactor. Otherwise stick to the task at hand.",
+                BehaviorLevel::Medium => "Take the obvious next step in your own work, but check with the team before large refactors or changes outside your focus.",
+                BehaviorLevel::High => "Act on your own judgement: pick up unassigned work in your focus and make the changes it needs, then tell the team what you changed.",
+            });
+        }
+
+        if let Some(style) = self.communication_style {
+            guidance.push(match style {
+                CommunicationStyle::Terse => "Keep messages to a line or two: what changed, what is blocked, what you need.",
+                CommunicationStyle::Direct => "Lead every message with the conclusion or request, then give the reasons.",
+                CommunicationStyle::Detailed => "Explain your reasoning in messages, including what you tried and the trade-offs you weighed.",
+            });
+        }
+
+        if let Some(level) = self.thoroughness {
+            guidance.push(match level {
+                BehaviorLevel::Low => "Favour speed: make the smallest change that works and leave wider cleanups for later.",
+                BehaviorLevel::Medium => "Test what you change and check the obvious edge cases before calling it done.",
+                BehaviorLevel::High => "Cover edge cases and failure modes, run the full test suite and review your own diff before calling anything done.",
+            });
+        }
+
+        guidance
+    }
+}
+
 /// Capabilities configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CapabilitiesConfig {
     /// Command-line tools available
     #[serde(default)]
@@ -574,7 +1331,7 @@ impl CapabilitiesConfig {
 }
 
 /// Layout configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LayoutConfig {
     /// Layout type
     #[serde(default = "default_layout_type", rename = "type")]
@@ -588,17 +1345,147 @@ fn default_layout_type() -> String {
     "default".to_string()
 }
 
+impl LayoutConfig {
+    /// Whether this layout describes its own windows and panes
+    pub fn is_custom(&self) -> bool {
+        self.layout_type == "custom" && !self.windows.is_empty()
+    }
+
+    /// Every problem with the layout: panes that can't be placed and windows
+    /// whose sizes don't add up
+    pub fn issues(&self, agents: &[AgentConfig], has_executor: bool) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut placed = std::collections::HashSet::new();
+
+        for (w, window) in self.windows.iter().enumerate() {
+            let window_path = format!("layout.windows[{}]", w);
+            if window.panes.is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.panes", window_path),
+                    format!("Layout window '{}' has no panes", window.name),
+                ));
+                continue;
+            }
+
+            let mut percent_total = 0u32;
+            let mut unsized_panes = 0;
+
+            for (p, pane) in window.panes.iter().enumerate() {
+                let pane_path = format!("{}.panes[{}]", window_path, p);
+                let key = match pane.pane_type.as_str() {
+                    "agent" => match pane.agent_id.as_deref() {
+                        None => {
+                            issues.push(ConfigIssue::new(
+                                pane_path.clone(),
+                                format!(
+                                    "Agent pane in layout window '{}' is missing agent_id",
+                                    window.name
+                                ),
+                            ));
+                            None
+                        }
+                        Some(agent_id) if !agents.iter().any(|a| a.id == agent_id) => {
+                            issues.push(ConfigIssue::new(
+                                format!("{}.agent_id", pane_path),
+                                format!(
+                                    "Layout window '{}' references unknown agent '{}'",
+                                    window.name, agent_id
+                                ),
+                            ));
+                            None
+                        }
+                        Some(agent_id) => Some(agent_id),
+                    },
+                    "executor" => {
+                        if !has_executor {
+                            issues.push(ConfigIssue::new(
+                                format!("{}.type", pane_path),
+                                format!(
+                                    "Layout window '{}' has an executor pane but the executor is not enabled",
+                                    window.name
+                                ),
+                            ));
+                        }
+                        Some("mcp-executor")
+                    }
+                    "tui" => Some("tui"),
+                    "tool" => None,
+                    other => {
+                        issues.push(ConfigIssue::new(
+                            format!("{}.type", pane_path),
+                            format!(
+                                "Unknown pane type '{}' in layout window '{}'. Expected agent, executor, tui or tool",
+                                other, window.name
+                            ),
+                        ));
+                        None
+                    }
+                };
+
+                if let Some(key) = key {
+                    if !placed.insert(key) {
+                        issues.push(ConfigIssue::new(
+                            pane_path.clone(),
+                            format!("'{}' appears more than once in the layout", key),
+                        ));
+                    }
+                }
+
+                match pane.parsed_size() {
+                    Ok(Some(PaneSize::Percent(pct))) => percent_total += u32::from(pct),
+                    Ok(Some(PaneSize::Cells(_))) => {}
+                    Ok(None) => unsized_panes += 1,
+                    Err(e) => {
+                        let message = match e {
+                            crate::error::ColonyError::Colony(message) => message,
+                            other => other.to_string(),
+                        };
+                        issues.push(ConfigIssue::new(format!("{}.size", pane_path), message));
+                    }
+                }
+            }
+
+            // Every pane without a size needs at least 1% of the window
+            if percent_total + unsized_panes > 100 {
+                issues.push(ConfigIssue::new(
+                    format!("{}.panes", window_path),
+                    format!(
+                        "Pane sizes in layout window '{}' add up to {}%, leaving no room for the other {} pane(s)",
+                        window.name, percent_total, unsized_panes
+                    ),
+                ));
+            }
+        }
+
+        issues
+    }
+}
+
 /// Window configuration for custom layouts
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WindowConfig {
     /// Window name
     pub name: String,
+    /// Whether panes are placed side by side (horizontal) or stacked (vertical)
+    #[serde(default)]
+    pub split: SplitDirection,
     /// Panes in this window
     pub panes: Vec<PaneConfig>,
 }
 
+/// Direction a window's panes are laid out in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitDirection {
+    /// Side by side, sizes are widths
+    #[default]
+    Horizontal,
+    /// Stacked top to bottom, sizes are heights
+    Vertical,
+}
+
 /// Pane configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PaneConfig {
     /// Pane type: agent, tool, executor, tui
     #[serde(rename = "type")]
@@ -617,6 +1504,50 @@ pub struct PaneConfig {
     pub size: Option<String>,
 }
 
+/// Size of a pane along its window's split direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneSize {
+    /// Percentage of the window, e.g. `60%`
+    Percent(u16),
+    /// Number of columns or rows, e.g. `40`
+    Cells(u16),
+}
+
+impl PaneConfig {
+    /// Parse the configured size, if any
+    pub fn parsed_size(&self) -> ColonyResult<Option<PaneSize>> {
+        let Some(size) = &self.size else {
+            return Ok(None);
+        };
+
+        let invalid = || {
+            crate::error::ColonyError::Colony(format!(
+                "Invalid pane size '{}'. Use a percentage (e.g. 60%) or a number of cells (e.g. 40)",
+                size
+            ))
+        };
+
+        let parsed = match size.trim().strip_suffix('%') {
+            Some(pct) => {
+                let pct = pct.trim().parse::<u16>().map_err(|_| invalid())?;
+                if pct == 0 || pct > 100 {
+                    return Err(invalid());
+                }
+                PaneSize::Percent(pct)
+            }
+            None => {
+                let cells = size.trim().parse::<u16>().map_err(|_| invalid())?;
+                if cells == 0 {
+                    return Err(invalid());
+                }
+                PaneSize::Cells(cells)
+            }
+        };
+
+        Ok(Some(parsed))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -642,34 +1573,312 @@ mod tests {
                     role: "Test".to_string(),
                     focus: "Testing".to_string(),
                     model: "claude-sonnet-4-20250514".to_string(),
+                    fallback_models: Vec::new(),
                     directory: None,
                     worktree: None,
                     env: None,
                     mcp_servers: None,
                     instructions: None,
                     startup_prompt: None,
+                    template: None,
+                    template_params: None,
+                    behavior: None,
+                    replicas: None,
+                    tags: Vec::new(),
+                    replica_of: None,
                     capabilities: None,
                     nudge: None,
+                    delivery: None,
                 },
                 AgentConfig {
                     id: "test".to_string(),
                     role: "Test 2".to_string(),
                     focus: "Testing".to_string(),
                     model: "claude-sonnet-4-20250514".to_string(),
+                    fallback_models: Vec::new(),
                     directory: None,
                     worktree: None,
                     env: None,
                     mcp_servers: None,
                     instructions: None,
                     startup_prompt: None,
+                    template: None,
+                    template_params: None,
+                    behavior: None,
+                    replicas: None,
+                    tags: Vec::new(),
+                    replica_of: None,
                     capabilities: None,
                     nudge: None,
+                    delivery: None,
                 },
             ],
             executor: None,
             capabilities: None,
             layout: None,
+            multiplexer: MultiplexerKind::default(),
+            groups: BTreeMap::new(),
+            repo_root: PathBuf::new(),
         };
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_model_candidates() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    model: claude-opus-4-20250514
+    fallback_models:
+      - claude-sonnet-4-20250514
+      - claude-opus-4-20250514
+      - claude-3-5-haiku-20241022
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.agents[0].model_candidates(),
+            vec![
+                "claude-opus-4-20250514",
+                "claude-sonnet-4-20250514",
+                "claude-3-5-haiku-20241022"
+            ]
+        );
+
+        let executor = ExecutorConfig::default();
+        assert!(executor.model_candidates().is_empty());
+    }
+
+    #[test]
+    fn test_behavior_guidance() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    behavior:
+      initiative_level: low
+      communication_style: terse
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let behavior = config.agents[0].behavior.as_ref().unwrap();
+        assert_eq!(behavior.thoroughness, None);
+
+        let guidance = behavior.guidance();
+        assert_eq!(guidance.len(), 2);
+        assert!(guidance[0].contains("before any large refactor"));
+        assert!(guidance[1].contains("a line or two"));
+        assert!(BehaviorConfig::default().guidance().is_empty());
+
+        let invalid = yaml.replace("terse", "chatty");
+        assert!(serde_yaml::from_str::<ColonyConfig>(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_issues() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    directory: /nonexistent/colony/backend
+    capabilities:
+      mcp_servers: [github]
+  - id: frontend
+    role: Frontend Engineer
+    focus: UI
+    worktree: backend
+  - id: mcp-executor
+    role: Executor
+    focus: Tools
+executor:
+  enabled: true
+layout:
+  type: custom
+  windows:
+    - name: main
+      panes:
+        - type: agent
+          agent_id: ghost
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let paths: Vec<String> = config.issues().into_iter().map(|i| i.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "executor.agent_id",
+                "layout.windows[0].panes[0].agent_id",
+                "agents[backend].directory",
+                "agents[backend].capabilities.mcp_servers[0]",
+                "agents[frontend].worktree",
+            ]
+        );
+        assert!(config.validate().is_err());
+
+        let invalid: serde_yaml::Value =
+            serde_yaml::from_str(&yaml.replace("focus: UI", "focus: [UI]")).unwrap();
+        let issues = ColonyConfig::load_issues(&invalid, Path::new(""));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "agents[frontend]");
+        let error = ColonyConfig::from_value(invalid, Path::new("")).unwrap_err();
+        assert!(error.to_string().contains("agents[frontend]"));
+    }
+
+    #[test]
+    fn test_worktree_issues() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    worktree: agent/backend
+  - id: frontend
+    role: Frontend Engineer
+    focus: UI
+    worktree: agent/backend
+  - id: docs
+    role: Writer
+    focus: Docs
+    directory: /tmp
+    worktree: agent/backend
+  - id: nested
+    role: Developer
+    focus: Code
+    worktree: agent/backend/nested
+  - id: escape
+    role: Developer
+    focus: Code
+    worktree: ../escape
+  - id: absolute
+    role: Developer
+    focus: Code
+    worktree: /tmp/absolute
+  - id: empty
+    role: Developer
+    focus: Code
+    worktree: agent//empty
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let issues = config.issues();
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "agents[docs].worktree",
+                "agents[nested].worktree",
+                "agents[escape].worktree",
+                "agents[absolute].worktree",
+                "agents[empty].worktree",
+            ]
+        );
+        assert!(issues[0].message.contains("with backend, frontend"));
+        assert!(issues[1]
+            .message
+            .contains("inside worktree 'agent/backend'"));
+    }
+
+    #[test]
+    fn test_message_groups() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    tags: [api]
+  - id: review
+    role: Reviewer
+    focus: Pull requests
+    replicas: 2
+    tags: [api, quality]
+groups:
+  reviewers: [review, backend, review-1]
+  broken: [ghost]
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let groups = config.message_groups();
+        assert_eq!(groups["review"], vec!["review-1", "review-2"]);
+        assert_eq!(
+            groups["group:reviewers"],
+            vec!["review-1", "review-2", "backend"]
+        );
+        assert_eq!(groups["role:Backend Engineer"], vec!["backend"]);
+        assert_eq!(groups["tag:api"], vec!["backend", "review-1", "review-2"]);
+        assert_eq!(groups["tag:quality"], vec!["review-1", "review-2"]);
+
+        let issues = config.issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "groups.broken[0]");
+    }
+
+    #[test]
+    fn test_issues_use_colony_next_to_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompts_dir = dir.path().join(".colony").join(prompt::PROMPTS_DIR);
+        fs::create_dir_all(&prompts_dir).unwrap();
+        fs::write(prompts_dir.join("conventions.md"), "Use snake_case").unwrap();
+        let path = dir.path().join("colony.yml");
+        fs::write(
+            &path,
+            r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    instructions: "{{> conventions}}"
+"#,
+        )
+        .unwrap();
+
+        // Includes are found whatever directory colony is run from
+        let config = ColonyConfig::load_file(&path).unwrap();
+        assert!(config.issues().is_empty());
+    }
+
+    #[test]
+    fn test_save_keeps_templates_unresolved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("colony.yml");
+        fs::write(
+            &path,
+            r#"
+agents:
+  - id: security
+    template: security-auditor
+    focus: OWASP Top 10 only
+  - id: reviewer
+    template: code-reviewer
+"#,
+        )
+        .unwrap();
+
+        let mut config = ColonyConfig::load_file(&path).unwrap();
+        assert_eq!(config.agents[0].role, "Security Auditor");
+        config.agents[0].replicas = Some(2);
+        config.agents[1].role = "Lead Reviewer".to_string();
+        config.save(&path).unwrap();
+
+        let saved: serde_yaml::Value =
+            serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let security = &saved["agents"][0];
+        assert_eq!(security["focus"].as_str(), Some("OWASP Top 10 only"));
+        assert_eq!(security["replicas"].as_u64(), Some(2));
+        for field in [
+            "role",
+            "model",
+            "instructions",
+            "behavior",
+            "startup_prompt",
+            "mcp_servers",
+        ] {
+            assert!(security.get(field).is_none(), "saved {}", field);
+        }
+        // Fields changed from the template's are kept
+        assert_eq!(saved["agents"][1]["role"].as_str(), Some("Lead Reviewer"));
+
+        let config = ColonyConfig::load_file(&path).unwrap();
+        assert_eq!(config.agents[0].role, "Security Auditor");
+        assert!(config.agents[0].instructions.is_some());
+        assert_eq!(config.agents[1].role, "Lead Reviewer");
+    }
 }
@@ -0,0 +1,225 @@
+use crate::colony::config::{self, ColonyConfig};
+use crate::colony::config_layers::{self, Layer, Locations, Merged};
+use crate::colony::template;
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+use colored::Colorize;
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Show the configuration with its overlays merged in
+///
+/// With `resolved`, every setting is shown as the colony uses it, including
+/// template settings and defaults, each with the file it came from.
+pub fn show(resolved: bool) -> ColonyResult<()> {
+    let config_path = &existing_config_path()?;
+    let profile = config::options().profile;
+    let layers = config_layers::load_layers(config_path, profile.as_deref())?;
+    let mut merged = config_layers::merge_layers(&layers);
+
+    let names: Vec<String> = layers.iter().map(|l| l.name()).collect();
+    println!("{}", format!("# {}", names.join(" <- ")).dimmed());
+
+    if !resolved {
+        print!("{}", serde_yaml::to_string(&merged.value)?);
+        return Ok(());
+    }
+
+    let repo_root = config_path.parent().unwrap_or(Path::new(""));
+    record_template_origins(&mut merged, repo_root)?;
+
+    let config = ColonyConfig::from_value(merged.value.clone(), repo_root)?;
+    if let Err(e) = config.validate() {
+        utils::warning(&format!("Configuration is invalid: {}", e));
+    }
+
+    let mut lines = Vec::new();
+    render(&serde_yaml::to_value(&config)?, "", 0, &merged, &mut lines);
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Check the configuration and report every problem, each with the file,
+/// line and column of the setting it is about
+pub fn validate() -> ColonyResult<()> {
+    let config_path = &existing_config_path()?;
+    let profile = config::options().profile;
+    let layers = config_layers::load_layers(config_path, profile.as_deref())?;
+    let merged = config_layers::merge_layers(&layers);
+
+    let repo_root = config_path.parent().unwrap_or(Path::new(""));
+    let issues = match ColonyConfig::from_value(merged.value.clone(), repo_root) {
+        Ok(config) => config.issues(),
+        Err(_) => ColonyConfig::load_issues(&merged.value, repo_root),
+    };
+
+    if issues.is_empty() {
+        utils::success(&format!("{} is valid", config_path.display()));
+        return Ok(());
+    }
+
+    let mut files = Vec::new();
+    for layer in &layers {
+        let contents = fs::read_to_string(&layer.path)?;
+        files.push((layer, config_layers::locations(&contents)));
+    }
+
+    for issue in &issues {
+        println!(
+            "{}: {} {}",
+            issue_location(&issue.path, &merged, &files).bold(),
+            issue.message,
+            format!("({})", issue.path).dimmed()
+        );
+    }
+
+    Err(ColonyError::InvalidConfig(format!(
+        "{} problem(s) found",
+        issues.len()
+    )))
+}
+
+/// Print the JSON Schema of colony.yml, for editor completion and checks
+pub fn schema() -> ColonyResult<()> {
+    let schema = schemars::schema_for!(ColonyConfig);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+fn existing_config_path() -> ColonyResult<PathBuf> {
+    let config_path = config::config_path();
+    if !config_path.exists() {
+        return Err(ColonyError::Colony(format!(
+            "{} not found. Run 'colony init' first.",
+            config_path.display()
+        )));
+    }
+    Ok(config_path)
+}
+
+/// `file:line:column` of the setting at `path`, in the layer it came from.
+/// Defaults and template settings are located at the closest setting above
+/// them in the base file.
+fn issue_location(path: &str, merged: &Merged, files: &[(&Layer, Locations)]) -> String {
+    let origin = config_layers::origin_of(&merged.origins, path);
+    let (layer, locations) = files
+        .iter()
+        .find(|(layer, _)| Some(layer.name().as_str()) == origin)
+        // Entries like `agents[backend]` have no origin of their own, only
+        // their fields do
+        .or_else(|| files.iter().rev().find(|(_, l)| l.contains_key(path)))
+        .unwrap_or(&files[0]);
+
+    match config_layers::location_of(locations, path) {
+        Some((line, column)) => format!("{}:{}:{}", layer.path.display(), line, column),
+        None => layer.path.display().to_string(),
+    }
+}
+
+/// Record the settings agents take from their templates
+fn record_template_origins(merged: &mut Merged, repo_root: &Path) -> ColonyResult<()> {
+    let mut with_templates = merged.value.clone();
+    let templates_dir = repo_root.join(".colony").join(template::TEMPLATES_DIR);
+    template::resolve_agent_templates(&mut with_templates, &templates_dir)?;
+
+    let (Some(before), Some(after)) = (
+        merged.value.get("agents").and_then(Value::as_sequence),
+        with_templates.get("agents").and_then(Value::as_sequence),
+    ) else {
+        return Ok(());
+    };
+
+    for (i, (before, after)) in before.iter().zip(after).enumerate() {
+        let (Some(reference), Some(fields)) = (
+            after.get("template").and_then(Value::as_str),
+            after.as_mapping(),
+        ) else {
+            continue;
+        };
+        let source = format!("template {}", reference);
+        let path = config_layers::item_path("agents", i, after);
+        for (key, value) in fields {
+            if before.get(key).is_none() {
+                let child = config_layers::child_path(&path, key);
+                config_layers::record(value, &source, &child, &mut merged.origins);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Render a mapping's entries as YAML lines, each value followed by its
+/// source. Unset and empty values are left out.
+fn render(value: &Value, path: &str, indent: usize, merged: &Merged, lines: &mut Vec<String>) {
+    let Some(map) = value.as_mapping() else {
+        return;
+    };
+    let pad = " ".repeat(indent);
+
+    for (key, value) in map {
+        let child = config_layers::child_path(path, key);
+        let key = key
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| scalar(key));
+        match value {
+            Value::Null => {}
+            Value::Mapping(map) if map.is_empty() => {}
+            Value::Sequence(items) if items.is_empty() => {}
+            Value::Mapping(_) => {
+                lines.push(format!("{}{}:", pad, key));
+                render(value, &child, indent + 2, merged, lines);
+            }
+            Value::Sequence(items) => {
+                lines.push(format!("{}{}:", pad, key));
+                for (i, item) in items.iter().enumerate() {
+                    let item_path = config_layers::item_path(&child, i, item);
+                    if item.is_mapping() {
+                        let start = lines.len();
+                        render(item, &item_path, indent + 4, merged, lines);
+                        if let Some(first) = lines.get_mut(start) {
+                            *first = format!("{}  - {}", pad, &first[indent + 4..]);
+                        }
+                    } else {
+                        lines.push(format!(
+                            "{}  - {}{}",
+                            pad,
+                            scalar(item),
+                            source(merged, &item_path)
+                        ));
+                    }
+                }
+            }
+            Value::String(s) if s.contains('\n') => {
+                lines.push(format!("{}{}: |{}", pad, key, source(merged, &child)));
+                for line in s.trim_end().lines() {
+                    lines.push(format!("{}  {}", pad, line).trim_end().to_string());
+                }
+            }
+            _ => lines.push(format!(
+                "{}{}: {}{}",
+                pad,
+                key,
+                scalar(value),
+                source(merged, &child)
+            )),
+        }
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    serde_yaml::to_string(value)
+        .unwrap_or_default()
+        .trim_end()
+        .to_string()
+}
+
+fn source(merged: &Merged, path: &str) -> String {
+    let origin = config_layers::origin_of(&merged.origins, path).unwrap_or("default");
+    format!("  {}", format!("# {}", origin).dimmed())
+}
@@ -0,0 +1,519 @@
+//! Layered configuration
+//!
+//! `colony.yml` is the base layer. `colony.local.yml` next to it (gitignored,
+//! for personal settings) and `colony.<profile>.yml` for the active profile are
+//! deep-merged over it in that order. Mappings merge key by key, lists of
+//! entries with an `id` (like `agents`) merge entry by entry, and anything
+//! else in a later layer replaces the earlier value.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use saphyr_parser::{Event, Parser};
+use serde_yaml::{Mapping, Value};
+
+use crate::error::{ColonyError, ColonyResult};
+
+/// One configuration file
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub path: PathBuf,
+    pub value: Value,
+}
+
+impl Layer {
+    /// File name shown as a value's source
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// Merged configuration with the layer each value came from, keyed by paths
+/// like `agents[backend].model`
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub value: Value,
+    pub origins: BTreeMap<String, String>,
+}
+
+/// Path of an overlay next to the base file: `colony.yml` -> `colony.<suffix>.yml`
+pub fn overlay_path(base: &Path, suffix: &str) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "colony".to_string());
+    let extension = base
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "yml".to_string());
+    base.with_file_name(format!("{}.{}.{}", stem, suffix, extension))
+}
+
+/// Add the base file's local overlay to the `.gitignore` next to it, so
+/// personal settings aren't committed. Returns whether it had to be added.
+pub fn ignore_local_overlay(base: &Path) -> ColonyResult<bool> {
+    let local = overlay_path(base, "local");
+    let Some(name) = local.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Ok(false);
+    };
+    let gitignore = local.with_file_name(".gitignore");
+
+    let mut contents = fs::read_to_string(&gitignore).unwrap_or_default();
+    let ignored = contents
+        .lines()
+        .any(|line| line.trim().trim_start_matches('/') == name);
+    if ignored {
+        return Ok(false);
+    }
+
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(&format!("{}\n", name));
+    fs::write(&gitignore, contents)?;
+    Ok(true)
+}
+
+/// Read the base file, its local overlay if there is one, and the profile's
+/// overlay, which must exist when a profile is given
+pub fn load_layers(base: &Path, profile: Option<&str>) -> ColonyResult<Vec<Layer>> {
+    let mut layers = vec![read_layer(base)?];
+
+    let local = overlay_path(base, "local");
+    if local.is_file() {
+        layers.push(read_layer(&local)?);
+    }
+
+    if let Some(profile) = profile {
+        let path = overlay_path(base, profile);
+        if !path.is_file() {
+            return Err(ColonyError::InvalidConfig(format!(
+                "profile '{}' not found: {} does not exist",
+                profile,
+                path.display()
+            )));
+        }
+        layers.push(read_layer(&path)?);
+    }
+
+    Ok(layers)
+}
+
+fn read_layer(path: &Path) -> ColonyResult<Layer> {
+    let contents = fs::read_to_string(path)?;
+    let value: Value = serde_yaml::from_str(&contents)
+        .map_err(|e| ColonyError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+    Ok(Layer {
+        path: path.to_path_buf(),
+        // An empty overlay is valid and changes nothing
+        value: if value.is_null() {
+            Value::Mapping(Mapping::new())
+        } else {
+            value
+        },
+    })
+}
+
+/// Deep-merge layers in order
+pub fn merge_layers(layers: &[Layer]) -> Merged {
+    let mut merged = Merged::default();
+    for (i, layer) in layers.iter().enumerate() {
+        let source = layer.name();
+        if i == 0 {
+            merged.value = layer.value.clone();
+            record(&merged.value, &source, "", &mut merged.origins);
+        } else {
+            merge(
+                &mut merged.value,
+                layer.value.clone(),
+                &source,
+                "",
+                &mut merged.origins,
+            );
+        }
+    }
+    merged
+}
+
+fn merge(
+    base: &mut Value,
+    overlay: Value,
+    source: &str,
+    path: &str,
+    origins: &mut BTreeMap<String, String>,
+) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                let child = child_path(path, &key);
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value, source, &child, origins),
+                    None => {
+                        record(&value, source, &child, origins);
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(overlay))
+            if all_have_ids(base) && all_have_ids(&overlay) =>
+        {
+            for entry in overlay {
+                let id = entry_id(&entry).unwrap_or_default().to_string();
+                let child = format!("{}[{}]", path, id);
+                match base.iter_mut().find(|e| entry_id(e) == Some(id.as_str())) {
+                    Some(existing) => merge(existing, entry, source, &child, origins),
+                    None => {
+                        record(&entry, source, &child, origins);
+                        base.push(entry);
+                    }
+                }
+            }
+        }
+        // Repeating a value, like an entry's id, doesn't change where it came from
+        (base, overlay) if *base == overlay => {}
+        (base, overlay) => {
+            origins.retain(|key, _| !is_within(key, path));
+            record(&overlay, source, path, origins);
+            *base = overlay;
+        }
+    }
+}
+
+/// Record `source` as the origin of every value in `value`
+pub fn record(value: &Value, source: &str, path: &str, origins: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Mapping(map) if !map.is_empty() => {
+            for (key, value) in map {
+                record(value, source, &child_path(path, key), origins);
+            }
+        }
+        Value::Sequence(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                record(item, source, &item_path(path, i, item), origins);
+            }
+        }
+        _ => {
+            origins.insert(path.to_string(), source.to_string());
+        }
+    }
+}
+
+/// The layer a value at `path` came from: its own, or the closest enclosing
+/// value's
+pub fn origin_of<'a>(origins: &'a BTreeMap<String, String>, path: &str) -> Option<&'a str> {
+    let mut path = path;
+    loop {
+        if let Some(source) = origins.get(path) {
+            return Some(source);
+        }
+        path = parent_path(path)?;
+    }
+}
+
+/// Path of a mapping value
+pub fn child_path(path: &str, key: &Value) -> String {
+    let key = match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    };
+    if path.is_empty() {
+        key
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Path of a list entry: by `id` if it has one, otherwise by index
+pub fn item_path(path: &str, index: usize, item: &Value) -> String {
+    match entry_id(item) {
+        Some(id) => format!("{}[{}]", path, id),
+        None => format!("{}[{}]", path, index),
+    }
+}
+
+/// Line and column of values in a file, keyed by the same paths as
+/// [`Merged::origins`]
+pub type Locations = BTreeMap<String, (usize, usize)>;
+
+/// Where every value in a file's contents is. Mapping values are located at
+/// their key.
+pub fn locations(contents: &str) -> Locations {
+    let mut events = Parser::new_from_str(contents).map_while(Result::ok);
+    let mut locations = BTreeMap::new();
+    while let Some((event, span)) = events.next() {
+        if matches!(
+            event,
+            Event::Scalar(..) | Event::SequenceStart(..) | Event::MappingStart(..)
+        ) {
+            let root = read_node(event, span.start, &mut events);
+            locate(&root, "", &mut locations);
+            break;
+        }
+    }
+    locations
+}
+
+/// The location of the value at `path`, or of the closest enclosing value
+pub fn location_of(locations: &Locations, path: &str) -> Option<(usize, usize)> {
+    let mut path = path;
+    loop {
+        if let Some(location) = locations.get(path) {
+            return Some(*location);
+        }
+        path = parent_path(path)?;
+    }
+}
+
+/// A parsed YAML node and where it starts
+struct Node {
+    location: (usize, usize),
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Scalar(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+}
+
+fn read_node<'a>(
+    event: Event<'a>,
+    start: saphyr_parser::Marker,
+    events: &mut impl Iterator<Item = (Event<'a>, saphyr_parser::Span)>,
+) -> Node {
+    let kind = match event {
+        Event::Scalar(value, ..) => NodeKind::Scalar(value.to_string()),
+        Event::SequenceStart(..) => {
+            let mut items = Vec::new();
+            while let Some((event, span)) = events.next() {
+                if matches!(event, Event::SequenceEnd) {
+                    break;
+                }
+                items.push(read_node(event, span.start, events));
+            }
+            NodeKind::Sequence(items)
+        }
+        Event::MappingStart(..) => {
+            let mut entries = Vec::new();
+            while let Some((event, span)) = events.next() {
+                if matches!(event, Event::MappingEnd) {
+                    break;
+                }
+                let key = read_node(event, span.start, events);
+                let Some((event, span)) = events.next() else {
+                    break;
+                };
+                entries.push((key, read_node(event, span.start, events)));
+            }
+            NodeKind::Mapping(entries)
+        }
+        _ => NodeKind::Scalar(String::new()),
+    };
+    Node {
+        location: (start.line(), start.col() + 1),
+        kind,
+    }
+}
+
+fn locate(node: &Node, path: &str, locations: &mut Locations) {
+    match &node.kind {
+        NodeKind::Scalar(_) => {}
+        NodeKind::Sequence(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let id = match &item.kind {
+                    NodeKind::Mapping(entries) => {
+                        entries
+                            .iter()
+                            .find_map(|(key, value)| match (&key.kind, &value.kind) {
+                                (NodeKind::Scalar(key), NodeKind::Scalar(id)) if key == "id" => {
+                                    Some(id.as_str())
+                                }
+                                _ => None,
+                            })
+                    }
+                    _ => None,
+                };
+                let child = match id {
+                    Some(id) => format!("{}[{}]", path, id),
+                    None => format!("{}[{}]", path, i),
+                };
+                locations.insert(child.clone(), item.location);
+                locate(item, &child, locations);
+            }
+        }
+        NodeKind::Mapping(entries) => {
+            for (key, value) in entries {
+                let NodeKind::Scalar(name) = &key.kind else {
+                    continue;
+                };
+                let child = child_path(path, &Value::String(name.clone()));
+                locations.insert(child.clone(), key.location);
+                locate(value, &child, locations);
+            }
+        }
+    }
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    if path.is_empty() {
+        return None;
+    }
+    let cut = path.rfind(['.', '[']).unwrap_or(0);
+    Some(&path[..cut])
+}
+
+fn is_within(key: &str, path: &str) -> bool {
+    path.is_empty()
+        || key == path
+        || key
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+}
+
+fn entry_id(entry: &Value) -> Option<&str> {
+    entry.get("id").and_then(Value::as_str)
+}
+
+fn all_have_ids(items: &[Value]) -> bool {
+    items.iter().all(|item| entry_id(item).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_merge_layers() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("colony.yml");
+        fs::write(
+            &base,
+            r#"
+name: shop
+agents:
+  - id: backend
+    role: Backend
+    focus: APIs
+    env:
+      LOG: info
+  - id: frontend
+    role: Frontend
+    focus: UI
+capabilities:
+  tools: [git, gh]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            overlay_path(&base, "local"),
+            r#"
+agents:
+  - id: backend
+    model: claude-opus-4-20250514
+    env:
+      TOKEN: secret
+"#,
+        )
+        .unwrap();
+        fs::write(
+            overlay_path(&base, "ci"),
+            r#"
+capabilities:
+  tools: [git]
+agents:
+  - id: tester
+    role: Tester
+    focus: CI
+"#,
+        )
+        .unwrap();
+
+        assert!(load_layers(&base, Some("nightly")).is_err());
+
+        let layers = load_layers(&base, Some("ci")).unwrap();
+        assert_eq!(layers.len(), 3);
+        let merged = merge_layers(&layers);
+
+        let agents = merged.value["agents"].as_sequence().unwrap();
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0]["model"].as_str(), Some("claude-opus-4-20250514"));
+        assert_eq!(agents[0]["env"]["LOG"].as_str(), Some("info"));
+        assert_eq!(agents[0]["env"]["TOKEN"].as_str(), Some("secret"));
+        assert_eq!(agents[0]["role"].as_str(), Some("Backend"));
+        assert_eq!(
+            merged.value["capabilities"]["tools"]
+                .as_sequence()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let origin = |path: &str| origin_of(&merged.origins, path).unwrap().to_string();
+        assert_eq!(origin("name"), "colony.yml");
+        assert_eq!(origin("agents[backend].id"), "colony.yml");
+        assert_eq!(origin("agents[backend].role"), "colony.yml");
+        assert_eq!(origin("agents[backend].model"), "colony.local.yml");
+        assert_eq!(origin("agents[backend].env.TOKEN"), "colony.local.yml");
+        assert_eq!(origin("agents[tester].focus"), "colony.ci.yml");
+        assert_eq!(origin("capabilities.tools[0]"), "colony.ci.yml");
+        assert!(!merged.origins.contains_key("capabilities.tools[1]"));
+    }
+
+    #[test]
+    fn test_ignore_local_overlay() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("colony.yml");
+        let gitignore = dir.path().join(".gitignore");
+
+        fs::write(&gitignore, "target").unwrap();
+        assert!(ignore_local_overlay(&base).unwrap());
+        assert_eq!(
+            fs::read_to_string(&gitignore).unwrap(),
+            "target\ncolony.local.yml\n"
+        );
+        assert!(!ignore_local_overlay(&base).unwrap());
+
+        let other = dir.path().join("ops.yml");
+        assert!(ignore_local_overlay(&other).unwrap());
+        assert!(fs::read_to_string(&gitignore)
+            .unwrap()
+            .ends_with("colony.local.yml\nops.local.yml\n"));
+    }
+
+    #[test]
+    fn test_locations() {
+        let contents = r#"name: shop
+agents:
+  - id: backend
+    role: Backend
+    env:
+      LOG: info
+  - role: Frontend
+layout:
+  windows:
+    - name: main
+      panes:
+        - type: agent
+"#;
+        let locations = locations(contents);
+        assert_eq!(locations.get("name"), Some(&(1, 1)));
+        assert_eq!(locations.get("agents[backend]"), Some(&(3, 5)));
+        assert_eq!(locations.get("agents[backend].role"), Some(&(4, 5)));
+        assert_eq!(locations.get("agents[backend].env.LOG"), Some(&(6, 7)));
+        assert_eq!(locations.get("agents[1].role"), Some(&(7, 5)));
+        assert_eq!(
+            location_of(&locations, "layout.windows[0].panes[0].agent_id"),
+            Some((12, 11))
+        );
+        assert_eq!(location_of(&locations, "executor.enabled"), None);
+    }
+}
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::colony::agent::AgentState;
-use crate::colony::worktree;
-use crate::colony::{Agent, ColonyConfig};
-use crate::error::ColonyResult;
+use crate::colony::multiplexer::{self, Multiplexer, PaneState};
+use crate::colony::{headless, messaging, start, worktree};
+use crate::colony::{Agent, AgentStatus, ColonyConfig};
+use crate::error::{ColonyError, ColonyResult};
 
 /// Manages a colony of agents
 pub struct ColonyController {
@@ -15,6 +17,8 @@ pub struct ColonyController {
     agents: HashMap<String, Agent>,
     /// Colony configuration
     config: ColonyConfig,
+    /// Multiplexer the agents' session runs in
+    multiplexer: Arc<dyn Multiplexer>,
 }
 
 impl ColonyController {
@@ -27,21 +31,48 @@ impl ColonyController {
             })?
             .join(".colony");
 
+        Self::with_root(config, colony_root)
+    }
+
+    /// Create a colony controller rooted at a specific `.colony` directory
+    pub fn with_root(config: ColonyConfig, colony_root: PathBuf) -> ColonyResult<Self> {
         // Create colony directory if it doesn't exist
         fs::create_dir_all(&colony_root)?;
         fs::create_dir_all(colony_root.join("worktrees"))?;
         fs::create_dir_all(colony_root.join("projects"))?;
         fs::create_dir_all(colony_root.join("logs"))?;
 
+        let multiplexer = multiplexer::from_kind(config.multiplexer);
+
         Ok(Self {
             colony_root,
             agents: HashMap::new(),
             config,
+            multiplexer,
         })
     }
 
+    /// Use a different multiplexer backend than the configured one
+    #[cfg(test)]
+    pub fn with_multiplexer(mut self, multiplexer: Arc<dyn Multiplexer>) -> Self {
+        self.multiplexer = multiplexer;
+        self
+    }
+
+    /// Get the multiplexer the agents' session runs in
+    pub fn multiplexer(&self) -> Arc<dyn Multiplexer> {
+        Arc::clone(&self.multiplexer)
+    }
+
     /// Initialize agents from configuration
+    ///
+    /// Agents with `replicas` set are expanded into `<id>-1` to `<id>-N`, both
+    /// here and in the controller's configuration. Replica sets, groups, roles
+    /// and tags are recorded as message groups (see [`ColonyConfig::message_groups`]).
     pub fn initialize_agents(&mut self) -> ColonyResult<()> {
+        messaging::write_groups(&self.colony_root, &self.config.message_groups())?;
+        self.config.agents = self.config.expanded_agents();
+
         for agent_config in &self.config.agents {
             let agent_id = &agent_config.id;
 
@@ -147,23 +178,129 @@ impl ColonyController {
 
     /// Load agent states from disk
     pub fn load_state(&mut self) -> ColonyResult<()> {
+        for state in self.saved_states()? {
+            if let Some(agent) = self.agents.get_mut(&state.id) {
+                agent.status = state.status;
+                agent.pid = state.pid;
+                agent.model = state.model;
+                agent.pane_id = state.pane_id;
+                agent.restarts = state.restarts;
+                agent.last_restart = state.last_restart;
+                agent.applied_config = state.config;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the saved agent states, including agents no longer in the configuration
+    pub fn saved_states(&self) -> ColonyResult<Vec<AgentState>> {
         let state_path = self.colony_root.join("state.json");
 
         if !state_path.exists() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
         let json = fs::read_to_string(state_path)?;
-        let states: Vec<AgentState> = serde_json::from_str(&json)?;
+        Ok(serde_json::from_str(&json)?)
+    }
 
-        for state in states {
-            if let Some(agent) = self.agents.get_mut(&state.id) {
-                agent.status = state.status;
-                agent.pid = state.pid;
+    /// Start a single agent in the running colony session
+    ///
+    /// Fails if the agent is already running. See [`Self::restart_agent`].
+    pub async fn start_agent(&mut self, agent_id: &str) -> ColonyResult<String> {
+        self.launch_agent(agent_id, false).await
+    }
+
+    /// Relaunch a single agent without touching the rest of the session
+    ///
+    /// The agent's startup prompt, settings.json and skills are rebuilt from its
+    /// current configuration, and Claude Code is relaunched in the agent's pane
+    /// (or a new pane in the agents' window if it no longer has one), with
+    /// pipe-pane logging re-enabled. Returns the pane ID.
+    pub async fn restart_agent(&mut self, agent_id: &str) -> ColonyResult<String> {
+        self.launch_agent(agent_id, true).await
+    }
+
+    /// Stop a single agent, leaving an idle shell in its pane so the layout is kept.
+    /// Headless agents, which have a process instead of a pane, are terminated.
+    pub fn stop_agent(&mut self, agent_id: &str) -> ColonyResult<()> {
+        let agent = self
+            .agents
+            .get_mut(agent_id)
+            .ok_or_else(|| ColonyError::Colony(format!("Agent '{}' not found", agent_id)))?;
+
+        if let Some(pid) = agent.pid.filter(|&pid| pid != 0) {
+            if headless::is_alive(pid) {
+                headless::terminate(pid);
             }
         }
 
-        Ok(())
+        if let Some(pane_id) = &agent.pane_id {
+            if self.multiplexer.pane_state(pane_id) != PaneState::Missing {
+                let idle_cmd = format!(
+                    "echo 'Agent {} stopped. Run: colony restart {}'; exec ${{SHELL:-sh}}",
+                    agent_id, agent_id
+                );
+                self.multiplexer.respawn_pane(pane_id, &idle_cmd)?;
+            }
+        }
+
+        agent.set_status(AgentStatus::Idle);
+        agent.pid = None;
+        agent.process = None;
+        self.save_state()
+    }
+
+    async fn launch_agent(&mut self, agent_id: &str, restart: bool) -> ColonyResult<String> {
+        let session_name = self.config.session_name();
+        let mux = Arc::clone(&self.multiplexer);
+        if !mux.session_exists(&session_name) {
+            return Err(ColonyError::Colony(format!(
+                "Colony session '{}' is not running. Start it with 'colony start'",
+                session_name
+            )));
+        }
+
+        let agent = self
+            .agents
+            .get_mut(agent_id)
+            .ok_or_else(|| ColonyError::Colony(format!("Agent '{}' not found", agent_id)))?;
+
+        let pane_state = agent
+            .pane_id
+            .as_deref()
+            .map_or(PaneState::Missing, |pane_id| mux.pane_state(pane_id));
+
+        // A stopped agent keeps an idle shell in its pane, which can be replaced
+        if pane_state == PaneState::Running && agent.status != AgentStatus::Idle && !restart {
+            return Err(ColonyError::Colony(format!(
+                "Agent '{}' is already running. Use 'colony restart {}' to relaunch it",
+                agent_id, agent_id
+            )));
+        }
+
+        start::prepare_agent(agent, &self.config, &self.colony_root).await;
+        let command = start::build_agent_command(agent, &self.config, &self.colony_root)?;
+
+        let pane_id = match (&agent.pane_id, pane_state) {
+            (Some(pane_id), PaneState::Running | PaneState::Dead) => {
+                mux.respawn_pane(pane_id, &command)?;
+                pane_id.clone()
+            }
+            _ => mux.split_window_with_id(&format!("{}:0", session_name), &command)?,
+        };
+
+        let _ = mux.set_title(&pane_id, &format!("Agent: {}", agent_id));
+        let _ = mux.set_remain_on_exit(&pane_id);
+        #[cfg(unix)]
+        let _ = mux.pipe_pane_to_file(&pane_id, &agent.log_path);
+
+        agent.pane_id = Some(pane_id.clone());
+        agent.set_status(AgentStatus::Running);
+        self.save_state()?;
+
+        Ok(pane_id)
     }
 
     /// Remove all worktrees
@@ -183,3 +320,146 @@ impl ColonyController {
         &self.colony_root
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::multiplexer::fake::FakeMultiplexer;
+    use tempfile::tempdir;
+
+    fn test_controller(dir: &Path, fake: &Arc<FakeMultiplexer>) -> ColonyController {
+        let yaml = format!(
+            r#"
+name: test
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    directory: {dir}
+  - id: frontend
+    role: Frontend Engineer
+    focus: UI
+    directory: {dir}
+"#,
+            dir = dir.display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+
+        let mut controller = ColonyController::with_root(config, dir.join(".colony"))
+            .unwrap()
+            .with_multiplexer(fake.clone());
+        controller.initialize_agents().unwrap();
+        controller
+    }
+
+    #[tokio::test]
+    async fn test_start_agent() {
+        let dir = tempdir().unwrap();
+        let fake = Arc::new(FakeMultiplexer::default());
+        let mut controller = test_controller(dir.path(), &fake);
+
+        // No session yet
+        assert!(controller.start_agent("backend").await.is_err());
+
+        fake.create_session("colony-test").unwrap();
+        let pane_id = controller.start_agent("backend").await.unwrap();
+        let pane = fake.pane(&pane_id).unwrap();
+        assert_eq!(pane.commands.len(), 1);
+        assert!(pane.commands[0].contains("claude --model"));
+        assert_eq!(pane.title.as_deref(), Some("Agent: backend"));
+        assert!(pane.remain_on_exit);
+
+        let agent = controller.get_agent("backend").unwrap();
+        assert_eq!(agent.status, AgentStatus::Running);
+        assert_eq!(agent.pane_id.as_deref(), Some(pane_id.as_str()));
+        assert_eq!(
+            controller.get_agent("frontend").unwrap().status,
+            AgentStatus::Idle
+        );
+
+        let err = controller.start_agent("backend").await.unwrap_err();
+        assert!(err.to_string().contains("already running"));
+        assert!(controller.start_agent("ghost").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restart_agent_respawns_its_pane() {
+        let dir = tempdir().unwrap();
+        let fake = Arc::new(FakeMultiplexer::default());
+        let mut controller = test_controller(dir.path(), &fake);
+        fake.create_session("colony-test").unwrap();
+
+        let backend = controller.start_agent("backend").await.unwrap();
+        let frontend = controller.start_agent("frontend").await.unwrap();
+        assert_ne!(backend, frontend);
+
+        fake.set_pane_state(&backend, PaneState::Dead);
+        assert_eq!(controller.restart_agent("backend").await.unwrap(), backend);
+
+        let pane = fake.pane(&backend).unwrap();
+        assert_eq!(pane.state, PaneState::Running);
+        assert_eq!(pane.commands.len(), 2);
+        assert!(pane.commands[1].contains("claude --model"));
+        assert_eq!(fake.pane(&frontend).unwrap().commands.len(), 1);
+
+        // A running agent can be restarted in place too
+        assert_eq!(controller.restart_agent("backend").await.unwrap(), backend);
+        assert_eq!(fake.pane(&backend).unwrap().commands.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stop_agent_returns_to_idle() {
+        let dir = tempdir().unwrap();
+        let fake = Arc::new(FakeMultiplexer::default());
+        let mut controller = test_controller(dir.path(), &fake);
+        fake.create_session("colony-test").unwrap();
+
+        let pane_id = controller.start_agent("backend").await.unwrap();
+        controller.stop_agent("backend").unwrap();
+
+        let pane = fake.pane(&pane_id).unwrap();
+        assert_eq!(pane.state, PaneState::Running);
+        assert!(pane.commands[1].contains("Agent backend stopped"));
+
+        let agent = controller.get_agent("backend").unwrap();
+        assert_eq!(agent.status, AgentStatus::Idle);
+        assert_eq!(agent.pid, None);
+        assert_eq!(agent.pane_id.as_deref(), Some(pane_id.as_str()));
+
+        let saved = controller.saved_states().unwrap();
+        let state = saved.iter().find(|s| s.id == "backend").unwrap();
+        assert_eq!(state.status, AgentStatus::Idle);
+
+        // The idle shell is replaced when the agent is started again
+        assert_eq!(controller.start_agent("backend").await.unwrap(), pane_id);
+        let pane = fake.pane(&pane_id).unwrap();
+        assert_eq!(pane.commands.len(), 3);
+        assert!(pane.commands[2].contains("claude --model"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_headless_agent() {
+        use std::os::unix::process::CommandExt;
+
+        let dir = tempdir().unwrap();
+        let fake = Arc::new(FakeMultiplexer::default());
+        let mut controller = test_controller(dir.path(), &fake);
+
+        // Headless agents lead their own process group and have no pane
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let agent = controller.get_agent_mut("backend").unwrap();
+        agent.pid = Some(child.id());
+        agent.set_status(AgentStatus::Running);
+
+        controller.stop_agent("backend").unwrap();
+        assert!(!child.wait().unwrap().success());
+        let agent = controller.get_agent("backend").unwrap();
+        assert_eq!(agent.status, AgentStatus::Idle);
+        assert_eq!(agent.pid, None);
+    }
+}
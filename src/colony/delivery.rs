@@ -0,0 +1,426 @@
+//! Message delivery
+//!
+//! An agent only sees a message once it runs `./colony_message.sh read`, so a
+//! task or question can sit in its inbox until the next nudge. The delivery
+//! watcher runs alongside the colony (in a background tmux window started by
+//! `colony start`), watches the message store for new messages and types a
+//! short notification into the recipient's pane. Messages that arrive close
+//! together are announced together, and a pane is only typed into once it has
+//! been idle for the agent's quiet period so an agent isn't interrupted
+//! mid-edit.
+
+use chrono::Utc;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::colony::config::DeliveryConfig;
+use crate::colony::messaging::{self, Message, MessageType};
+use crate::colony::nudge::{self, Target};
+use crate::colony::{ColonyConfig, ColonyController};
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+
+/// tmux window the delivery watcher runs in
+pub const DELIVERY_WINDOW: &str = "delivery";
+
+/// How often the watcher checks queued messages against agent panes
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest message excerpt included in a notification
+const EXCERPT_LEN: usize = 80;
+
+/// Per-agent delivery state
+struct DeliveryState {
+    config: DeliveryConfig,
+    /// Messages already queued or announced, and those unread at startup
+    seen: HashSet<String>,
+    /// Messages waiting to be announced
+    pending: Vec<Message>,
+    /// When the first pending message was queued
+    pending_since: Option<Instant>,
+    last_capture: Option<String>,
+    /// When the pane last changed or showed Claude Code working
+    last_activity: Instant,
+}
+
+impl DeliveryState {
+    fn new(config: DeliveryConfig, seen: HashSet<String>, now: Instant) -> Self {
+        Self {
+            config,
+            seen,
+            pending: Vec::new(),
+            pending_since: None,
+            last_capture: None,
+            last_activity: now,
+        }
+    }
+
+    /// Queue unread messages of the configured types that haven't been seen
+    fn queue(&mut self, unread: Vec<Message>, now: Instant) {
+        for message in unread {
+            if self.config.types.contains(&message.message_type)
+                && self.seen.insert(message.id.clone())
+            {
+                self.pending.push(message);
+            }
+        }
+
+        if !self.pending.is_empty() && self.pending_since.is_none() {
+            self.pending_since = Some(now);
+            // Only panes watched for the whole quiet period count as idle
+            self.last_capture = None;
+            self.last_activity = now;
+        }
+    }
+
+    /// Record a capture of the agent's pane
+    fn observe(&mut self, capture: String, now: Instant) {
+        if nudge::is_busy(self.last_capture.as_deref(), &capture) {
+            self.last_activity = now;
+        }
+        self.last_capture = Some(capture);
+    }
+
+    /// Pending messages are due once the batch window has passed and the pane
+    /// has been idle for the quiet period
+    fn is_due(&self, now: Instant) -> bool {
+        let batched = self.pending_since.is_some_and(|since| {
+            now.duration_since(since) >= Duration::from_secs(self.config.batch_window)
+        });
+        let quiet =
+            now.duration_since(self.last_activity) >= Duration::from_secs(self.config.quiet_period);
+        batched && quiet
+    }
+
+    /// Take the pending messages that are still unread
+    fn take(&mut self, unread: &HashSet<String>) -> Vec<Message> {
+        self.pending_since = None;
+        let mut messages = std::mem::take(&mut self.pending);
+        messages.retain(|message| unread.contains(&message.id));
+        messages
+    }
+}
+
+/// Check whether any agent in the configuration has message delivery enabled
+pub fn is_enabled(config: &ColonyConfig) -> bool {
+    config
+        .agents
+        .iter()
+        .any(|agent| config.delivery_config_for(agent).enabled)
+}
+
+/// Run the delivery watcher until interrupted
+pub async fn run() -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    let config = ColonyConfig::load(config_path)?;
+    let mut controller = ColonyController::new(config)?;
+    controller.initialize_agents()?;
+    let mux = controller.multiplexer();
+    let colony_root = controller.colony_root().to_path_buf();
+
+    let started_at = Instant::now();
+    let mut states: HashMap<String, DeliveryState> = HashMap::new();
+    for agent in &controller.config().agents {
+        let config = controller.config().delivery_config_for(agent);
+        if !config.enabled {
+            continue;
+        }
+        // Messages that were already waiting are left to the agent and the nudger
+        let seen = messaging::load_unread_messages(&colony_root, &agent.id)?
+            .into_iter()
+            .map(|message| message.id)
+            .collect();
+        states.insert(
+            agent.id.clone(),
+            DeliveryState::new(config, seen, started_at),
+        );
+    }
+
+    if states.is_empty() {
+        utils::info("Message delivery is not enabled for any agent");
+        return Ok(());
+    }
+
+    // Create the store before watching its directory, which SQLite also
+    // writes journal files to
+    messaging::open_store(&colony_root)?;
+    let store_path = messaging::store_path(&colony_root);
+    let store_dir = store_path.parent().unwrap_or(&colony_root).to_path_buf();
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| ColonyError::Colony(format!("Failed to create message watcher: {}", e)))?;
+    watcher
+        .watch(&store_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| {
+            ColonyError::Colony(format!("Failed to watch {}: {}", store_dir.display(), e))
+        })?;
+
+    utils::header("Colony Message Delivery");
+    for (agent_id, state) in &states {
+        let types: Vec<&str> = state.config.types.iter().map(type_name).collect();
+        println!(
+            "  {} ({}, batched over {}s, after {}s idle)",
+            agent_id,
+            types.join(", "),
+            state.config.batch_window,
+            state.config.quiet_period
+        );
+    }
+    println!();
+
+    loop {
+        // Reload state every tick so restarted agents are picked up in their new panes
+        let _ = controller.load_state();
+        let now = Instant::now();
+
+        let mut changed = false;
+        for event in rx.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    changed |= event
+                        .paths
+                        .iter()
+                        .any(|path| is_store_file(&store_path, path));
+                }
+                Ok(_) => {}
+                Err(e) => utils::warning(&format!("Message watcher error: {}", e)),
+            }
+        }
+
+        for (agent_id, state) in states.iter_mut() {
+            if changed {
+                match messaging::load_unread_messages(&colony_root, agent_id) {
+                    Ok(unread) => state.queue(unread, now),
+                    Err(e) => utils::warning(&format!(
+                        "Failed to load messages for '{}': {}",
+                        agent_id, e
+                    )),
+                }
+            }
+
+            if state.pending.is_empty() {
+                continue;
+            }
+
+            let Some(target) = controller
+                .get_agent(agent_id)
+                .and_then(|agent| Target::of(mux.as_ref(), agent))
+            else {
+                continue;
+            };
+
+            if let Err(e) = check_agent(&colony_root, agent_id, &target, state, now) {
+                utils::warning(&format!(
+                    "Failed to deliver messages to '{}': {}",
+                    agent_id, e
+                ));
+            }
+        }
+
+        tokio::time::sleep(TICK_INTERVAL).await;
+    }
+}
+
+/// Announce an agent's pending messages once they are due
+fn check_agent(
+    colony_root: &Path,
+    agent_id: &str,
+    target: &Target,
+    state: &mut DeliveryState,
+    now: Instant,
+) -> ColonyResult<()> {
+    state.observe(target.capture()?, now);
+    if !state.is_due(now) {
+        return Ok(());
+    }
+
+    // Skip anything the agent read while the messages were queued
+    let unread: HashSet<String> = messaging::load_unread_messages(colony_root, agent_id)?
+        .into_iter()
+        .map(|message| message.id)
+        .collect();
+    let messages = state.take(&unread);
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    target.send(&notification(&messages))?;
+    // The notification itself changes the pane, don't mistake that for activity
+    state.last_capture = target.capture().ok();
+
+    println!(
+        "[{}] Delivered {} message(s) to {}",
+        Utc::now().format("%H:%M:%S"),
+        messages.len(),
+        agent_id
+    );
+
+    Ok(())
+}
+
+/// Whether `path` is the message store or one of SQLite's journal files
+/// for it (`messages.db-wal`, `messages.db-journal`)
+fn is_store_file(store_path: &Path, path: &Path) -> bool {
+    let (Some(store_name), Some(name)) = (store_path.file_name(), path.file_name()) else {
+        return false;
+    };
+    path.parent() == store_path.parent()
+        && name
+            .to_string_lossy()
+            .starts_with(store_name.to_string_lossy().as_ref())
+}
+
+/// Short notification typed into the agent's pane
+fn notification(messages: &[Message]) -> String {
+    if let [message] = messages {
+        let line = message.content.lines().next().unwrap_or_default();
+        let mut excerpt: String = line.chars().take(EXCERPT_LEN).collect();
+        if line.chars().count() > EXCERPT_LEN {
+            excerpt.push_str("...");
+        }
+        return format!(
+            "New {} from {}: \"{}\". Run ./colony_message.sh read to see it.",
+            type_name(&message.message_type),
+            message.from,
+            excerpt
+        );
+    }
+
+    let mut counts: Vec<(&MessageType, usize)> = Vec::new();
+    let mut senders: Vec<&str> = Vec::new();
+    for message in messages {
+        match counts.iter_mut().find(|(t, _)| **t == message.message_type) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&message.message_type, 1)),
+        }
+        if !senders.contains(&message.from.as_str()) {
+            senders.push(&message.from);
+        }
+    }
+
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(message_type, count)| {
+            let plural = if *count == 1 { "" } else { "s" };
+            format!("{} {}{}", count, type_name(message_type), plural)
+        })
+        .collect();
+
+    format!(
+        "{} new messages ({}) from {}. Run ./colony_message.sh read to see them.",
+        messages.len(),
+        counts.join(", "),
+        senders.join(", ")
+    )
+}
+
+fn type_name(message_type: &MessageType) -> &'static str {
+    match message_type {
+        MessageType::Info => "info",
+        MessageType::Task => "task",
+        MessageType::Question => "question",
+        MessageType::Answer => "answer",
+        MessageType::Completed => "completed",
+        MessageType::Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn message(from: &str, content: &str, message_type: MessageType) -> Message {
+        Message::new(from, "frontend", content.to_string(), message_type)
+    }
+
+    #[test]
+    fn test_is_store_file() {
+        let store = PathBuf::from("/repo/.colony/messages.db");
+        assert!(is_store_file(&store, &store));
+        assert!(is_store_file(
+            &store,
+            Path::new("/repo/.colony/messages.db-wal")
+        ));
+        assert!(!is_store_file(
+            &store,
+            Path::new("/repo/.colony/messages.imported-20250101000000")
+        ));
+        assert!(!is_store_file(&store, Path::new("/elsewhere/messages.db")));
+    }
+
+    #[test]
+    fn test_batching_and_quiet_period() {
+        let start = Instant::now();
+        let config = DeliveryConfig {
+            enabled: true,
+            batch_window: 5,
+            quiet_period: 10,
+            ..Default::default()
+        };
+        let mut state = DeliveryState::new(config, HashSet::new(), start);
+
+        // Only the configured types are queued, and each message only once
+        let task = message("backend", "Build the login page", MessageType::Task);
+        let info = message("backend", "FYI", MessageType::Info);
+        state.queue(vec![task.clone(), info], start);
+        state.queue(vec![task.clone()], start);
+        assert_eq!(state.pending.len(), 1);
+
+        let at = |secs| start + Duration::from_secs(secs);
+        state.observe("> ".to_string(), at(1));
+        assert!(!state.is_due(at(1)));
+
+        // The agent keeps working past the batch window
+        state.observe("Editing src/main.rs".to_string(), at(6));
+        assert!(!state.is_due(at(6)));
+        state.observe("Editing src/main.rs".to_string(), at(15));
+        assert!(!state.is_due(at(15)));
+        state.observe("Editing src/main.rs".to_string(), at(16));
+        assert!(state.is_due(at(16)));
+
+        // A question arriving meanwhile goes out in the same batch, unless it was read
+        let question = message("frontend", "Which endpoint?", MessageType::Question);
+        state.queue(vec![question.clone()], at(16));
+        let unread = HashSet::from([task.id.clone()]);
+        let delivered = state.take(&unread);
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].id, task.id);
+        assert!(state.pending.is_empty());
+        assert!(!state.is_due(at(30)));
+    }
+
+    #[test]
+    fn test_notification() {
+        let task = message(
+            "backend",
+            "Build the login page\nDetails...",
+            MessageType::Task,
+        );
+        assert_eq!(
+            notification(std::slice::from_ref(&task)),
+            "New task from backend: \"Build the login page\". Run ./colony_message.sh read to see it."
+        );
+
+        let messages = vec![
+            task,
+            message("backend", "Add tests", MessageType::Task),
+            message("qa", "Which browser?", MessageType::Question),
+        ];
+        assert_eq!(
+            notification(&messages),
+            "3 new messages (2 tasks, 1 question) from backend, qa. Run ./colony_message.sh read to see them."
+        );
+    }
+}
@@ -1,12 +1,10 @@
-use std::path::Path;
-
 use crate::colony::{ColonyConfig, ColonyController};
 use crate::error::ColonyResult;
 use crate::utils;
 
 /// Destroy the colony and clean up all resources
 pub async fn run() -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
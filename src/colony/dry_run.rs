@@ -0,0 +1,181 @@
+//! `colony start --dry-run`
+//!
+//! Renders everything `colony start` would hand to Claude Code, so a bad MCP
+//! config or prompt can be debugged without starting the colony. Nothing is
+//! run in tmux or git; the output goes to `.colony/dry-run/`.
+
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::colony::{layout, start, worktree, ColonyController};
+use crate::error::ColonyResult;
+use crate::utils;
+
+/// Directory under the colony root the dry run writes to
+pub const DRY_RUN_DIR: &str = "dry-run";
+
+/// Render each agent's launch command, startup prompt and settings.json, and
+/// print the worktrees and pane map `colony start` would create
+pub async fn run(controller: &ColonyController) -> ColonyResult<()> {
+    let config = controller.config();
+    let out_dir = controller.colony_root().join(DRY_RUN_DIR);
+
+    // Start clean so agents removed from the config don't leave stale files behind
+    if out_dir.exists() {
+        fs::remove_dir_all(&out_dir)?;
+    }
+    fs::create_dir_all(&out_dir)?;
+
+    utils::header("Colony Dry Run");
+
+    for agent_config in &config.agents {
+        let Some(agent) = controller.get_agent(&agent_config.id) else {
+            continue;
+        };
+        let agent_dir = out_dir.join(agent.id());
+        fs::create_dir_all(&agent_dir)?;
+
+        let command = start::agent_command(agent, config, controller.colony_root())?;
+        fs::write(agent_dir.join("command.sh"), format!("{}\n", command))?;
+
+        let prompt = start::render_startup_prompt(agent, config, controller.colony_root())?;
+        fs::write(agent_dir.join("startup_prompt.txt"), prompt)?;
+
+        let mut files = vec!["command.sh", "startup_prompt.txt"];
+
+        // Secrets are checked but stay unresolved in files written to disk
+        let env_vars = start::agent_env(agent, true)?;
+        if !env_vars.is_empty() {
+            fs::write(
+                agent_dir.join(start::ENV_FILE),
+                start::env_file_contents(&env_vars),
+            )?;
+            files.push(start::ENV_FILE);
+        }
+        if agent.config.has_mcp_servers() {
+            let settings = start::merged_agent_settings(agent).await?;
+            fs::write(
+                agent_dir.join("settings.json"),
+                serde_json::to_string_pretty(&settings)?,
+            )?;
+            files.push("settings.json");
+        }
+
+        utils::success(&format!("{}: {}", agent.id(), files.join(", ")));
+    }
+
+    let summary = summary(controller);
+    fs::write(out_dir.join("summary.txt"), &summary)?;
+    println!("\n{}", summary);
+
+    utils::info(&format!("Wrote dry run output to {}", out_dir.display()));
+    Ok(())
+}
+
+/// Worktree paths and branches, and where each pane goes
+fn summary(controller: &ColonyController) -> String {
+    let config = controller.config();
+    let width = config.agents.iter().map(|a| a.id.len()).max().unwrap_or(0);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Worktrees:");
+    for agent_config in &config.agents {
+        let Some(agent) = controller.get_agent(&agent_config.id) else {
+            continue;
+        };
+        let checkout = if agent.config.uses_custom_directory() {
+            "custom directory".to_string()
+        } else {
+            format!(
+                "branch {}",
+                worktree::branch_name(agent.config.worktree_name())
+            )
+        };
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {} ({})",
+            agent.id(),
+            agent.worktree_path.display(),
+            checkout,
+            width = width
+        );
+    }
+
+    let mux = controller.multiplexer();
+    let _ = writeln!(
+        out,
+        "\nPanes ({} session '{}'):",
+        mux.name(),
+        config.session_name()
+    );
+    for (key, (window, pane)) in layout::planned_pane_map(config, mux.supports_custom_layouts()) {
+        let _ = writeln!(out, "  {}.{}  {}", window, pane, key);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::ColonyConfig;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let dir = tempdir().unwrap();
+        let yaml = format!(
+            r#"
+name: test
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    directory: {dir}
+    mcp_servers:
+      filesystem:
+        command: npx
+        args: [-y, "@modelcontextprotocol/server-filesystem"]
+  - id: frontend
+    role: Frontend Engineer
+    focus: UI
+"#,
+            dir = dir.path().display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+        let colony_root = dir.path().join(".colony");
+        let mut controller = ColonyController::with_root(config, colony_root.clone()).unwrap();
+        controller.initialize_agents().unwrap();
+
+        run(&controller).await.unwrap();
+
+        let out_dir = colony_root.join(DRY_RUN_DIR);
+        let backend = out_dir.join("backend");
+        let command = fs::read_to_string(backend.join("command.sh")).unwrap();
+        assert!(command.contains("--mcp-config"));
+        let prompt = fs::read_to_string(backend.join("startup_prompt.txt")).unwrap();
+        assert!(prompt.contains("**Focus**: API"));
+        // The rendered prompt is what Claude is launched with
+        assert!(command.contains("--append-system-prompt '# Welcome to Colony"));
+        assert!(command.contains("**Focus**: API"));
+        let settings: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(backend.join("settings.json")).unwrap())
+                .unwrap();
+        assert!(settings["mcpServers"]["filesystem"].is_object());
+        assert!(!out_dir.join("frontend/settings.json").exists());
+
+        let summary = fs::read_to_string(out_dir.join("summary.txt")).unwrap();
+        assert!(summary.contains("(branch colony/frontend)"));
+        assert!(summary.contains("  0.1  frontend"));
+        assert!(summary.contains("  0.2  tui"));
+
+        // Nothing is prepared for launch
+        assert!(fs::read_dir(colony_root.join("worktrees"))
+            .unwrap()
+            .next()
+            .is_none());
+        assert!(!colony_root
+            .join("projects/backend/startup_prompt.txt")
+            .exists());
+    }
+}
@@ -332,7 +332,7 @@ Use appropriate message types when responding:
 
 Your execution history is tracked in:
 - `.colony/logs/{}.log` - Your output log
-- `.colony/messages/{}/sent/` - Your sent messages
+- `./colony_message.sh sent` - Your sent messages
 - Colony TUI - Executor tab (press 5)
 
 ## Troubleshooting
@@ -388,7 +388,6 @@ For detailed MCP executor skill documentation, see:
             .collect::<Vec<_>>()
             .join("\n"),
         executor_id,
-        executor_id,
     )
 }
 
@@ -397,7 +396,10 @@ pub fn create_executor_submit_script(
     colony_root: &Path,
     executor_id: &str,
 ) -> ColonyResult<String> {
-    let messages_dir = colony_root.join("messages");
+    // Tasks go through the message store, via the binary that started the colony
+    let colony_bin = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "colony".to_string());
     let script_content = format!(
         r#"#!/usr/bin/env bash
 # MCP Executor Task Submission Script
@@ -406,9 +408,19 @@ pub fn create_executor_submit_script(
 set -euo pipefail
 
 EXECUTOR_ID="{executor_id}"
-MESSAGES_DIR="{messages_dir}"
+COLONY_ROOT="{colony_root}"
 AGENT_ID="${{COLONY_AGENT_ID:-unknown}}"
 
+if [[ -z "${{COLONY_BIN:-}}" ]]; then
+    COLONY_BIN="{colony_bin}"
+    [[ -x "$COLONY_BIN" ]] || COLONY_BIN=colony
+fi
+
+# Run `colony messages` from the directory the colony was started in
+colony_messages() {{
+    (cd "$COLONY_ROOT/.." && "$COLONY_BIN" messages "$@")
+}}
+
 usage() {{
     cat <<EOF
 Usage: $0 <command> [options]
@@ -452,57 +464,28 @@ submit_task() {{
         exit 1
     fi
 
-    # Create task message
-    local timestamp=$(date -u +"%Y-%m-%dT%H:%M:%SZ")
-    local msg_id="${{AGENT_ID}}-$(date +%s%N)"
-    local executor_inbox="${{MESSAGES_DIR}}/${{EXECUTOR_ID}}"
-
-    mkdir -p "$executor_inbox"
-
-    # Create JSON message
-    cat > "${{executor_inbox}}/${{msg_id}}.json" <<EOJSON
-{{
-  "id": "$msg_id",
-  "from": "$AGENT_ID",
-  "to": "$EXECUTOR_ID",
-  "content": "Execute MCP workflow: $description\\n\\nPattern: $pattern\\nLanguage: $language",
-  "timestamp": "$timestamp",
-  "message_type": "task"
-}}
-EOJSON
+    # Send the task to the executor
+    colony_messages send --from "$AGENT_ID" --type task --project-dir "$PWD" -- \
+        "$EXECUTOR_ID" "Execute MCP workflow: $description
+
+Pattern: $pattern
+Language: $language" >/dev/null
 
     echo "✓ Task submitted to executor: $description"
     echo "  Pattern: $pattern"
     echo "  Language: $language"
-    echo "  Message ID: $msg_id"
     echo ""
     echo "Use './colony_message.sh read' to monitor for responses from the executor."
 }}
 
 check_status() {{
-    local executor_inbox="${{MESSAGES_DIR}}/${{EXECUTOR_ID}}"
-    local queue_depth=0
-
-    if [[ -d "$executor_inbox" ]]; then
-        queue_depth=$(find "$executor_inbox" -maxdepth 1 -type f -name "*.json" | wc -l)
-    fi
-
     echo "MCP Executor Status"
     echo "==================="
     echo "Executor ID: $EXECUTOR_ID"
-    echo "Queue depth: $queue_depth pending tasks"
     echo ""
 
-    if [[ $queue_depth -gt 0 ]]; then
-        echo "Pending tasks:"
-        find "$executor_inbox" -maxdepth 1 -type f -name "*.json" | while read -r file; do
-            local from=$(jq -r '.from // "unknown"' "$file" 2>/dev/null || echo "unknown")
-            local content=$(jq -r '.content // "No description"' "$file" 2>/dev/null || echo "No description")
-            echo "  - From $from: ${{content:0:60}}..."
-        done
-    else
-        echo "No pending tasks. Executor is idle."
-    fi
+    # Tasks the executor hasn't picked up yet are marked [UNREAD]
+    colony_messages list "$EXECUTOR_ID"
 }}
 
 # Main command dispatcher
@@ -530,7 +513,8 @@ case "${{1:-}}" in
 esac
 "#,
         executor_id = executor_id,
-        messages_dir = messages_dir.display()
+        colony_root = colony_root.display(),
+        colony_bin = colony_bin
     );
 
     Ok(script_content)
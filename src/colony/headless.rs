@@ -0,0 +1,476 @@
+//! Headless agent backend
+//!
+//! Runs each agent's Claude Code command as a child process on its own
+//! pseudo-terminal instead of in a tmux pane, for CI runners and servers with no
+//! terminal to attach to. Everything the agent prints goes straight to its log
+//! file, and prompts are written to the PTY through a FIFO in the agent's project
+//! directory, so any colony process can reach the agent.
+//!
+//! `colony start --headless` stays in the foreground as the host for the agents:
+//! it records their PIDs in the agent state (so `status`, `stop` and `logs` work
+//! as they do with tmux), updates their status as they exit, and stops them all
+//! on Ctrl+C.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::colony::{delivery, nudge, start, Agent, AgentStatus, ColonyController};
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+
+/// Name of the FIFO in an agent's project directory that feeds its PTY
+const INPUT_FIFO: &str = "pty_input";
+
+/// Size of the PTY agents run on
+const PTY_ROWS: u16 = 50;
+const PTY_COLS: u16 = 200;
+
+/// Pause between typing a prompt and submitting it, so Claude Code doesn't
+/// treat the Enter as part of a paste
+const SUBMIT_DELAY: Duration = Duration::from_millis(200);
+
+/// How often the host checks whether agents have exited
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long agents get to exit after SIGTERM before they are killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Amount of log output returned by [`capture`]
+const CAPTURE_BYTES: u64 = 4096;
+
+/// Path of the FIFO that feeds an agent's PTY
+pub fn input_path(agent: &Agent) -> PathBuf {
+    agent.project_path.join(INPUT_FIFO)
+}
+
+/// Type a prompt into a headless agent's PTY and submit it
+pub fn send_text(agent: &Agent, text: &str) -> ColonyResult<()> {
+    send_input(&input_path(agent), text).map_err(|e| {
+        ColonyError::Colony(format!(
+            "Failed to send input to headless agent '{}': {}",
+            agent.id(),
+            e
+        ))
+    })
+}
+
+/// Recent output of a headless agent, taken from the end of its log file
+pub fn capture(agent: &Agent) -> ColonyResult<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(&agent.log_path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(CAPTURE_BYTES)))?;
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Launch every agent headless and host them until they exit or Ctrl+C is pressed
+pub async fn run(mut controller: ColonyController) -> ColonyResult<()> {
+    if let Some(agent) = controller
+        .agents()
+        .values()
+        .find(|a| a.is_running() && a.pid.is_some_and(is_alive))
+    {
+        return Err(ColonyError::Colony(format!(
+            "Agent '{}' is already running headless (PID {}). Stop it with 'colony stop'",
+            agent.id(),
+            agent.pid.unwrap_or_default()
+        )));
+    }
+
+    if controller
+        .config()
+        .executor
+        .as_ref()
+        .is_some_and(|e| e.enabled)
+    {
+        utils::warning("The MCP Executor is not started in headless mode");
+    }
+
+    let config = controller.config().clone();
+    let colony_root = controller.colony_root().to_path_buf();
+
+    let mut agent_ids: Vec<String> = controller.agents().keys().cloned().collect();
+    agent_ids.sort();
+
+    for agent_id in &agent_ids {
+        let agent = controller
+            .agents_mut()
+            .get_mut(agent_id)
+            .expect("Agent ID should always exist in agents_mut; collected from agents().keys()");
+
+        utils::info(&format!("Starting agent: {}", agent.id()));
+        println!("  Role:  {}", agent.config.role);
+        println!("  Focus: {}", agent.config.focus);
+        println!("  Model: {}", agent.config.model);
+
+        start::prepare_agent(agent, &config, &colony_root).await;
+        let command = start::build_agent_command(agent, &config, &colony_root)?;
+
+        match spawn_agent(agent, &command) {
+            Ok(pid) => {
+                agent.restarts = 0;
+                agent.last_restart = None;
+                utils::success(&format!("  Started agent '{}' (PID: {})", agent.id(), pid));
+            }
+            Err(e) => {
+                agent.set_status(AgentStatus::Failed);
+                utils::warning(&format!("  Failed to start agent '{}': {}", agent.id(), e));
+            }
+        }
+        println!();
+    }
+
+    controller.save_state()?;
+
+    if nudge::is_enabled(controller.config()) {
+        tokio::spawn(async {
+            if let Err(e) = nudge::run().await {
+                utils::warning(&format!("Nudge scheduler stopped: {}", e));
+            }
+        });
+    }
+
+    if delivery::is_enabled(controller.config()) {
+        tokio::spawn(async {
+            if let Err(e) = delivery::run().await {
+                utils::warning(&format!("Message delivery stopped: {}", e));
+            }
+        });
+    }
+
+    utils::header("Colony Started (headless)");
+    let running_count = controller
+        .agents()
+        .values()
+        .filter(|a| a.process.is_some())
+        .count();
+    println!(
+        "Running agents: {}/{}",
+        running_count,
+        controller.agents().len()
+    );
+    println!("\nAgents run until they exit or you press Ctrl+C.");
+    println!("  • Use 'colony status' to check agent status from another shell");
+    println!("  • Use 'colony logs <agent-id>' to view agent output");
+    println!("  • Use 'colony stop [agent-id]' to stop agents");
+    println!();
+
+    host_agents(&mut controller).await
+}
+
+/// Start an agent's launch command on a new PTY and record the process on the agent
+fn spawn_agent(agent: &mut Agent, command: &str) -> ColonyResult<u32> {
+    if let Some(parent) = agent.log_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let child = spawn(command, &agent.log_path, &input_path(agent))?;
+    let pid = child
+        .id()
+        .ok_or_else(|| ColonyError::Colony("Agent process exited immediately".to_string()))?;
+
+    agent.process = Some(child);
+    agent.pid = Some(pid);
+    agent.pane_id = None;
+    agent.set_status(AgentStatus::Running);
+
+    Ok(pid)
+}
+
+/// Wait for hosted agents to exit, keeping the agent state up to date
+async fn host_agents(controller: &mut ColonyController) -> ColonyResult<()> {
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                utils::info("Stopping headless agents...");
+                stop_all(controller).await;
+                controller.save_state()?;
+                utils::success("Stopped agents");
+                return Ok(());
+            }
+            _ = ticker.tick() => {}
+        }
+
+        let exited: Vec<(String, std::process::ExitStatus)> = controller
+            .agents_mut()
+            .iter_mut()
+            .filter_map(|(id, agent)| {
+                let status = agent.process.as_mut()?.try_wait().ok()??;
+                Some((id.clone(), status))
+            })
+            .collect();
+
+        if !exited.is_empty() {
+            // Pick up agents stopped with 'colony stop' so they aren't reported as failed
+            let _ = controller.load_state();
+
+            for (agent_id, exit_status) in exited {
+                let Some(agent) = controller.get_agent_mut(&agent_id) else {
+                    continue;
+                };
+                agent.process = None;
+                agent.pid = None;
+
+                if !agent.is_running() {
+                    utils::info(&format!("Agent '{}' stopped", agent_id));
+                } else if exit_status.success() {
+                    agent.set_status(AgentStatus::Completed);
+                    utils::success(&format!("Agent '{}' exited", agent_id));
+                } else {
+                    agent.set_status(AgentStatus::Failed);
+                    utils::warning(&format!("Agent '{}' exited with {}", agent_id, exit_status));
+                }
+            }
+
+            controller.save_state()?;
+        }
+
+        if controller.agents().values().all(|a| a.process.is_none()) {
+            utils::info("All agents have exited");
+            return Ok(());
+        }
+    }
+}
+
+/// Terminate every hosted agent, killing any that don't exit in time
+async fn stop_all(controller: &mut ColonyController) {
+    for agent in controller.agents_mut().values_mut() {
+        if let Some(pid) = agent.pid {
+            terminate(pid);
+        }
+    }
+
+    for agent in controller.agents_mut().values_mut() {
+        if let Some(mut child) = agent.process.take() {
+            if tokio::time::timeout(STOP_TIMEOUT, child.wait())
+                .await
+                .is_err()
+            {
+                if let Some(pid) = agent.pid {
+                    kill(pid);
+                }
+                let _ = child.wait().await;
+            }
+        }
+        if agent.pid.take().is_some() {
+            agent.set_status(AgentStatus::Idle);
+        }
+    }
+}
+
+/// Run `command` under the user's shell on a new PTY
+///
+/// The process leads its own session with the PTY as its controlling terminal,
+/// so signalling its process group reaches Claude Code as well as the shell.
+/// Output is appended to `log_path` and anything written to the FIFO at
+/// `input_path` (created if needed) is typed into the PTY.
+#[cfg(unix)]
+fn spawn(command: &str, log_path: &Path, input_path: &Path) -> ColonyResult<tokio::process::Child> {
+    use nix::pty::{openpty, Winsize};
+    use std::fs::{File, OpenOptions};
+    use std::os::unix::io::FromRawFd;
+    use std::process::Stdio;
+
+    let winsize = Winsize {
+        ws_row: PTY_ROWS,
+        ws_col: PTY_COLS,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let pty = openpty(Some(&winsize), None)
+        .map_err(|e| ColonyError::Colony(format!("Failed to open a PTY: {}", e)))?;
+    // Take ownership so both ends are closed on every return path
+    let master = unsafe { File::from_raw_fd(pty.master) };
+    let slave = unsafe { File::from_raw_fd(pty.slave) };
+    let master_fd = pty.master;
+
+    create_fifo(input_path)?;
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let mut cmd = tokio::process::Command::new(shell);
+    cmd.arg("-c")
+        .arg(command)
+        .env("TERM", "xterm-256color")
+        .stdin(Stdio::from(slave.try_clone()?))
+        .stdout(Stdio::from(slave.try_clone()?))
+        .stderr(Stdio::from(slave));
+
+    // SAFETY: only async-signal-safe calls are made between fork and exec
+    unsafe {
+        cmd.pre_exec(move || {
+            nix::unistd::setsid()?;
+            if nix::libc::ioctl(0, nix::libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            nix::libc::close(master_fd);
+            Ok(())
+        });
+    }
+
+    let child = cmd.spawn()?;
+
+    let mut output = master.try_clone()?;
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    std::thread::spawn(move || {
+        // Ends with EIO once the agent exits and the PTY closes
+        let _ = std::io::copy(&mut output, &mut log);
+    });
+
+    // Opened read-write so the FIFO never reports EOF between writers
+    let mut input = OpenOptions::new().read(true).write(true).open(input_path)?;
+    let mut pty_input = master;
+    std::thread::spawn(move || {
+        let _ = std::io::copy(&mut input, &mut pty_input);
+    });
+
+    Ok(child)
+}
+
+#[cfg(not(unix))]
+fn spawn(
+    _command: &str,
+    _log_path: &Path,
+    _input_path: &Path,
+) -> ColonyResult<tokio::process::Child> {
+    Err(ColonyError::Colony(
+        "Headless mode is only supported on Unix".to_string(),
+    ))
+}
+
+/// Create the input FIFO, replacing any regular file left at its path
+#[cfg(unix)]
+fn create_fifo(path: &Path) -> ColonyResult<()> {
+    use nix::sys::stat::Mode;
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.file_type().is_fifo() {
+            return Ok(());
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    nix::unistd::mkfifo(path, Mode::S_IRUSR | Mode::S_IWUSR).map_err(|e| {
+        ColonyError::Colony(format!(
+            "Failed to create input FIFO {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Write a prompt to an agent's input FIFO, then submit it with Enter
+#[cfg(unix)]
+fn send_input(input_path: &Path, text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    // Non-blocking so this fails (ENXIO) instead of hanging when no host is reading
+    let mut fifo = std::fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(nix::libc::O_NONBLOCK)
+        .open(input_path)?;
+    fifo.write_all(text.as_bytes())?;
+    std::thread::sleep(SUBMIT_DELAY);
+    fifo.write_all(b"\r")
+}
+
+#[cfg(not(unix))]
+fn send_input(_input_path: &Path, _text: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "headless mode is only supported on Unix",
+    ))
+}
+
+/// Check whether a process exists
+#[cfg(unix)]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid as i32), None).is_ok()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Ask an agent's process group to exit
+#[cfg(unix)]
+pub(crate) fn terminate(pid: u32) {
+    use nix::sys::signal::{killpg, Signal};
+    let _ = killpg(nix::unistd::Pid::from_raw(pid as i32), Signal::SIGTERM);
+}
+
+#[cfg(not(unix))]
+pub(crate) fn terminate(_pid: u32) {}
+
+/// Kill an agent's process group
+#[cfg(unix)]
+fn kill(pid: u32) {
+    use nix::sys::signal::{killpg, Signal};
+    let _ = killpg(nix::unistd::Pid::from_raw(pid as i32), Signal::SIGKILL);
+}
+
+#[cfg(not(unix))]
+fn kill(_pid: u32) {}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_spawn_captures_output_and_accepts_input() {
+        let dir = tempdir().unwrap();
+        let log_path = dir.path().join("agent.log");
+        let input = dir.path().join(INPUT_FIFO);
+
+        let mut child = spawn(
+            "test -t 0 && echo on-a-tty; read line; echo \"got:$line\"",
+            &log_path,
+            &input,
+        )
+        .unwrap();
+
+        send_input(&input, "hello").unwrap();
+        let status = tokio::time::timeout(Duration::from_secs(10), child.wait())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status.success());
+
+        // Give the output thread a moment to drain the PTY
+        let mut log = String::new();
+        for _ in 0..50 {
+            log = std::fs::read_to_string(&log_path).unwrap_or_default();
+            if log.contains("got:hello") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(log.contains("on-a-tty"));
+        assert!(log.contains("got:hello"));
+    }
+
+    #[test]
+    fn test_send_input_without_host_fails() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join(INPUT_FIFO);
+        create_fifo(&input).unwrap();
+
+        assert!(send_input(&input, "hello").is_err());
+    }
+}
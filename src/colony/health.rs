@@ -1,5 +1,6 @@
 //! Health check command for Colony system
 
+use crate::colony::messaging;
 use crate::colony::ColonyConfig;
 use crate::error::{ColonyError, ColonyResult};
 use crate::utils;
@@ -102,7 +103,7 @@ pub async fn run() -> ColonyResult<()> {
     match check_message_queue() {
         Ok(count) => {
             if count > 0 {
-                println!("{}", format!("✓ OK ({} messages pending)", count).green());
+                println!("{}", format!("✓ OK ({} messages)", count).green());
             } else {
                 println!("{}", "✓ OK (empty)".green());
             }
@@ -127,7 +128,7 @@ pub async fn run() -> ColonyResult<()> {
 
 /// Check colony configuration
 async fn check_config() -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(ColonyError::Colony("colony.yml not found".to_string()));
@@ -149,14 +150,6 @@ fn check_colony_directory() -> ColonyResult<()> {
         ));
     }
 
-    // Check for essential subdirectories
-    let messages_dir = colony_root.join("messages");
-    if !messages_dir.exists() {
-        return Err(ColonyError::Colony(
-            "messages directory missing".to_string(),
-        ));
-    }
-
     Ok(())
 }
 
@@ -210,7 +203,7 @@ fn check_tmux() -> ColonyResult<()> {
 
 /// Check shared state system
 async fn check_shared_state() -> Result<Option<String>, String> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Ok(None);
@@ -250,7 +243,7 @@ async fn check_shared_state() -> Result<Option<String>, String> {
 
 /// Check agent state
 fn check_agent_state() -> Result<(usize, usize), String> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err("No configuration".to_string());
@@ -284,25 +277,13 @@ fn check_agent_state() -> Result<(usize, usize), String> {
 
 /// Check message queue
 fn check_message_queue() -> Result<usize, String> {
-    let messages_dir = Path::new(".colony/messages");
-
-    if !messages_dir.exists() {
-        return Err("Messages directory not found".to_string());
-    }
-
-    // Count pending messages across all inboxes
-    let mut total = 0;
+    let colony_root = Path::new(".colony");
 
-    if let Ok(entries) = std::fs::read_dir(messages_dir) {
-        for entry in entries.flatten() {
-            if entry.file_type().ok().is_some_and(|t| t.is_dir()) {
-                let inbox_path = entry.path();
-                if let Ok(messages) = std::fs::read_dir(&inbox_path) {
-                    total += messages.count();
-                }
-            }
-        }
+    if !colony_root.exists() {
+        return Err("Colony directory not found".to_string());
     }
 
-    Ok(total)
+    messaging::load_all_messages(colony_root)
+        .map(|messages| messages.len())
+        .map_err(|e| format!("Failed to open message store: {}", e))
 }
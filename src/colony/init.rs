@@ -1,17 +1,24 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::colony::config::{AgentConfig, ColonyConfig, ExecutorConfig};
+use crate::colony::blueprint;
+use crate::colony::config::{
+    AgentConfig, BehaviorConfig, BehaviorLevel, ColonyConfig, CommunicationStyle, ExecutorConfig,
+    TelemetryConfig,
+};
+use crate::colony::config_layers;
 use crate::colony::controller::ColonyController;
 use crate::colony::mcp_registry::McpRegistry;
 use crate::colony::tasks::queue::TaskQueue;
+use crate::colony::workflow::WorkflowStorage;
 use crate::colony::worktree;
 use crate::error::ColonyResult;
 use crate::utils;
 use colored::Colorize;
 
-/// Initialize a new colony configuration with interactive wizard
-pub async fn run() -> ColonyResult<()> {
+/// Initialize a new colony configuration with interactive wizard, or from a
+/// blueprint when one is given
+pub async fn run(blueprint: Option<String>) -> ColonyResult<()> {
     // Check if we're in a Git repository
     if !worktree::is_git_repo() {
         return Err(crate::error::ColonyError::Colony(
@@ -19,7 +26,7 @@ pub async fn run() -> ColonyResult<()> {
         ));
     }
 
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     // Check if config already exists
     if config_path.exists() {
@@ -30,6 +37,10 @@ pub async fn run() -> ColonyResult<()> {
         }
     }
 
+    if let Some(source) = blueprint {
+        return run_blueprint(&source, config_path);
+    }
+
     println!();
     println!("{}", "Colony Configuration Wizard".bold().green());
     println!("{}", "─".repeat(50));
@@ -52,7 +63,83 @@ pub async fn run() -> ColonyResult<()> {
     println!();
     utils::success("Created colony.yml");
     utils::success("Initialized task queue directories");
+    ignore_local_overlay(config_path);
+
+    print_next_steps(config_path)
+}
+
+/// Expand a blueprint into colony.yml, workflow definitions and starter tasks,
+/// asking for the blueprint's parameters
+fn run_blueprint(source: &str, config_path: &Path) -> ColonyResult<()> {
+    let repo_root = Path::new("");
+    let blueprint = blueprint::load_blueprint(source, repo_root)?;
+
+    println!();
+    println!(
+        "{}",
+        format!("Blueprint: {} {}", blueprint.name, blueprint.version)
+            .bold()
+            .green()
+    );
+    println!("{}", "─".repeat(50));
+    if let Some(description) = &blueprint.description {
+        println!("{}", description);
+    }
+    println!();
+
+    let mut values = HashMap::new();
+    for parameter in &blueprint.parameters {
+        let label = parameter.description.as_deref().unwrap_or(&parameter.name);
+        let value = utils::prompt(label, parameter.default.as_deref()).ok_or_else(|| {
+            crate::error::ColonyError::Colony("Initialization cancelled".to_string())
+        })?;
+        values.insert(parameter.name.clone(), value);
+    }
+
+    let expanded = blueprint::expand(&blueprint, &values, repo_root)?;
+    std::fs::write(config_path, serde_yaml::to_string(&expanded.colony_yaml)?)?;
+
+    let controller = ColonyController::new(expanded.config)?;
+    let task_queue = TaskQueue::new(controller.colony_root());
+    task_queue.initialize()?;
+    for task in &expanded.tasks {
+        task_queue.create_task(task.clone())?;
+    }
+
+    let storage = WorkflowStorage::new(controller.colony_root());
+    storage.initialize()?;
+    for workflow in &expanded.workflows {
+        storage.save_workflow(workflow)?;
+    }
 
+    println!();
+    utils::success(&format!(
+        "Created colony.yml from blueprint '{}'",
+        blueprint.name
+    ));
+    utils::success(&format!(
+        "Added {} workflow(s) to .colony/workflows/",
+        expanded.workflows.len()
+    ));
+    utils::success(&format!("Added {} starter task(s)", expanded.tasks.len()));
+    ignore_local_overlay(config_path);
+
+    print_next_steps(config_path)
+}
+
+/// Keep the personal `colony.local.yml` overlay out of git
+fn ignore_local_overlay(config_path: &Path) {
+    match config_layers::ignore_local_overlay(config_path) {
+        Ok(true) => utils::success(&format!(
+            "Added {} to .gitignore",
+            config_layers::overlay_path(config_path, "local").display()
+        )),
+        Ok(false) => {}
+        Err(e) => utils::warning(&format!("Failed to update .gitignore: {}", e)),
+    }
+}
+
+fn print_next_steps(config_path: &Path) -> ColonyResult<()> {
     println!();
     utils::info("Next steps:");
     println!("  1. Run {} to spawn agents", "colony start".cyan());
@@ -234,20 +321,22 @@ fn run_wizard() -> ColonyResult<ColonyConfig> {
             default_model
         };
 
-        agents.push(AgentConfig {
-            id: agent_id.clone(),
+        let behavior = if utils::confirm(
+            "Set how this agent works? (initiative, communication style, thoroughness)",
+        ) {
+            Some(prompt_behavior())
+        } else {
+            None
+        };
+
+        agents.push(wizard_agent(
+            agent_id,
             role,
             focus,
-            model: model.to_string(),
-            directory: None,
-            worktree: Some(format!("agent/{}", agent_id)),
-            env: None,
-            mcp_servers: None,
-            instructions: None,
+            model,
             startup_prompt,
-            capabilities: None,
-            nudge: None,
-        });
+            behavior,
+        ));
     }
 
     // Colony name (optional)
@@ -279,7 +368,7 @@ fn run_wizard() -> ColonyResult<ColonyConfig> {
 
     let enable_telemetry = utils::confirm("Enable telemetry to help improve Colony?");
 
-    let mut telemetry = crate::colony::config::TelemetryConfig::default();
+    let mut telemetry = TelemetryConfig::default();
     if enable_telemetry {
         telemetry.enabled = true;
         telemetry.get_or_create_anonymous_id();
@@ -288,7 +377,49 @@ fn run_wizard() -> ColonyResult<ColonyConfig> {
         utils::info("Telemetry disabled (you can enable it anytime in colony.yml)");
     }
 
-    Ok(ColonyConfig {
+    Ok(wizard_config(name, agents, telemetry))
+}
+
+/// Agent configuration the custom setup wizard writes for each agent
+fn wizard_agent(
+    agent_id: String,
+    role: String,
+    focus: String,
+    model: &str,
+    startup_prompt: Option<String>,
+    behavior: Option<BehaviorConfig>,
+) -> AgentConfig {
+    AgentConfig {
+        id: agent_id.clone(),
+        role,
+        focus,
+        model: model.to_string(),
+        fallback_models: Vec::new(),
+        directory: None,
+        worktree: Some(format!("agent/{}", agent_id)),
+        env: None,
+        mcp_servers: None,
+        instructions: None,
+        startup_prompt,
+        capabilities: None,
+        nudge: None,
+        delivery: None,
+        template: None,
+        template_params: None,
+        behavior,
+        replicas: None,
+        tags: Vec::new(),
+        replica_of: None,
+    }
+}
+
+/// Colony configuration the custom setup wizard writes
+fn wizard_config(
+    name: Option<String>,
+    agents: Vec<AgentConfig>,
+    telemetry: TelemetryConfig,
+) -> ColonyConfig {
+    ColonyConfig {
         name,
         repository: None,
         agents,
@@ -298,7 +429,31 @@ fn run_wizard() -> ColonyResult<ColonyConfig> {
         telemetry,
         capabilities: None,
         layout: None,
-    })
+        multiplexer: Default::default(),
+        groups: Default::default(),
+        repo_root: Default::default(),
+    }
+}
+
+/// Ask for an agent's behavior levels, each of which can be left unset
+fn prompt_behavior() -> BehaviorConfig {
+    fn choose<T: Copy + std::fmt::Display>(label: &str, levels: &[T]) -> Option<T> {
+        let mut options: Vec<String> = levels.iter().map(|l| l.to_string()).collect();
+        options.push("not set".to_string());
+        utils::select(label, &options).and_then(|idx| levels.get(idx).copied())
+    }
+
+    BehaviorConfig {
+        initiative_level: choose(
+            "Initiative (low asks before large changes, high acts on its own)",
+            &BehaviorLevel::ALL,
+        ),
+        communication_style: choose("Communication style", &CommunicationStyle::ALL),
+        thoroughness: choose(
+            "Thoroughness (low favours speed, high checks everything)",
+            &BehaviorLevel::ALL,
+        ),
+    }
 }
 
 /// Quick wizard with smart defaults based on project type
@@ -332,28 +487,44 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
                         role: "Backend Engineer".to_string(),
                         focus: "API endpoints, server logic, and database operations".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("backend".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                     AgentConfig {
                         id: "frontend".to_string(),
                         role: "Frontend Engineer".to_string(),
                         focus: "UI components, styling, and user interactions".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("frontend".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                 ],
                 McpRegistry::for_web_development(),
@@ -369,28 +540,44 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
                         focus: "Command-line interface, argument parsing, and core functionality"
                             .to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("cli".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                     AgentConfig {
                         id: "testing".to_string(),
                         role: "QA Engineer".to_string(),
                         focus: "Unit tests, integration tests, and quality assurance".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("testing".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                 ],
                 McpRegistry::for_executor(),
@@ -405,28 +592,44 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
                         role: "Data Analyst".to_string(),
                         focus: "Data exploration, analysis, and insights generation".to_string(),
                         model: "claude-opus-4-20250514".to_string(), // Use Opus for complex analysis
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("analysis".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                     AgentConfig {
                         id: "visualization".to_string(),
                         role: "Visualization Engineer".to_string(),
                         focus: "Data visualization, charts, and reporting".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("viz".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                 ],
                 McpRegistry::for_data_analysis(),
@@ -440,14 +643,22 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
                     role: "Automation Engineer".to_string(),
                     focus: "Scripts, bots, and automated workflows".to_string(),
                     model: "claude-sonnet-4-20250514".to_string(),
+                    fallback_models: Vec::new(),
                     directory: None,
                     worktree: Some("automation".to_string()),
                     env: None,
                     mcp_servers: None,
                     instructions: None,
                     startup_prompt: None,
+                    template: None,
+                    template_params: None,
+                    behavior: None,
+                    replicas: None,
+                    tags: Vec::new(),
+                    replica_of: None,
                     capabilities: None,
                     nudge: None,
+                    delivery: None,
                 }],
                 McpRegistry::for_automation(),
             )
@@ -461,28 +672,44 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
                         role: "Software Developer".to_string(),
                         focus: "General software development and implementation".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("dev1".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                     AgentConfig {
                         id: "dev-2".to_string(),
                         role: "Software Developer".to_string(),
                         focus: "General software development and implementation".to_string(),
                         model: "claude-sonnet-4-20250514".to_string(),
+                        fallback_models: Vec::new(),
                         directory: None,
                         worktree: Some("dev2".to_string()),
                         env: None,
                         mcp_servers: None,
                         instructions: None,
                         startup_prompt: None,
+                        template: None,
+                        template_params: None,
+                        behavior: None,
+                        replicas: None,
+                        tags: Vec::new(),
+                        replica_of: None,
                         capabilities: None,
                         nudge: None,
+                        delivery: None,
                     },
                 ],
                 McpRegistry::for_executor(),
@@ -548,6 +775,8 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
             agent_id: "mcp-executor".to_string(),
             mcp_servers,
             languages: vec!["typescript".to_string(), "python".to_string()],
+            model: None,
+            fallback_models: Vec::new(),
         })
     } else {
         None
@@ -576,5 +805,42 @@ fn run_quick_wizard() -> ColonyResult<ColonyConfig> {
         telemetry,
         capabilities: None,
         layout: None,
+        multiplexer: Default::default(),
+        groups: Default::default(),
+        repo_root: Default::default(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_wizard_config_is_valid() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("colony.yml");
+
+        let agents = ["backend", "frontend"]
+            .into_iter()
+            .map(|id| {
+                wizard_agent(
+                    id.to_string(),
+                    "Software Developer".to_string(),
+                    "General software development".to_string(),
+                    "claude-sonnet-4-20250514",
+                    None,
+                    None,
+                )
+            })
+            .collect();
+        wizard_config(None, agents, TelemetryConfig::default())
+            .save(&config_path)
+            .unwrap();
+
+        let config = ColonyConfig::load_file(&config_path).unwrap();
+        assert_eq!(config.agents[0].worktree.as_deref(), Some("agent/backend"));
+        assert!(config.issues().is_empty());
+        config.validate().unwrap();
+    }
+}
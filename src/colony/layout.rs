@@ -1,310 +1,342 @@
-use serde::Serialize;
 use std::collections::HashMap;
-use std::fs;
-use std::process::Command;
 
+use crate::colony::config::{ColonyConfig, LayoutConfig, PaneConfig, PaneSize, SplitDirection};
+use crate::colony::multiplexer::Multiplexer;
 use crate::colony::ColonyController;
-use crate::error::ColonyResult;
+use crate::error::{ColonyError, ColonyResult};
 
-/// Moxide template structure for YAML generation
-#[derive(Debug, Serialize)]
-struct MoxideTemplate {
-    name: String,
-    windows: Vec<MoxideWindow>,
-}
-
-#[derive(Debug, Serialize)]
-struct MoxideWindow {
-    name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    layout: Option<String>,
-    panes: Vec<String>,
-}
-
-/// Generate moxide template from Colony layout configuration and create session
-/// Returns a map of (agent_id/component -> (window_index, pane_index))
-pub fn create_session_with_moxide(
+/// Create the session from the colony's custom layout, if it has one
+/// Returns a map of (agent_id/component -> (window_index, pane_index)), which is
+/// empty when there is no custom layout and the session hasn't been created
+pub fn create_session_with_layout(
     session_name: &str,
     controller: &ColonyController,
+    mux: &dyn Multiplexer,
 ) -> ColonyResult<HashMap<String, (usize, usize)>> {
-    let config = controller.config();
-
-    // Check if custom layout is configured
-    if let Some(layout_config) = &config.layout {
-        eprintln!(
-            "DEBUG: Found layout config, type={}, windows={}",
-            layout_config.layout_type,
-            layout_config.windows.len()
-        );
+    let Some(layout_config) = controller
+        .config()
+        .layout
+        .as_ref()
+        .filter(|l| l.is_custom())
+    else {
+        return Ok(HashMap::new());
+    };
 
-        if layout_config.layout_type == "custom" && !layout_config.windows.is_empty() {
-            crate::utils::info(&format!(
-                "Using custom layout with {} windows",
-                layout_config.windows.len()
-            ));
-            return create_custom_layout_with_moxide(session_name, controller, layout_config);
-        }
-    } else {
-        eprintln!("DEBUG: No layout config found");
+    if !mux.supports_custom_layouts() {
+        crate::utils::warning(&format!(
+            "Custom layouts are not supported with {}, using the default layout",
+            mux.name()
+        ));
+        return Ok(HashMap::new());
     }
 
-    // Fallback: no custom layout, return empty map (use default tmux creation)
-    Ok(HashMap::new())
+    crate::utils::info(&format!(
+        "Using custom layout with {} windows",
+        layout_config.windows.len()
+    ));
+    create_custom_layout(session_name, layout_config, mux)
 }
 
-/// Create custom layout using moxide
-fn create_custom_layout_with_moxide(
+/// Create the session's windows and panes from a custom layout
+///
+/// Each window starts as a single pane that is split once per extra pane, in
+/// config order. Every split hands the new pane the space of all the panes
+/// still to come, so each pane ends up with the size computed by
+/// [`pane_lengths`]. Panes start with a shell; tool panes then get their command.
+fn create_custom_layout(
     session_name: &str,
-    controller: &ColonyController,
-    layout_config: &crate::colony::config::LayoutConfig,
+    layout_config: &LayoutConfig,
+    mux: &dyn Multiplexer,
 ) -> ColonyResult<HashMap<String, (usize, usize)>> {
-    // Build moxide template from colony layout
-    let mut moxide_windows = Vec::new();
     let mut pane_map: HashMap<String, (usize, usize)> = HashMap::new();
 
+    mux.create_session(session_name)?;
+
     for (window_idx, window) in layout_config.windows.iter().enumerate() {
-        let mut pane_commands = Vec::new();
-        let mut pane_configs: Vec<(usize, &crate::colony::config::PaneConfig)> =
-            window.panes.iter().enumerate().collect();
-
-        // Special ordering for main-dev window: put TUI first so it ends up on bottom
-        if window.name == "main-dev" {
-            pane_configs.sort_by_key(|(_, pane)| {
-                match pane.pane_type.as_str() {
-                    "tui" => 0,      // TUI first
-                    "tool" => 1,     // Tools next (nvim)
-                    "agent" => 2,    // Agents
-                    "executor" => 3, // Executor last
-                    _ => 4,
-                }
-            });
+        if window_idx == 0 {
+            mux.rename_window(session_name, window_idx, &window.name)?;
+        } else {
+            mux.create_window(session_name, window_idx, &window.name)?;
         }
 
-        for (original_idx, pane) in pane_configs {
-            let command = match pane.pane_type.as_str() {
-                "agent" => {
-                    if let Some(agent_id) = &pane.agent_id {
-                        // Track with NEW position in output
-                        let new_idx = pane_commands.len();
-                        pane_map.insert(agent_id.clone(), (window_idx, new_idx));
-                        "bash".to_string()
-                    } else {
-                        "bash".to_string()
-                    }
-                }
-                "executor" => {
-                    let new_idx = pane_commands.len();
-                    pane_map.insert("mcp-executor".to_string(), (window_idx, new_idx));
-                    "bash".to_string()
-                }
-                "tui" => {
-                    let new_idx = pane_commands.len();
-                    pane_map.insert("tui".to_string(), (window_idx, new_idx));
-                    "bash".to_string()
+        let horizontal = window.split == SplitDirection::Horizontal;
+        let (width, height) = mux.window_size(session_name, window_idx)?;
+        let total = if horizontal { width } else { height };
+
+        let sizes = window
+            .panes
+            .iter()
+            .map(|pane| pane.parsed_size())
+            .collect::<ColonyResult<Vec<_>>>()?;
+        let lengths = pane_lengths(&sizes, total).map_err(|e| {
+            ColonyError::Colony(format!(
+                "Layout window '{}' doesn't fit: {}",
+                window.name, e
+            ))
+        })?;
+
+        // Pane indices in split order; the window's initial pane is 0
+        let mut pane_indices = vec![0];
+        for i in 1..lengths.len() {
+            // The new pane takes the space of every remaining pane plus the borders between them
+            let remaining = lengths[i..].iter().sum::<u16>() + (lengths.len() - 1 - i) as u16;
+            let pane_idx = mux.split_pane(
+                session_name,
+                window_idx,
+                pane_indices[i - 1],
+                horizontal,
+                remaining,
+            )?;
+            pane_indices.push(pane_idx);
+        }
+
+        for (position, (pane, pane_idx)) in window.panes.iter().zip(pane_indices).enumerate() {
+            if pane.pane_type == "tool" {
+                if let Some(command) = &pane.command {
+                    mux.send_command_to_window_pane(session_name, window_idx, pane_idx, command)?;
                 }
-                "tool" => {
-                    let new_idx = pane_commands.len();
-                    let tool_id = format!("tool-{}-{}", window_idx, original_idx);
-                    pane_map.insert(tool_id, (window_idx, new_idx));
-                    pane.command.clone().unwrap_or_else(|| "bash".to_string())
+                if let Some(title) = pane.title.as_ref().or(pane.command.as_ref()) {
+                    mux.set_window_pane_title(session_name, window_idx, pane_idx, title)?;
                 }
-                _ => "bash".to_string(),
-            };
+            }
 
-            pane_commands.push(command);
+            if let Some(key) = pane_key(pane, window_idx, position) {
+                pane_map.insert(key, (window_idx, pane_idx));
+            }
         }
-
-        // Infer layout from pane count, types, and window name
-        let layout = infer_tmux_layout(&window.name, &window.panes);
-
-        moxide_windows.push(MoxideWindow {
-            name: window.name.clone(),
-            layout,
-            panes: pane_commands,
-        });
-    }
-
-    let template = MoxideTemplate {
-        name: session_name.to_string(),
-        windows: moxide_windows,
-    };
-
-    // Write moxide template to ~/.config/moxide/templates/
-    let template_yaml = serde_yaml::to_string(&template).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to serialize moxide template: {}", e))
-    })?;
-
-    let home_dir = dirs::home_dir().ok_or_else(|| {
-        crate::error::ColonyError::Colony("Failed to get home directory".to_string())
-    })?;
-
-    let templates_dir = home_dir.join(".config/moxide/templates");
-    fs::create_dir_all(&templates_dir).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to create moxide templates dir: {}", e))
-    })?;
-
-    let template_filename = format!("colony-{}", session_name);
-    let template_path = templates_dir.join(format!("{}.yaml", template_filename));
-
-    fs::write(&template_path, template_yaml).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to write moxide template: {}", e))
-    })?;
-
-    crate::utils::info(&format!(
-        "Generated moxide template: {}",
-        template_path.display()
-    ));
-
-    // Create session with moxide (references template by its name field, not filename)
-    let output = Command::new("moxide")
-        .arg("template")
-        .arg("start")
-        .arg(session_name) // Use the name from template YAML
-        .arg("--detached")
-        .arg("--name")
-        .arg(session_name)
-        .output()
-        .map_err(|e| crate::error::ColonyError::Colony(format!("Failed to run moxide: {}", e)))?;
-
-    if !output.status.success() {
-        return Err(crate::error::ColonyError::Colony(format!(
-            "Moxide failed to create session: {}",
-            String::from_utf8_lossy(&output.stderr)
-        )));
     }
 
     crate::utils::success(&format!(
-        "Created tmux session '{}' with moxide",
+        "Created {} session '{}' with custom layout",
+        mux.name(),
         session_name
     ));
 
-    // Apply layout refinements to match exact specifications
-    refine_layout(session_name, layout_config, &pane_map)?;
-
     Ok(pane_map)
 }
 
-/// Refine the layout after moxide creates it to match exact specifications
-fn refine_layout(
-    session_name: &str,
-    layout_config: &crate::colony::config::LayoutConfig,
-    pane_map: &HashMap<String, (usize, usize)>,
-) -> ColonyResult<()> {
-    use crate::colony::tmux;
-
-    // Small delay to let moxide finish creating panes
-    std::thread::sleep(std::time::Duration::from_millis(500));
+/// Key a layout pane is known by in the pane map
+fn pane_key(pane: &PaneConfig, window_idx: usize, position: usize) -> Option<String> {
+    match pane.pane_type.as_str() {
+        "agent" => pane.agent_id.clone(),
+        "executor" => Some("mcp-executor".to_string()),
+        "tui" => Some("tui".to_string()),
+        "tool" => Some(format!("tool-{}-{}", window_idx, position)),
+        _ => None,
+    }
+}
 
-    for (window_idx, window) in layout_config.windows.iter().enumerate() {
-        // Apply window-specific refinements
-        match window.name.as_str() {
-            "main-dev" => {
-                // Desired layout:
-                // ┌────────────┬─────────┐
-                // │   nvim     │  agent  │
-                // │            ├─────────┤
-                // │            │executor │
-                // ├────────────┴─────────┤
-                // │        TUI           │
-                // └──────────────────────┘
-
-                // After reordering, panes are: TUI(0), nvim(1), agent(2), executor(3)
-                // Strategy: Use tiled layout as base (it's functional)
-                // Future: Implement precise geometry with layout strings
-
-                std::thread::sleep(std::time::Duration::from_millis(500));
-
-                crate::utils::info("  Applying tiled layout to main-dev window");
-                tmux::select_window_layout(session_name, window_idx, "tiled")?;
-
-                // With 4 panes, tiled creates a 2x2 grid which is close to what we want
-                // The exact geometry (TUI bottom span) would require custom layout string
-                // or breaking/rejoining panes (complex due to index changes)
-
-                crate::utils::info("  Main-dev layout: 2x2 tiled (TUI, nvim, agent, executor)");
-            }
-            "backend-services" => {
-                // Backend services: even split between agents
-                tmux::select_window_layout(session_name, window_idx, "even-horizontal")?;
+/// Work out where `colony start` will put each agent, the executor and the TUI,
+/// without creating anything
+///
+/// Returns (key, (window_index, pane_index)) in window and pane order. Agents
+/// missing from a custom layout are left out, as `colony start` skips them.
+pub fn planned_pane_map(
+    config: &ColonyConfig,
+    supports_custom_layouts: bool,
+) -> Vec<(String, (usize, usize))> {
+    let mut panes = Vec::new();
+
+    match config.layout.as_ref().filter(|l| l.is_custom()) {
+        Some(layout_config) if supports_custom_layouts => {
+            for (window_idx, window) in layout_config.windows.iter().enumerate() {
+                for (position, pane) in window.panes.iter().enumerate() {
+                    if let Some(key) = pane_key(pane, window_idx, position) {
+                        panes.push((key, (window_idx, position)));
+                    }
+                }
             }
-            _ => {
-                // Default: tiled layout
-                tmux::select_window_layout(session_name, window_idx, "tiled")?;
+        }
+        _ => {
+            for (pane_idx, agent) in config.agents.iter().enumerate() {
+                panes.push((agent.id.clone(), (0, pane_idx)));
             }
         }
+    }
 
-        // Apply size-based resizing if specified in config
-        for pane in &window.panes {
-            if let Some(size_str) = &pane.size {
-                // Parse size (e.g., "60%", "25%")
-                if let Some(pct) = size_str.strip_suffix('%') {
-                    if let Ok(percentage) = pct.parse::<u16>() {
-                        // Find pane coordinates
-                        let pane_id = match pane.pane_type.as_str() {
-                            "agent" => pane.agent_id.as_ref(),
-                            "executor" => Some(&"mcp-executor".to_string()),
-                            "tui" => Some(&"tui".to_string()),
-                            _ => None,
-                        };
-
-                        if let Some(id) = pane_id {
-                            if let Some((win, pane_idx)) = pane_map.get(id.as_str()) {
-                                // Apply size (heuristic: if > 50%, it's width, else height)
-                                if percentage > 50 {
-                                    let _ = tmux::resize_pane_percentage(
-                                        session_name,
-                                        *win,
-                                        *pane_idx,
-                                        Some(percentage),
-                                        None,
-                                    );
-                                } else {
-                                    let _ = tmux::resize_pane_percentage(
-                                        session_name,
-                                        *win,
-                                        *pane_idx,
-                                        None,
-                                        Some(percentage),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    // The executor and the TUI are split off the first window when the layout doesn't place them
+    let mut next_pane = panes.iter().filter(|(_, (w, _))| *w == 0).count();
+    let has_executor = config.executor.as_ref().is_some_and(|e| e.enabled);
+    for (key, wanted) in [
+        ("mcp-executor", has_executor),
+        ("tui", !config.agents.is_empty()),
+    ] {
+        if wanted && !panes.iter().any(|(k, _)| k == key) {
+            panes.push((key.to_string(), (0, next_pane)));
+            next_pane += 1;
         }
     }
 
-    Ok(())
+    panes
 }
 
-/// Infer appropriate tmux layout from pane configuration
-fn infer_tmux_layout(
-    window_name: &str,
-    panes: &[crate::colony::config::PaneConfig],
-) -> Option<String> {
-    // Special case for main-dev window: use main-horizontal for bottom-spanning TUI
-    if window_name == "main-dev" && panes.len() == 4 {
-        // 4 panes: nvim, agent, executor, TUI
-        // main-horizontal puts one pane as "main" (larger) and others stacked
-        return Some("main-horizontal".to_string());
+/// Work out how many columns (or rows) each pane in a window gets
+///
+/// `total` is the window's size along the split direction. One cell goes to the
+/// border between each pair of panes. Sized panes get their share, panes without
+/// a size split what is left evenly, and any rounding slack goes to the last pane.
+fn pane_lengths(sizes: &[Option<PaneSize>], total: u16) -> ColonyResult<Vec<u16>> {
+    let borders = sizes.len().saturating_sub(1) as u32;
+    let usable = u32::from(total).checked_sub(borders).ok_or_else(|| {
+        ColonyError::Colony(format!(
+            "{} panes don't fit in {} cells",
+            sizes.len(),
+            total
+        ))
+    })?;
+
+    let mut lengths: Vec<Option<u32>> = sizes
+        .iter()
+        .map(|size| match size {
+            Some(PaneSize::Percent(pct)) => Some(usable * u32::from(*pct) / 100),
+            Some(PaneSize::Cells(cells)) => Some(u32::from(*cells)),
+            None => None,
+        })
+        .collect();
+
+    let sized: u32 = lengths.iter().flatten().sum();
+    let remaining = usable.checked_sub(sized).ok_or_else(|| {
+        ColonyError::Colony(format!(
+            "pane sizes need {} cells but only {} are available",
+            sized, usable
+        ))
+    })?;
+
+    let unsized_panes = lengths.iter().filter(|l| l.is_none()).count() as u32;
+    if let Some(share) = remaining.checked_div(unsized_panes) {
+        for length in lengths.iter_mut().filter(|l| l.is_none()) {
+            *length = Some(share);
+        }
     }
 
-    // Backend services or other: even split
-    if window_name.contains("backend") || window_name.contains("services") {
-        return Some("even-horizontal".to_string());
+    let mut lengths: Vec<u32> = lengths.into_iter().flatten().collect();
+    let slack = usable - lengths.iter().sum::<u32>();
+    if let Some(last) = lengths.last_mut() {
+        *last += slack;
     }
 
-    // Default based on pane count
-    match panes.len() {
-        0 | 1 => None,
-        2 => Some("even-horizontal".to_string()),
-        3 | 4 => Some("tiled".to_string()),
-        _ => Some("tiled".to_string()),
+    if lengths.contains(&0) {
+        return Err(ColonyError::Colony(format!(
+            "a pane would be left with no space in {} cells",
+            total
+        )));
     }
+
+    Ok(lengths.into_iter().map(|l| l as u16).collect())
 }
 
 /// Helper to convert window/pane coordinates to tmux target format
 pub fn pane_target(session_name: &str, window_idx: usize, pane_idx: usize) -> String {
     format!("{}:{}.{}", session_name, window_idx, pane_idx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::multiplexer::fake::{FakeMultiplexer, WINDOW_SIZE};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pane_lengths() {
+        // 100 columns, 2 borders => 98 usable
+        let lengths = pane_lengths(&[Some(PaneSize::Percent(50)), None, None], 100).unwrap();
+        assert_eq!(lengths, vec![49, 24, 25]);
+
+        let lengths = pane_lengths(&[Some(PaneSize::Cells(30)), None], 100).unwrap();
+        assert_eq!(lengths, vec![30, 69]);
+
+        assert!(
+            pane_lengths(&[Some(PaneSize::Cells(90)), Some(PaneSize::Cells(20))], 100).is_err()
+        );
+        assert!(pane_lengths(&[Some(PaneSize::Percent(100)), None], 100).is_err());
+    }
+
+    #[test]
+    fn test_custom_layout() {
+        let yaml = r#"
+name: test
+agents:
+  - id: backend
+    role: Backend
+    focus: API
+  - id: frontend
+    role: Frontend
+    focus: UI
+layout:
+  type: custom
+  windows:
+    - name: main
+      panes:
+        - type: agent
+          agent_id: backend
+          size: 60%
+        - type: tool
+          command: nvim
+        - type: agent
+          agent_id: frontend
+    - name: monitor
+      split: vertical
+      panes:
+        - type: tui
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        config.validate().unwrap();
+
+        let dir = tempdir().unwrap();
+        let controller = ColonyController::with_root(config, dir.path().to_path_buf()).unwrap();
+        let fake = FakeMultiplexer::default();
+        let pane_map = create_session_with_layout("colony-test", &controller, &fake).unwrap();
+
+        assert_eq!(pane_map["backend"], (0, 0));
+        assert_eq!(pane_map["tool-0-1"], (0, 1));
+        assert_eq!(pane_map["frontend"], (0, 2));
+        assert_eq!(pane_map["tui"], (1, 0));
+
+        let planned: HashMap<_, _> = planned_pane_map(controller.config(), true)
+            .into_iter()
+            .collect();
+        assert_eq!(planned, pane_map);
+
+        let session = fake.session("colony-test").unwrap();
+        assert_eq!(session.windows[0].name, "main");
+        assert_eq!(session.windows[1].name, "monitor");
+        assert_eq!(session.windows[1].panes.len(), 1);
+
+        // 200 columns: 198 usable, backend gets 60% of it
+        let lengths =
+            pane_lengths(&[Some(PaneSize::Percent(60)), None, None], WINDOW_SIZE.0).unwrap();
+        let main = &session.windows[0].panes;
+        assert_eq!(main[1].length, Some(lengths[1] + lengths[2] + 1));
+        assert_eq!(main[2].length, Some(lengths[2]));
+        assert_eq!(main[1].commands, vec!["nvim"]);
+        assert_eq!(main[1].title.as_deref(), Some("nvim"));
+    }
+
+    #[test]
+    fn test_invalid_layouts() {
+        let base = r#"
+agents:
+  - id: backend
+    role: Backend
+    focus: API
+layout:
+  type: custom
+  windows:
+    - name: main
+      panes:
+"#;
+        let invalid = [
+            "        - type: agent\n          agent_id: missing\n",
+            "        - type: agent\n          agent_id: backend\n          size: 120%\n",
+            "        - type: agent\n          agent_id: backend\n          size: wide\n",
+            "        - type: agent\n          agent_id: backend\n          size: 100%\n        - type: tui\n",
+            "        - type: agent\n          agent_id: backend\n        - type: agent\n          agent_id: backend\n",
+            "        - type: executor\n",
+        ];
+
+        for panes in invalid {
+            let config: ColonyConfig = serde_yaml::from_str(&format!("{}{}", base, panes)).unwrap();
+            assert!(config.validate().is_err(), "accepted:\n{}", panes);
+        }
+    }
+}
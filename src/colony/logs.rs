@@ -21,7 +21,7 @@ pub async fn run_with_options(
     json: bool,
     no_color: bool,
 ) -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
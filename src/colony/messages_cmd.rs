@@ -1,10 +1,17 @@
+use chrono::{DateTime, Utc};
 use colored::Colorize;
 use std::env;
+use std::path::{Path, PathBuf};
 
 use crate::error::{ColonyError, ColonyResult};
 use crate::utils;
 
-use super::messaging::{load_all_messages, load_messages_for_agent, MessageType};
+use super::messaging::{
+    self, find_message, load_all_messages, load_messages_for_agent, load_thread,
+    load_unread_messages, mark_read, read_message_ids, readers, thread_tree, Message, MessageType,
+};
+use super::state::MessageSearch;
+use super::workflow::definition::parse_duration;
 
 /// Helper to format the sender badge
 fn format_from_badge(from: &str) -> colored::ColoredString {
@@ -29,16 +36,10 @@ fn format_message_type(message_type: &MessageType) -> String {
 
 /// List messages for a specific agent
 pub async fn list_messages(agent_id: String) -> ColonyResult<()> {
-    let current_dir = env::current_dir()?;
-    let colony_root = current_dir.join(".colony");
-
-    if !colony_root.exists() {
-        return Err(ColonyError::Colony(
-            "No colony found. Run 'colony init' first.".to_string(),
-        ));
-    }
+    let colony_root = colony_root()?;
 
     let messages = load_messages_for_agent(&colony_root, &agent_id)?;
+    let read = read_message_ids(&colony_root, &agent_id)?;
 
     if messages.is_empty() {
         println!("No messages for agent '{}'", agent_id.cyan());
@@ -60,12 +61,19 @@ pub async fn list_messages(agent_id: String) -> ColonyResult<()> {
             format!("[TO: {}]", message.to).dimmed().to_string()
         };
 
+        let unread_badge = if message.from != agent_id && !read.contains(&message.id) {
+            " [UNREAD]".red().bold().to_string()
+        } else {
+            String::new()
+        };
+
         println!(
-            "{} {} {} {}",
+            "{} {} {} {}{}",
             type_badge,
             from_badge,
             to_badge,
-            message.timestamp.dimmed()
+            message.timestamp.dimmed(),
+            unread_badge
         );
         println!("  {}", message.content);
         println!();
@@ -78,14 +86,7 @@ pub async fn list_messages(agent_id: String) -> ColonyResult<()> {
 
 /// List all messages in the system
 pub async fn list_all_messages() -> ColonyResult<()> {
-    let current_dir = env::current_dir()?;
-    let colony_root = current_dir.join(".colony");
-
-    if !colony_root.exists() {
-        return Err(ColonyError::Colony(
-            "No colony found. Run 'colony init' first.".to_string(),
-        ));
-    }
+    let colony_root = colony_root()?;
 
     let messages = load_all_messages(&colony_root)?;
 
@@ -97,28 +98,300 @@ pub async fn list_all_messages() -> ColonyResult<()> {
     utils::header("All Colony Messages");
     println!();
 
-    for message in &messages {
-        let type_badge = format_message_type(&message.message_type);
-        let from_badge = format_from_badge(&message.from);
+    // Replies are shown under the message they answer
+    for (depth, message) in thread_tree(&messages) {
+        print_threaded_message(&colony_root, depth, message)?;
+    }
 
-        let to_badge = if message.to == "all" {
-            "[BROADCAST]".yellow().to_string()
+    utils::success(&format!("Displayed {} message(s)", messages.len()));
+
+    Ok(())
+}
+
+/// Show the conversation a message belongs to
+pub async fn show_thread(message_id: String) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let messages = load_thread(&colony_root, &message_id)?;
+
+    utils::header(&format!("Thread: {}", messages[0].thread()));
+    println!();
+
+    for (depth, message) in thread_tree(&messages) {
+        print_threaded_message(&colony_root, depth, message)?;
+    }
+
+    utils::success(&format!("Displayed {} message(s)", messages.len()));
+
+    Ok(())
+}
+
+/// Mark a message in an agent's inbox as read
+pub async fn ack_message(agent_id: String, message_id: String) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    if !load_messages_for_agent(&colony_root, &agent_id)?
+        .iter()
+        .any(|message| message.id == message_id)
+    {
+        return Err(ColonyError::Colony(format!(
+            "Message '{}' not found in the inbox of '{}'",
+            message_id, agent_id
+        )));
+    }
+
+    mark_read(&colony_root, &agent_id, &[&message_id])?;
+    utils::success(&format!("Marked {} read for {}", message_id, agent_id));
+
+    Ok(())
+}
+
+/// Send a message as `from`, recording `project_dir` as its context
+pub async fn send_message(
+    from: String,
+    to: String,
+    content: String,
+    message_type: MessageType,
+    project_dir: Option<PathBuf>,
+) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let mut message = Message::new(&from, &to, content, message_type);
+    if let Some(project_dir) = project_dir {
+        message = message.with_context(&project_dir);
+    }
+    message.save(&colony_root)?;
+    println!("Message sent to {}", to);
+
+    Ok(())
+}
+
+/// Reply to a message as `from`, in the same thread
+pub async fn reply_to_message(
+    from: String,
+    message_id: String,
+    content: String,
+    project_dir: Option<PathBuf>,
+) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let original = find_message(&colony_root, &message_id)?
+        .ok_or_else(|| ColonyError::Colony(format!("Message '{}' not found", message_id)))?;
+
+    // Reply to the sender, or to the original recipients when replying to yourself
+    let to = if original.from == from {
+        &original.to
+    } else {
+        &original.from
+    };
+    let message_type = if original.message_type == MessageType::Question {
+        MessageType::Answer
+    } else {
+        MessageType::Info
+    };
+
+    let mut message = Message::new(&from, to, content, message_type).in_reply_to(&original);
+    if let Some(project_dir) = project_dir {
+        message = message.with_context(&project_dir);
+    }
+    message.save(&colony_root)?;
+    println!("Message sent to {}", message.to);
+
+    Ok(())
+}
+
+/// Show an agent the messages it hasn't read, or all of them, and mark them read
+pub async fn read_messages(agent_id: String, all: bool) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let messages = if all {
+        println!("=== Messages for {} ===", agent_id);
+        load_messages_for_agent(&colony_root, &agent_id)?
+    } else {
+        println!("=== New messages for {} ===", agent_id);
+        load_unread_messages(&colony_root, &agent_id)?
+    };
+
+    if messages.is_empty() {
+        println!("No new messages");
+        return Ok(());
+    }
+
+    let read = read_message_ids(&colony_root, &agent_id)?;
+    for message in &messages {
+        let prefix = if message.to == "all" {
+            "[BROADCAST] "
         } else {
-            format!("[TO: {}]", message.to).green().to_string()
+            ""
         };
+        let mut from = format!("{}From: {}", prefix, message.from);
+        if let Some(project_dir) = &message.project_dir {
+            from.push_str(&format!(" [{}]", project_dir));
+        }
+        if let Some(git_branch) = &message.git_branch {
+            from.push_str(&format!(" ({})", git_branch));
+        }
+        let mut id = format!("ID: {}", message.id);
+        if let Some(reply_to) = &message.reply_to {
+            id.push_str(&format!(" (reply to {})", reply_to));
+        }
+
+        println!("---");
+        println!("{}", from);
+        println!("{}", id);
+        println!("{}", message.content);
+    }
+
+    let unread: Vec<&str> = messages
+        .iter()
+        .map(|message| message.id.as_str())
+        .filter(|id| !read.contains(*id))
+        .collect();
+    mark_read(&colony_root, &agent_id, &unread)?;
+
+    Ok(())
+}
+
+/// Show the messages an agent sent and which agents have read them
+pub async fn sent_messages(agent_id: String) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let search = MessageSearch {
+        from: Some(agent_id.clone()),
+        ..Default::default()
+    };
+    let messages = messaging::search_messages(&colony_root, &search)?;
+
+    println!("=== Messages sent by {} ===", agent_id);
+    for message in &messages {
+        let readers = readers(&colony_root, &message.id)?;
+        println!("---");
+        println!("To: {}", message.to);
+        println!("ID: {}", message.id);
+        if readers.is_empty() {
+            println!("Read by: nobody yet");
+        } else {
+            println!("Read by: {}", readers.join(" "));
+        }
+        println!("{}", message.content);
+    }
+
+    Ok(())
+}
+
+/// Search message content, optionally by sender and age
+pub async fn search_messages(
+    query: String,
+    from: Option<String>,
+    since: Option<String>,
+    limit: usize,
+) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let since = since.as_deref().map(parse_since).transpose()?;
+    let search = MessageSearch {
+        text: Some(query.clone()),
+        from,
+        since,
+        limit: Some(limit),
+    };
+    let messages = messaging::search_messages(&colony_root, &search)?;
+
+    if messages.is_empty() {
+        println!("No messages match '{}'", query);
+        return Ok(());
+    }
+
+    utils::header(&format!("Messages matching '{}'", query));
+    println!();
 
+    for message in messages.iter().rev() {
+        print_threaded_message(&colony_root, 0, message)?;
+    }
+
+    utils::success(&format!("Found {} message(s)", messages.len()));
+
+    Ok(())
+}
+
+/// Write every message to `output` as JSON files
+pub async fn export_messages(output: PathBuf) -> ColonyResult<()> {
+    let colony_root = colony_root()?;
+
+    let count = messaging::export_messages(&colony_root, &output)?;
+    utils::success(&format!(
+        "Exported {} message(s) to {}",
+        count,
+        output.display()
+    ));
+
+    Ok(())
+}
+
+/// The `.colony` directory of the colony in the current directory
+fn colony_root() -> ColonyResult<PathBuf> {
+    let colony_root = env::current_dir()?.join(".colony");
+
+    if !colony_root.exists() {
+        return Err(ColonyError::Colony(
+            "No colony found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    Ok(colony_root)
+}
+
+/// Parse `--since` as a duration before now (`2h`) or an RFC 3339 time
+fn parse_since(since: &str) -> ColonyResult<DateTime<Utc>> {
+    if let Some(duration) = parse_duration(since) {
+        let duration = chrono::Duration::from_std(duration)
+            .map_err(|_| ColonyError::Colony(format!("Duration '{}' is too long", since)))?;
+        return Ok(Utc::now() - duration);
+    }
+
+    DateTime::parse_from_rfc3339(since)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| {
+            ColonyError::Colony(format!(
+                "Invalid --since '{}'. Use a duration like 30m, 2h or 1d, or an RFC 3339 time",
+                since
+            ))
+        })
+}
+
+/// Print a message indented under the one it replies to
+fn print_threaded_message(colony_root: &Path, depth: usize, message: &Message) -> ColonyResult<()> {
+    let indent = "   ".repeat(depth);
+    let branch = if depth > 0 { "└─ " } else { "" };
+    let type_badge = format_message_type(&message.message_type);
+    let from_badge = format_from_badge(&message.from);
+
+    let to_badge = if message.to == "all" {
+        "[BROADCAST]".yellow().to_string()
+    } else {
+        format!("[TO: {}]", message.to).green().to_string()
+    };
+
+    println!(
+        "{}{}{} {} → {} {}",
+        indent,
+        branch,
+        type_badge,
+        from_badge,
+        to_badge,
+        message.timestamp.dimmed()
+    );
+    println!("{}   {}", indent, message.id.dimmed());
+    println!("{}   {}", indent, message.content);
+    let readers = readers(colony_root, &message.id)?;
+    if !readers.is_empty() {
         println!(
-            "{} {} → {} {}",
-            type_badge,
-            from_badge,
-            to_badge,
-            message.timestamp.dimmed()
+            "{}   {}",
+            indent,
+            format!("✓ read by {}", readers.join(", ")).dimmed()
         );
-        println!("  {}", message.content);
-        println!();
     }
-
-    utils::success(&format!("Displayed {} message(s)", messages.len()));
+    println!();
 
     Ok(())
 }
@@ -1,10 +1,26 @@
 use chrono::Utc;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
 
+use crate::colony::state::{MessageSearch, StateCache};
 use crate::error::ColonyResult;
 
+/// Directory under `.colony/` listing the members of each message group
+pub const GROUPS_DIR: &str = "groups";
+
+/// Directory under `.colony/` listing, per agent, the IDs of messages it has read
+pub const READ_DIR: &str = "read";
+
+/// Directory under `.colony/` that older versions stored messages in as JSON
+/// files. It is imported into the message store and renamed the first time
+/// the store is opened.
+const LEGACY_MESSAGES_DIR: &str = "messages";
+
 /// A message between agents
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -27,6 +43,12 @@ pub struct Message {
     /// Git branch context
     #[serde(default)]
     pub git_branch: Option<String>,
+    /// ID of the message this one replies to
+    #[serde(default)]
+    pub reply_to: Option<String>,
+    /// ID of the first message in the conversation, set on replies
+    #[serde(default)]
+    pub thread_id: Option<String>,
 }
 
 fn default_message_type() -> MessageType {
@@ -34,7 +56,7 @@ fn default_message_type() -> MessageType {
 }
 
 /// Type of message
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageType {
     /// Informational message
@@ -51,6 +73,25 @@ pub enum MessageType {
     Error,
 }
 
+impl FromStr for MessageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Self::Info),
+            "task" => Ok(Self::Task),
+            "question" => Ok(Self::Question),
+            "answer" => Ok(Self::Answer),
+            "completed" => Ok(Self::Completed),
+            "error" => Ok(Self::Error),
+            _ => Err(format!(
+                "Invalid message type '{}'. Use info, task, question, answer, completed or error",
+                s
+            )),
+        }
+    }
+}
+
 impl Message {
     /// Create a new message
     pub fn new(from: &str, to: &str, content: String, message_type: MessageType) -> Self {
@@ -73,122 +114,334 @@ impl Message {
             message_type,
             project_dir: None,
             git_branch: None,
+            reply_to: None,
+            thread_id: None,
         }
     }
 
-    /// Save message to the message queue
-    pub fn save(&self, colony_root: &Path) -> ColonyResult<()> {
-        let messages_dir = colony_root.join("messages");
-        fs::create_dir_all(&messages_dir)?;
+    /// Make this message a reply to `original`, in the same thread
+    pub fn in_reply_to(mut self, original: &Message) -> Self {
+        self.reply_to = Some(original.id.clone());
+        self.thread_id = Some(original.thread().to_string());
+        self
+    }
+
+    /// ID of the thread this message belongs to, its own ID if it starts one
+    pub fn thread(&self) -> &str {
+        self.thread_id.as_deref().unwrap_or(&self.id)
+    }
+
+    /// Record the directory the message was sent from and its Git branch
+    pub fn with_context(mut self, project_dir: &Path) -> Self {
+        self.project_dir = Some(project_dir.display().to_string());
+        self.git_branch = Command::new("git")
+            .arg("-C")
+            .arg(project_dir)
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|branch| !branch.is_empty());
+        self
+    }
 
-        // Save to recipient's inbox
-        let inbox_dir = if self.to == "all" {
-            messages_dir.join("broadcast")
+    /// Save message to the message store, in the recipient's inbox or each
+    /// member's inbox for a group
+    pub fn save(&self, colony_root: &Path) -> ColonyResult<()> {
+        let inboxes = if self.to == "all" {
+            vec!["broadcast".to_string()]
         } else {
-            messages_dir.join(&self.to)
+            resolve_recipients(colony_root, &self.to)?
+                .into_iter()
+                .filter(|recipient| *recipient != self.from)
+                .collect()
         };
-        fs::create_dir_all(&inbox_dir)?;
 
-        let message_file = inbox_dir.join(format!("{}.json", self.id));
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(message_file, json)?;
+        let inboxes: Vec<&str> = inboxes.iter().map(String::as_str).collect();
+        open_store(colony_root)?.save_message(self, &inboxes)
+    }
+}
 
-        // Also save to sender's outbox for record keeping
-        let outbox_dir = messages_dir.join(&self.from).join("sent");
-        fs::create_dir_all(&outbox_dir)?;
+/// Record message groups, one file per address under `.colony/groups/` with
+/// a member ID per line, so both [`Message::save`] and `colony_message.sh` can
+/// deliver to every member. `kind:name` addresses like `group:reviewers` are
+/// stored as `groups/<kind>/<name>`, others as `groups/<address>`.
+pub fn write_groups(
+    colony_root: &Path,
+    groups: &BTreeMap<String, Vec<String>>,
+) -> ColonyResult<()> {
+    let groups_dir = colony_root.join(GROUPS_DIR);
+    if groups_dir.exists() {
+        fs::remove_dir_all(&groups_dir)?;
+    }
+    fs::create_dir_all(&groups_dir)?;
 
-        let outbox_file = outbox_dir.join(format!("{}.json", self.id));
-        let json = serde_json::to_string_pretty(self)?;
-        fs::write(outbox_file, json)?;
+    for (address, members) in groups {
+        // Roles can be any text; those that can't be a file name can't be addressed
+        let Some(path) = group_path(colony_root, address) else {
+            continue;
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, format!("{}\n", members.join("\n")))?;
+    }
+    Ok(())
+}
 
-        Ok(())
+/// Agents a message to `to` is delivered to: the members of a group, role,
+/// tag or replica set, or `to` itself. `group:`, `role:` and `tag:`
+/// addresses must match at least one agent.
+pub fn resolve_recipients(colony_root: &Path, to: &str) -> ColonyResult<Vec<String>> {
+    let members = group_path(colony_root, to)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|contents| {
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        });
+
+    match members {
+        Some(members) => Ok(members),
+        None if to.contains(':') => Err(crate::error::ColonyError::Colony(format!(
+            "No agents match '{}'. Use group:<name>, role:<role> or tag:<tag>",
+            to
+        ))),
+        None => Ok(vec![to.to_string()]),
     }
 }
 
-/// Load all messages for a specific agent
-pub fn load_messages_for_agent(colony_root: &Path, agent_id: &str) -> ColonyResult<Vec<Message>> {
-    let inbox_dir = colony_root.join("messages").join(agent_id);
+/// File listing the members of the group at `address`
+fn group_path(colony_root: &Path, address: &str) -> Option<PathBuf> {
+    let groups_dir = colony_root.join(GROUPS_DIR);
+    let is_file_name =
+        |name: &str| !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\']);
 
-    if !inbox_dir.exists() {
-        return Ok(Vec::new());
+    match address.split_once(':') {
+        Some((kind @ ("group" | "role" | "tag"), name)) if is_file_name(name) => {
+            Some(groups_dir.join(kind).join(name))
+        }
+        Some(_) => None,
+        None if is_file_name(address) => Some(groups_dir.join(address)),
+        None => None,
     }
+}
 
-    let mut messages = Vec::new();
+/// Load all messages for a specific agent
+pub fn load_messages_for_agent(colony_root: &Path, agent_id: &str) -> ColonyResult<Vec<Message>> {
+    open_store(colony_root)?.get_inbox_messages(agent_id)
+}
 
-    for entry in fs::read_dir(inbox_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+/// Messages sent to an agent that it hasn't read yet, oldest first
+pub fn load_unread_messages(colony_root: &Path, agent_id: &str) -> ColonyResult<Vec<Message>> {
+    let read = read_message_ids(colony_root, agent_id)?;
+    Ok(load_messages_for_agent(colony_root, agent_id)?
+        .into_iter()
+        .filter(|message| message.from != agent_id && !read.contains(&message.id))
+        .collect())
+}
 
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let content = fs::read_to_string(&path)?;
-            if let Ok(message) = serde_json::from_str::<Message>(&content) {
-                messages.push(message);
-            }
-        }
+/// IDs of the messages an agent has read or acknowledged
+pub fn read_message_ids(colony_root: &Path, agent_id: &str) -> ColonyResult<HashSet<String>> {
+    let path = colony_root.join(READ_DIR).join(agent_id);
+    if !path.exists() {
+        return Ok(HashSet::new());
     }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
 
-    // Also load broadcast messages
-    let broadcast_dir = colony_root.join("messages").join("broadcast");
-    if broadcast_dir.exists() {
-        for entry in fs::read_dir(broadcast_dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = fs::read_to_string(&path)?;
-                if let Ok(message) = serde_json::from_str::<Message>(&content) {
-                    messages.push(message);
-                }
-            }
-        }
+/// Record that an agent has read the given messages
+pub fn mark_read(colony_root: &Path, agent_id: &str, ids: &[&str]) -> ColonyResult<()> {
+    use std::io::Write;
+
+    let read_dir = colony_root.join(READ_DIR);
+    fs::create_dir_all(&read_dir)?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(read_dir.join(agent_id))?;
+    for id in ids {
+        writeln!(file, "{}", id)?;
     }
+    Ok(())
+}
 
-    // Sort by timestamp
-    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+/// Agents that have read a message, in ID order
+pub fn readers(colony_root: &Path, message_id: &str) -> ColonyResult<Vec<String>> {
+    let read_dir = colony_root.join(READ_DIR);
+    if !read_dir.exists() {
+        return Ok(Vec::new());
+    }
 
-    Ok(messages)
+    let mut readers = Vec::new();
+    for entry in fs::read_dir(read_dir)? {
+        let agent_id = entry?.file_name().to_string_lossy().to_string();
+        if read_message_ids(colony_root, &agent_id)?.contains(message_id) {
+            readers.push(agent_id);
+        }
+    }
+    readers.sort();
+    Ok(readers)
 }
 
 /// Load all messages in the system
 pub fn load_all_messages(colony_root: &Path) -> ColonyResult<Vec<Message>> {
-    let messages_dir = colony_root.join("messages");
+    open_store(colony_root)?.get_messages()
+}
 
-    if !messages_dir.exists() {
-        return Ok(Vec::new());
+/// Database holding the message store. It is kept apart from the state
+/// cache, which can be deleted and rebuilt, because it is the only copy of
+/// the messages.
+pub fn store_path(colony_root: &Path) -> PathBuf {
+    colony_root.join("messages.db")
+}
+
+/// Open the message store. Messages that older versions left as JSON files
+/// in `.colony/messages/` are imported the first time.
+pub fn open_store(colony_root: &Path) -> ColonyResult<StateCache> {
+    let mut store = StateCache::open(&store_path(colony_root))?;
+
+    let messages_dir = colony_root.join(LEGACY_MESSAGES_DIR);
+    if messages_dir.is_dir() {
+        import_message_files(&mut store, &messages_dir)?;
+        // Keep the files around, but out of the way so they're only imported once
+        let imported_dir = colony_root.join(format!(
+            "{}.imported-{}",
+            LEGACY_MESSAGES_DIR,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ));
+        match fs::rename(&messages_dir, imported_dir) {
+            // Another colony command imported them at the same time
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            result => result?,
+        }
     }
 
-    let mut messages = Vec::new();
+    Ok(store)
+}
 
-    // Recursively walk all message directories
-    fn walk_messages(dir: &Path, messages: &mut Vec<Message>) -> ColonyResult<()> {
-        if !dir.exists() {
-            return Ok(());
+/// Import the message files of an older colony: `<agent>/` and `broadcast/`
+/// inboxes, each with a `sent/` outbox
+fn import_message_files(store: &mut StateCache, messages_dir: &Path) -> ColonyResult<()> {
+    for entry in fs::read_dir(messages_dir)? {
+        let dir = entry?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let name = dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        // Older helper scripts delivered messages to "all" into messages/all/
+        let inbox = if name == "all" { "broadcast" } else { &name };
+
+        store.import_messages(Some(inbox), &read_message_files(&dir)?)?;
+        let outbox = dir.join("sent");
+        if outbox.is_dir() {
+            store.import_messages(None, &read_message_files(&outbox)?)?;
         }
+    }
+    Ok(())
+}
 
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if path.is_dir() {
-                walk_messages(&path, messages)?;
-            } else if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = fs::read_to_string(&path)?;
-                if let Ok(message) = serde_json::from_str::<Message>(&content) {
-                    messages.push(message);
-                }
+/// Messages stored as `<id>.json` files in `dir`, skipping unreadable ones
+fn read_message_files(dir: &Path) -> ColonyResult<Vec<Message>> {
+    let mut messages = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            let content = fs::read_to_string(&path)?;
+            if let Ok(message) = serde_json::from_str::<Message>(&content) {
+                messages.push(message);
             }
         }
+    }
+    Ok(messages)
+}
 
-        Ok(())
+/// Write every message in the store to `output_dir` as `<id>.json`
+pub fn export_messages(colony_root: &Path, output_dir: &Path) -> ColonyResult<usize> {
+    let messages = load_all_messages(colony_root)?;
+    fs::create_dir_all(output_dir)?;
+    for message in &messages {
+        fs::write(
+            output_dir.join(format!("{}.json", message.id)),
+            serde_json::to_string_pretty(message)?,
+        )?;
     }
+    Ok(messages.len())
+}
 
-    walk_messages(&messages_dir, &mut messages)?;
+/// Search the message store
+pub fn search_messages(colony_root: &Path, search: &MessageSearch) -> ColonyResult<Vec<Message>> {
+    open_store(colony_root)?.search_messages(search)
+}
 
-    // Sort by timestamp and deduplicate
-    messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-    messages.dedup_by(|a, b| a.id == b.id);
+/// Find a message by ID
+pub fn find_message(colony_root: &Path, id: &str) -> ColonyResult<Option<Message>> {
+    open_store(colony_root)?.get_message(id)
+}
 
-    Ok(messages)
+/// Load the conversation `id` belongs to, oldest message first
+pub fn load_thread(colony_root: &Path, id: &str) -> ColonyResult<Vec<Message>> {
+    let messages = load_all_messages(colony_root)?;
+    let Some(thread) = messages
+        .iter()
+        .find(|message| message.id == id)
+        .map(|message| message.thread().to_string())
+    else {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Message '{}' not found",
+            id
+        )));
+    };
+
+    Ok(messages
+        .into_iter()
+        .filter(|message| message.thread() == thread)
+        .collect())
+}
+
+/// Arrange messages as conversation trees, each paired with its depth
+///
+/// Every reply follows the message it answers, replies in timestamp order.
+/// Messages that aren't replies, or whose original isn't in `messages`, are
+/// roots and keep their order from `messages`.
+pub fn thread_tree(messages: &[Message]) -> Vec<(usize, &Message)> {
+    let ids: HashSet<&str> = messages.iter().map(|m| m.id.as_str()).collect();
+    let mut replies: HashMap<&str, Vec<&Message>> = HashMap::new();
+    let mut roots = Vec::new();
+    for message in messages {
+        match message.reply_to.as_deref() {
+            Some(parent) if parent != message.id && ids.contains(parent) => {
+                replies.entry(parent).or_default().push(message)
+            }
+            _ => roots.push(message),
+        }
+    }
+    for children in replies.values_mut() {
+        children.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    }
+
+    let mut tree = Vec::with_capacity(messages.len());
+    let mut stack: Vec<(usize, &Message)> = roots.into_iter().rev().map(|m| (0, m)).collect();
+    while let Some((depth, message)) = stack.pop() {
+        tree.push((depth, message));
+        if let Some(children) = replies.remove(message.id.as_str()) {
+            stack.extend(children.into_iter().rev().map(|m| (depth + 1, m)));
+        }
+    }
+    tree
 }
 
 /// Shell-escape a string for safe embedding in bash scripts
@@ -211,6 +464,11 @@ pub fn create_message_helper_script(
     // Shell-escape values to prevent injection
     let escaped_root = shell_escape_for_script(&colony_root.display().to_string());
     let escaped_agent = shell_escape_for_script(agent_id);
+    // Messages are read and written through the binary that started the colony
+    let colony_bin = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "colony".to_string());
+    let escaped_bin = shell_escape_for_script(&colony_bin);
 
     let script_content = format!(
         r#"#!/bin/bash
@@ -220,145 +478,68 @@ pub fn create_message_helper_script(
 COLONY_ROOT='{}'
 AGENT_ID='{}'
 
+# Messages live in the colony's message store, which the colony binary manages
+if [ -z "$COLONY_BIN" ]; then
+    COLONY_BIN='{}'
+    [ -x "$COLONY_BIN" ] || COLONY_BIN=colony
+fi
+if ! command -v "$COLONY_BIN" >/dev/null 2>&1; then
+    echo "Error: colony binary not found"
+    exit 1
+fi
+
+# Run `colony messages` from the directory the colony was started in
+colony_messages() {{
+    (cd "$COLONY_ROOT/.." && "$COLONY_BIN" messages "$@")
+}}
+
 case "$1" in
     send)
-        # Usage: ./colony_message.sh send <recipient> <message>
-        RECIPIENT="$2"
-        MESSAGE="$3"
-        if [ -z "$RECIPIENT" ] || [ -z "$MESSAGE" ]; then
-            echo "Usage: ./colony_message.sh send <recipient> <message>"
+        # Usage: ./colony_message.sh send [--type <type>] <recipient> <message>
+        MESSAGE_TYPE=info
+        if [ "$2" = "--type" ]; then
+            MESSAGE_TYPE="$3"
+            shift 2
+        fi
+        if [ -z "$2" ] || [ -z "$3" ]; then
+            echo "Usage: ./colony_message.sh send [--type <type>] <recipient> <message>"
             exit 1
         fi
+        colony_messages send --from "$AGENT_ID" --type "$MESSAGE_TYPE" --project-dir "$PWD" -- "$2" "$3"
+        ;;
 
-        # Validate recipient ID (only alphanumeric, hyphens, underscores, or "all")
-        if ! (echo "$RECIPIENT" | grep -qE '^[a-zA-Z0-9_-]+$' || [ "$RECIPIENT" = "all" ]); then
-            echo "Error: Invalid recipient ID '$RECIPIENT'"
-            echo "Recipient must contain only alphanumeric characters, hyphens, underscores, or 'all'"
+    reply)
+        # Usage: ./colony_message.sh reply <message-id> <message>
+        if [ -z "$2" ] || [ -z "$3" ]; then
+            echo "Usage: ./colony_message.sh reply <message-id> <message>"
             exit 1
         fi
+        colony_messages reply --from "$AGENT_ID" --project-dir "$PWD" -- "$2" "$3"
+        ;;
 
-        TIMESTAMP=$(date -u +"%Y-%m-%dT%H:%M:%SZ")
-        # Include nanoseconds to prevent ID collisions
-        TIMESTAMP_NS=$(date +%s%N 2>/dev/null || date +%s000000000)
-        MSG_ID="${{AGENT_ID}}-${{TIMESTAMP_NS}}"
-
-        # Capture context: current directory and git branch
-        PROJECT_DIR="$PWD"
-        GIT_BRANCH=$(git rev-parse --abbrev-ref HEAD 2>/dev/null || echo "")
-
-        mkdir -p "$COLONY_ROOT/messages/$RECIPIENT"
-        mkdir -p "$COLONY_ROOT/messages/$AGENT_ID/sent"
-
-        # Use jq to safely construct JSON (prevents injection)
-        if command -v jq >/dev/null 2>&1; then
-            jq -n \
-                --arg id "$MSG_ID" \
-                --arg from "$AGENT_ID" \
-                --arg to "$RECIPIENT" \
-                --arg content "$MESSAGE" \
-                --arg timestamp "$TIMESTAMP" \
-                --arg project_dir "$PROJECT_DIR" \
-                --arg git_branch "$GIT_BRANCH" \
-                '{{
-                    id: $id,
-                    from: $from,
-                    to: $to,
-                    content: $content,
-                    timestamp: $timestamp,
-                    message_type: "info",
-                    project_dir: (if $project_dir != "" then $project_dir else null end),
-                    git_branch: (if $git_branch != "" then $git_branch else null end)
-                }}' > "$COLONY_ROOT/messages/$RECIPIENT/${{MSG_ID}}.json"
+    read)
+        # Usage: ./colony_message.sh read [--all]
+        # Shows messages not read yet and marks them read; --all shows every message
+        if [ "$2" = "--all" ]; then
+            colony_messages read --all "$AGENT_ID"
         else
-            # Fallback: use Python for proper JSON escaping if available
-            if command -v python3 >/dev/null 2>&1; then
-                python3 -c "import json; print(json.dumps({{
-                    'id': '''$MSG_ID''',
-                    'from': '''$AGENT_ID''',
-                    'to': '''$RECIPIENT''',
-                    'content': '''$MESSAGE''',
-                    'timestamp': '''$TIMESTAMP''',
-                    'message_type': 'info',
-                    'project_dir': '''$PROJECT_DIR''' if '''$PROJECT_DIR''' else None,
-                    'git_branch': '''$GIT_BRANCH''' if '''$GIT_BRANCH''' else None
-                }}, indent=2))" > "$COLONY_ROOT/messages/$RECIPIENT/${{MSG_ID}}.json"
-            else
-                # Last resort: manual JSON escaping with improved handling
-                # Escape backslashes first (must be first), then quotes, then newlines
-                ESCAPED_MSG=$(printf '%s' "$MESSAGE" | sed 's/\\/\\\\/g' | sed 's/"/\\"/g' | sed ':a;N;$!ba;s/\n/\\n/g')
-                ESCAPED_DIR=$(printf '%s' "$PROJECT_DIR" | sed 's/\\/\\\\/g' | sed 's/"/\\"/g')
-                ESCAPED_BRANCH=$(printf '%s' "$GIT_BRANCH" | sed 's/\\/\\\\/g' | sed 's/"/\\"/g')
-                # Use printf to avoid issues with special characters in echo
-                printf '{{
-  "id": "%s",
-  "from": "%s",
-  "to": "%s",
-  "content": "%s",
-  "timestamp": "%s",
-  "message_type": "info",
-  "project_dir": "%s",
-  "git_branch": %s
-}}\n' "$MSG_ID" "$AGENT_ID" "$RECIPIENT" "$ESCAPED_MSG" "$TIMESTAMP" "$ESCAPED_DIR" "$(if [ -n "$ESCAPED_BRANCH" ]; then echo "\"$ESCAPED_BRANCH\""; else echo "null"; fi)" > "$COLONY_ROOT/messages/$RECIPIENT/${{MSG_ID}}.json"
-            fi
+            colony_messages read "$AGENT_ID"
         fi
-
-        cp "$COLONY_ROOT/messages/$RECIPIENT/${{MSG_ID}}.json" "$COLONY_ROOT/messages/$AGENT_ID/sent/"
-        echo "Message sent to $RECIPIENT"
         ;;
 
-    read)
-        # Usage: ./colony_message.sh read
-        echo "=== Messages for {} ==="
-        if [ -d "$COLONY_ROOT/messages/$AGENT_ID" ]; then
-            for msg in "$COLONY_ROOT/messages/$AGENT_ID"/*.json; do
-                [ -e "$msg" ] || continue
-                echo "---"
-                if command -v jq >/dev/null 2>&1; then
-                    cat "$msg" | jq -r '
-                        "From: \(.from)" +
-                        (if .project_dir then " [\(.project_dir)]" else "" end) +
-                        (if .git_branch then " (\(.git_branch))" else "" end) +
-                        "\n\(.content)"'
-                else
-                    # Fallback: simple grep-based parsing
-                    from=$(grep -o '"from": *"[^"]*"' "$msg" | sed 's/"from": *"\([^"]*\)"/\1/')
-                    content=$(grep -o '"content": *"[^"]*"' "$msg" | sed 's/"content": *"\([^"]*\)"/\1/')
-                    project_dir=$(grep -o '"project_dir": *"[^"]*"' "$msg" | sed 's/"project_dir": *"\([^"]*\)"/\1/' || echo "")
-                    git_branch=$(grep -o '"git_branch": *"[^"]*"' "$msg" | sed 's/"git_branch": *"\([^"]*\)"/\1/' || echo "")
-                    echo -n "From: $from"
-                    [ -n "$project_dir" ] && echo -n " [$project_dir]"
-                    [ -n "$git_branch" ] && echo -n " ($git_branch)"
-                    echo ""
-                    echo "$content"
-                fi
-            done
+    ack)
+        # Usage: ./colony_message.sh ack <message-id>
+        if [ -z "$2" ]; then
+            echo "Usage: ./colony_message.sh ack <message-id>"
+            exit 1
         fi
+        colony_messages ack "$AGENT_ID" "$2"
+        ;;
 
-        # Also check broadcast messages
-        if [ -d "$COLONY_ROOT/messages/broadcast" ]; then
-            for msg in "$COLONY_ROOT/messages/broadcast"/*.json; do
-                [ -e "$msg" ] || continue
-                echo "---"
-                if command -v jq >/dev/null 2>&1; then
-                    cat "$msg" | jq -r '
-                        "[BROADCAST] From: \(.from)" +
-                        (if .project_dir then " [\(.project_dir)]" else "" end) +
-                        (if .git_branch then " (\(.git_branch))" else "" end) +
-                        "\n\(.content)"'
-                else
-                    # Fallback: simple grep-based parsing
-                    from=$(grep -o '"from": *"[^"]*"' "$msg" | sed 's/"from": *"\([^"]*\)"/\1/')
-                    content=$(grep -o '"content": *"[^"]*"' "$msg" | sed 's/"content": *"\([^"]*\)"/\1/')
-                    project_dir=$(grep -o '"project_dir": *"[^"]*"' "$msg" | sed 's/"project_dir": *"\([^"]*\)"/\1/' || echo "")
-                    git_branch=$(grep -o '"git_branch": *"[^"]*"' "$msg" | sed 's/"git_branch": *"\([^"]*\)"/\1/' || echo "")
-                    echo -n "[BROADCAST] From: $from"
-                    [ -n "$project_dir" ] && echo -n " [$project_dir]"
-                    [ -n "$git_branch" ] && echo -n " ($git_branch)"
-                    echo ""
-                    echo "$content"
-                fi
-            done
-        fi
+    sent)
+        # Usage: ./colony_message.sh sent
+        # Shows your messages and which agents have read them
+        colony_messages sent "$AGENT_ID"
         ;;
 
     list-agents)
@@ -370,14 +551,19 @@ case "$1" in
     *)
         echo "Colony Messaging Helper"
         echo "Usage:"
-        echo "  ./colony_message.sh send <recipient> <message>  - Send a message"
-        echo "  ./colony_message.sh read                        - Read your messages"
+        echo "  ./colony_message.sh send [--type <type>] <recipient> <message> - Send a message"
+        echo "      <recipient> is an agent ID, 'all', group:<name>, role:<role> or tag:<tag>"
+        echo "      <type> is info (default), task, question, answer, completed or error"
+        echo "  ./colony_message.sh reply <message-id> <message> - Reply to a message"
+        echo "  ./colony_message.sh read [--all]                - Read new messages (--all for every message)"
+        echo "  ./colony_message.sh ack <message-id>            - Mark a message read"
+        echo "  ./colony_message.sh sent                        - Show who has read your messages"
         echo "  ./colony_message.sh list-agents                 - List all agents"
         exit 1
         ;;
 esac
 "#,
-        agent_id, escaped_root, escaped_agent, agent_id
+        agent_id, escaped_root, escaped_agent, escaped_bin
     );
 
     fs::write(&script_path, script_content)?;
@@ -449,17 +635,13 @@ This colony uses a message queue system for inter-agent communication.
 
 ## Messaging Structure
 
-Messages are stored in `.colony/messages/` with the following structure:
+Messages are stored in the colony's message store, the SQLite database at
+`.colony/messages.db`. Each message is delivered to its recipient's inbox,
+to every matching agent's inbox for a group, role or tag, or to the broadcast
+inbox that all agents see.
 
-```
-.colony/messages/
-├── agent-1/              # Inbox for agent-1
-│   └── message-id.json
-├── agent-2/              # Inbox for agent-2
-│   └── message-id.json
-└── broadcast/            # Broadcast messages (visible to all)
-    └── message-id.json
-```
+Which messages each agent has read is recorded in `.colony/read/<agent-id>`,
+one message ID per line.
 
 ## How to Communicate
 
@@ -471,31 +653,38 @@ Each agent has a `colony_message.sh` script in their project directory:
 # Send a message to another agent
 ./colony_message.sh send backend-1 "API endpoints are ready for integration"
 
-# Read your messages
+# Read new messages (they are marked read), or every message with --all
 ./colony_message.sh read
+./colony_message.sh read --all
+
+# Mark a message read without reading it
+./colony_message.sh ack backend-1-1736510400-123456789
+
+# See which agents have read the messages you sent
+./colony_message.sh sent
+
+# Reply to a message, keeping the conversation together
+./colony_message.sh reply backend-1-1736510400-123456789 "Yes, v2 is stable"
 
 # List all agents in the colony
 ./colony_message.sh list-agents
 ```
 
-### Using Direct File Operations
+### Using the colony CLI
 
-You can also read/write messages directly. Replace `backend-1` with your actual agent ID:
+The same operations are available as `colony messages` commands, along with
+full-text search and a JSON export of the whole store:
 
 ```bash
-# Check your inbox (replace 'backend-1' with your agent ID)
-ls .colony/messages/backend-1/
-
-# Read a specific message
-cat .colony/messages/backend-1/message-id.json
-
-# Check broadcast messages (visible to all agents)
-ls .colony/messages/broadcast/
+colony messages list backend-1
+colony messages search "migration" --from backend-1 --since 2h
+colony messages export ./messages-backup
 ```
 
 ## Message Format
 
-Messages are JSON files with this structure:
+Each message has this structure, which is also how `colony messages export`
+writes it:
 
 ```json
 {
@@ -504,10 +693,15 @@ Messages are JSON files with this structure:
   "to": "recipient-agent-id",
   "content": "Message content here",
   "timestamp": "2025-01-10T12:00:00Z",
-  "message_type": "info"
+  "message_type": "info",
+  "reply_to": null,
+  "thread_id": null
 }
 ```
 
+A reply sets `reply_to` to the ID of the message it answers and `thread_id`
+to the ID of the first message in the conversation.
+
 ## Message Types
 
 - `info`: General information
@@ -519,9 +713,9 @@ Messages are JSON files with this structure:
 
 ## Best Practices
 
-1. **Check messages regularly**: Run `./colony_message.sh read` periodically
+1. **Check messages regularly**: Run `./colony_message.sh read` periodically; it only shows what you haven't read
 2. **Be specific**: Include context in your messages
-3. **Use task type for coordination**: Use message_type "task" for work assignments
+3. **Use task type for coordination**: Send work assignments with `--type task`
 4. **Broadcast important updates**: Send to "all" for colony-wide announcements
 5. **Clean communication**: Keep messages concise and actionable
 
@@ -536,7 +730,40 @@ Post a message when you start working on something to avoid duplicate work:
 
 ### Asking for Help
 ```bash
-./colony_message.sh send backend-1 "Need API endpoint spec for user profile"
+./colony_message.sh send --type question backend-1 "Need API endpoint spec for user profile"
+```
+
+### Handing Off Work
+`send` takes `--type` before the recipient: `info` (the default), `task`,
+`question`, `answer`, `completed` or `error`. With `delivery` enabled in
+colony.yml, tasks and questions are announced in the recipient's pane as they
+arrive:
+
+```bash
+./colony_message.sh send --type task frontend-1 "Wire the login form to /api/v2/session"
+```
+
+### Messaging Several Agents
+Each of these addresses delivers a copy to every agent it matches:
+
+- `worker` - every replica of an agent with `replicas` set (`worker-1`, `worker-2`, ...)
+- `group:reviewers` - the agents in a group defined under `groups:` in colony.yml
+- `role:"Backend Engineer"` - every agent with that role
+- `tag:frontend` - every agent with that tag
+
+```bash
+./colony_message.sh send worker "Pick up the next open bug from the tracker"
+./colony_message.sh send group:reviewers "PR #42 is ready for review"
+./colony_message.sh send role:"Backend Engineer" "Schema migration lands at noon"
+```
+
+### Answering Questions
+Reply with the question's ID (shown by `read`) rather than starting a new
+message, so the answer stays in the question's thread. Replies to a question
+are sent as answers:
+
+```bash
+./colony_message.sh reply frontend-1-1736510400-123456789 "The endpoint is /api/v2/users"
 ```
 
 ### Sharing Findings
@@ -554,3 +781,252 @@ Post a message when you start working on something to avoid duplicate work:
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_threads() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+
+        let question = Message::new(
+            "frontend",
+            "backend",
+            "Is v2 stable?".to_string(),
+            MessageType::Question,
+        );
+        question.save(colony_root).unwrap();
+        let answer = Message::new(
+            "backend",
+            "frontend",
+            "Yes".to_string(),
+            MessageType::Answer,
+        )
+        .in_reply_to(&question);
+        answer.save(colony_root).unwrap();
+        let follow_up = Message::new(
+            "frontend",
+            "backend",
+            "Thanks".to_string(),
+            MessageType::Info,
+        )
+        .in_reply_to(&answer);
+        follow_up.save(colony_root).unwrap();
+        let other = Message::new("backend", "all", "Deploying".to_string(), MessageType::Info);
+        other.save(colony_root).unwrap();
+
+        assert_eq!(answer.reply_to.as_deref(), Some(question.id.as_str()));
+        assert_eq!(follow_up.thread(), question.id);
+
+        let thread = load_thread(colony_root, &follow_up.id).unwrap();
+        assert_eq!(thread.len(), 3);
+        assert!(load_thread(colony_root, "missing").is_err());
+
+        let all = load_all_messages(colony_root).unwrap();
+        let tree: Vec<(usize, &str)> = thread_tree(&all)
+            .into_iter()
+            .map(|(depth, message)| (depth, message.content.as_str()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (0, "Is v2 stable?"),
+                (1, "Yes"),
+                (2, "Thanks"),
+                (0, "Deploying")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tracking() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+
+        let direct = Message::new("backend", "frontend", "Hi".to_string(), MessageType::Info);
+        direct.save(colony_root).unwrap();
+        let broadcast = Message::new("backend", "all", "Deploying".to_string(), MessageType::Info);
+        broadcast.save(colony_root).unwrap();
+
+        // A broadcast reaches agents without an inbox of their own
+        assert_eq!(load_unread_messages(colony_root, "docs").unwrap().len(), 1);
+        assert_eq!(
+            load_unread_messages(colony_root, "frontend").unwrap().len(),
+            2
+        );
+        assert!(load_unread_messages(colony_root, "backend")
+            .unwrap()
+            .is_empty());
+
+        mark_read(colony_root, "frontend", &[&direct.id]).unwrap();
+        mark_read(colony_root, "docs", &[&broadcast.id]).unwrap();
+        let unread = load_unread_messages(colony_root, "frontend").unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].id, broadcast.id);
+
+        assert_eq!(readers(colony_root, &direct.id).unwrap(), vec!["frontend"]);
+        assert_eq!(readers(colony_root, &broadcast.id).unwrap(), vec!["docs"]);
+    }
+
+    #[test]
+    fn test_message_store() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+
+        // Messages older versions wrote as files, including into messages/all/,
+        // are imported once when the store is opened
+        let old = Message::new("docs", "all", "Docs moved".to_string(), MessageType::Info);
+        let all_dir = colony_root.join("messages").join("all");
+        fs::create_dir_all(&all_dir).unwrap();
+        fs::write(
+            all_dir.join(format!("{}.json", old.id)),
+            serde_json::to_string(&old).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            load_messages_for_agent(colony_root, "backend")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(!colony_root.join("messages").exists());
+
+        // New messages only go to the store
+        Message::new("frontend", "backend", "Ping".to_string(), MessageType::Info)
+            .save(colony_root)
+            .unwrap();
+        assert!(!colony_root.join("messages").exists());
+        assert_eq!(
+            load_messages_for_agent(colony_root, "backend")
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(load_all_messages(colony_root).unwrap().len(), 2);
+
+        let search = MessageSearch {
+            text: Some("ping".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search_messages(colony_root, &search).unwrap().len(), 1);
+
+        let export_dir = dir.path().join("export");
+        assert_eq!(export_messages(colony_root, &export_dir).unwrap(), 2);
+        assert!(export_dir.join(format!("{}.json", old.id)).exists());
+    }
+
+    #[test]
+    fn test_group_messages() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: APIs
+    tags: [api]
+  - id: review
+    role: Reviewer
+    focus: Pull requests
+    replicas: 2
+    tags: [api]
+  - id: docs
+    role: Writer
+    focus: Docs
+groups:
+  reviewers: [review, backend]
+"#;
+        let config: crate::colony::ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        write_groups(colony_root, &config.message_groups()).unwrap();
+
+        let resolve = |to: &str| resolve_recipients(colony_root, to).unwrap();
+        assert_eq!(
+            resolve("group:reviewers"),
+            vec!["review-1", "review-2", "backend"]
+        );
+        assert_eq!(resolve("role:Reviewer"), vec!["review-1", "review-2"]);
+        assert_eq!(resolve("tag:api"), vec!["backend", "review-1", "review-2"]);
+        assert_eq!(resolve("review"), vec!["review-1", "review-2"]);
+        assert_eq!(resolve("docs"), vec!["docs"]);
+        assert!(resolve_recipients(colony_root, "group:ghost").is_err());
+        assert!(resolve_recipients(colony_root, "tag:ghost").is_err());
+
+        // One copy per member, none for the sender or anyone outside the group
+        let message = Message::new(
+            "backend",
+            "group:reviewers",
+            "Please review #42".to_string(),
+            MessageType::Task,
+        );
+        message.save(colony_root).unwrap();
+        for agent_id in ["review-1", "review-2"] {
+            let inbox = load_messages_for_agent(colony_root, agent_id).unwrap();
+            assert_eq!(inbox.len(), 1);
+            assert_eq!(inbox[0].id, message.id);
+        }
+        for agent_id in ["backend", "docs"] {
+            assert!(load_messages_for_agent(colony_root, agent_id)
+                .unwrap()
+                .is_empty());
+        }
+        assert_eq!(load_all_messages(colony_root).unwrap().len(), 1);
+
+        let unknown = Message::new("docs", "group:ghost", "Hi".to_string(), MessageType::Info);
+        assert!(unknown.save(colony_root).is_err());
+    }
+
+    #[test]
+    fn test_message_type_from_str() {
+        assert_eq!("task".parse::<MessageType>(), Ok(MessageType::Task));
+        assert_eq!("question".parse::<MessageType>(), Ok(MessageType::Question));
+        assert!("urgent".parse::<MessageType>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_helper_script_message_type() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path().join(".colony");
+        fs::create_dir_all(colony_root.join("projects").join("frontend")).unwrap();
+        let script = create_message_helper_script(&colony_root, "frontend", None).unwrap();
+
+        // Stand in for the colony binary, recording how it was run
+        let colony_bin = dir.path().join("colony");
+        let calls = dir.path().join("calls");
+        fs::write(
+            &colony_bin,
+            format!("#!/bin/sh\necho \"$PWD $*\" >> '{}'\n", calls.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&colony_bin, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let send = |args: &[&str]| {
+            std::process::Command::new("bash")
+                .arg(&script)
+                .arg("send")
+                .args(args)
+                .current_dir(&colony_root)
+                .env("COLONY_BIN", &colony_bin)
+                .status()
+                .unwrap()
+        };
+        assert!(send(&["backend", "FYI"]).success());
+        assert!(send(&["--type", "task", "backend", "Review the API"]).success());
+
+        let root = dir.path().display();
+        let project = colony_root.display();
+        assert_eq!(
+            fs::read_to_string(&calls).unwrap(),
+            format!(
+                "{root} messages send --from frontend --type info --project-dir {project} -- backend FYI\n\
+                 {root} messages send --from frontend --type task --project-dir {project} -- backend Review the API\n"
+            )
+        );
+    }
+}
@@ -4,14 +4,21 @@
 /// running in parallel with proper isolation and state management.
 pub mod agent;
 pub mod agent_skills;
+pub mod apply;
 pub mod attach;
 pub mod auth;
 pub mod auth_cmd;
+pub mod blueprint;
 pub mod broadcast;
 pub mod config;
+pub mod config_cmd;
+pub mod config_layers;
 pub mod controller;
+pub mod delivery;
 pub mod destroy;
+pub mod dry_run;
 pub mod executor;
+pub mod headless;
 pub mod health;
 pub mod init;
 pub mod layout;
@@ -22,10 +29,16 @@ pub mod messages_cmd;
 pub mod messaging;
 pub mod metrics;
 pub mod metrics_cmd;
+pub mod multiplexer;
+pub mod nudge;
 pub mod plugin;
 pub mod plugin_cmd;
+pub mod prompt;
 pub mod relay;
 pub mod relay_cmd;
+pub mod restart;
+pub mod scale;
+pub mod secrets;
 pub mod skills;
 pub mod start;
 pub mod state;
@@ -33,6 +46,7 @@ pub mod state_cmd;
 pub mod state_integration;
 pub mod status;
 pub mod stop;
+pub mod supervise;
 pub mod tasks;
 pub mod tasks_cmd;
 pub mod telemetry;
@@ -0,0 +1,377 @@
+//! In-memory multiplexer for tests
+//!
+//! Keeps sessions, windows and panes in memory and records what is run in
+//! each pane, so code that drives a multiplexer can be tested without tmux.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{Multiplexer, PaneState};
+use crate::error::{ColonyError, ColonyResult};
+
+/// A pane in a [`FakeMultiplexer`] session
+#[derive(Debug, Clone)]
+pub struct FakePane {
+    pub id: String,
+    pub title: Option<String>,
+    /// Commands the pane was started with or had typed into it, in order
+    pub commands: Vec<String>,
+    /// Columns (side by side) or rows (stacked) requested when the pane was split off
+    pub length: Option<u16>,
+    /// Text typed into the pane with [`Multiplexer::send_text`]
+    pub input: Vec<String>,
+    pub log_path: Option<PathBuf>,
+    pub remain_on_exit: bool,
+    pub state: PaneState,
+}
+
+/// A window in a [`FakeMultiplexer`] session
+#[derive(Debug, Clone, Default)]
+pub struct FakeWindow {
+    pub name: String,
+    pub layout: Option<String>,
+    pub panes: Vec<FakePane>,
+}
+
+/// Size of every window in a [`FakeMultiplexer`] session
+pub const WINDOW_SIZE: (u16, u16) = (200, 50);
+
+/// A [`FakeMultiplexer`] session
+#[derive(Debug, Clone, Default)]
+pub struct FakeSession {
+    pub windows: Vec<FakeWindow>,
+}
+
+impl FakeSession {
+    /// Find the pane with a title
+    pub fn pane_titled(&self, title: &str) -> Option<&FakePane> {
+        self.windows
+            .iter()
+            .flat_map(|w| &w.panes)
+            .find(|p| p.title.as_deref() == Some(title))
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    sessions: BTreeMap<String, FakeSession>,
+    next_pane: usize,
+}
+
+impl Inner {
+    fn new_pane(&mut self, command: Option<&str>) -> FakePane {
+        let id = format!("%{}", self.next_pane);
+        self.next_pane += 1;
+        FakePane {
+            id,
+            title: None,
+            commands: command.map(|c| vec![c.to_string()]).unwrap_or_default(),
+            length: None,
+            input: Vec::new(),
+            log_path: None,
+            remain_on_exit: false,
+            state: PaneState::Running,
+        }
+    }
+
+    fn window(&mut self, session_name: &str, window: usize) -> ColonyResult<&mut FakeWindow> {
+        self.sessions
+            .get_mut(session_name)
+            .and_then(|s| s.windows.get_mut(window))
+            .ok_or_else(|| not_found(&format!("{}:{}", session_name, window)))
+    }
+
+    fn pane(
+        &mut self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+    ) -> ColonyResult<&mut FakePane> {
+        self.window(session_name, window)?
+            .panes
+            .get_mut(pane)
+            .ok_or_else(|| not_found(&format!("{}:{}.{}", session_name, window, pane)))
+    }
+
+    fn pane_by_id(&mut self, pane_id: &str) -> ColonyResult<&mut FakePane> {
+        self.sessions
+            .values_mut()
+            .flat_map(|s| s.windows.iter_mut())
+            .flat_map(|w| w.panes.iter_mut())
+            .find(|p| p.id == pane_id)
+            .ok_or_else(|| not_found(pane_id))
+    }
+
+    fn split(&mut self, session_name: &str, window: usize, command: &str) -> ColonyResult<usize> {
+        let pane = self.new_pane(Some(command));
+        let window = self.window(session_name, window)?;
+        window.panes.push(pane);
+        Ok(window.panes.len() - 1)
+    }
+}
+
+fn not_found(target: &str) -> ColonyError {
+    ColonyError::Colony(format!("can't find pane: {}", target))
+}
+
+/// Multiplexer that keeps everything in memory
+#[derive(Default)]
+pub struct FakeMultiplexer {
+    inner: Mutex<Inner>,
+}
+
+impl FakeMultiplexer {
+    fn inner(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Snapshot of a session
+    pub fn session(&self, session_name: &str) -> Option<FakeSession> {
+        self.inner().sessions.get(session_name).cloned()
+    }
+
+    /// Snapshot of a pane
+    pub fn pane(&self, pane_id: &str) -> Option<FakePane> {
+        self.inner().pane_by_id(pane_id).ok().cloned()
+    }
+
+    /// Simulate the program in a pane exiting (or the pane being closed)
+    pub fn set_pane_state(&self, pane_id: &str, state: PaneState) {
+        let mut inner = self.inner();
+        if state == PaneState::Missing {
+            for window in inner
+                .sessions
+                .values_mut()
+                .flat_map(|s| s.windows.iter_mut())
+            {
+                window.panes.retain(|p| p.id != pane_id);
+            }
+        } else if let Ok(pane) = inner.pane_by_id(pane_id) {
+            pane.state = state;
+        }
+    }
+}
+
+impl Multiplexer for FakeMultiplexer {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        self.inner().sessions.contains_key(session_name)
+    }
+
+    fn create_session(&self, session_name: &str) -> ColonyResult<()> {
+        let mut inner = self.inner();
+        if inner.sessions.contains_key(session_name) {
+            return Err(ColonyError::Colony(format!(
+                "duplicate session: {}",
+                session_name
+            )));
+        }
+        let pane = inner.new_pane(None);
+        inner.sessions.insert(
+            session_name.to_string(),
+            FakeSession {
+                windows: vec![FakeWindow {
+                    name: "0".to_string(),
+                    layout: None,
+                    panes: vec![pane],
+                }],
+            },
+        );
+        Ok(())
+    }
+
+    fn kill_session(&self, session_name: &str) -> ColonyResult<()> {
+        self.inner().sessions.remove(session_name);
+        Ok(())
+    }
+
+    fn attach_session(&self, session_name: &str) -> ColonyResult<()> {
+        if !self.session_exists(session_name) {
+            return Err(not_found(session_name));
+        }
+        Ok(())
+    }
+
+    fn create_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+        let mut inner = self.inner();
+        let pane = inner.new_pane(None);
+        let session = inner
+            .sessions
+            .get_mut(session_name)
+            .ok_or_else(|| not_found(session_name))?;
+        if window > session.windows.len() {
+            return Err(not_found(&format!("{}:{}", session_name, window)));
+        }
+        session.windows.insert(
+            window,
+            FakeWindow {
+                name: name.to_string(),
+                layout: None,
+                panes: vec![pane],
+            },
+        );
+        Ok(())
+    }
+
+    fn rename_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+        self.inner().window(session_name, window)?.name = name.to_string();
+        Ok(())
+    }
+
+    fn window_size(&self, session_name: &str, window: usize) -> ColonyResult<(u16, u16)> {
+        self.inner().window(session_name, window)?;
+        Ok(WINDOW_SIZE)
+    }
+
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        _horizontal: bool,
+        length: u16,
+    ) -> ColonyResult<usize> {
+        let mut inner = self.inner();
+        inner.pane(session_name, window, pane)?;
+        let mut new_pane = inner.new_pane(None);
+        new_pane.length = Some(length);
+        inner
+            .window(session_name, window)?
+            .panes
+            .insert(pane + 1, new_pane);
+        Ok(pane + 1)
+    }
+
+    fn create_background_window(
+        &self,
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+    ) -> ColonyResult<()> {
+        let mut inner = self.inner();
+        let pane = inner.new_pane(Some(command));
+        let session = inner
+            .sessions
+            .get_mut(session_name)
+            .ok_or_else(|| not_found(session_name))?;
+        session.windows.push(FakeWindow {
+            name: window_name.to_string(),
+            layout: None,
+            panes: vec![pane],
+        });
+        Ok(())
+    }
+
+    fn split_horizontal(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        self.inner().split(session_name, 0, command)
+    }
+
+    fn split_vertical(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        self.inner().split(session_name, 0, command)
+    }
+
+    fn send_command_to_window_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        command: &str,
+    ) -> ColonyResult<()> {
+        self.inner()
+            .pane(session_name, window, pane)?
+            .commands
+            .push(command.to_string());
+        Ok(())
+    }
+
+    fn set_window_pane_title(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        title: &str,
+    ) -> ColonyResult<()> {
+        self.inner().pane(session_name, window, pane)?.title = Some(title.to_string());
+        Ok(())
+    }
+
+    fn pane_id(&self, session_name: &str, window: usize, pane: usize) -> ColonyResult<String> {
+        Ok(self.inner().pane(session_name, window, pane)?.id.clone())
+    }
+
+    fn select_window_layout(
+        &self,
+        session_name: &str,
+        window: usize,
+        layout: &str,
+    ) -> ColonyResult<()> {
+        self.inner().window(session_name, window)?.layout = Some(layout.to_string());
+        Ok(())
+    }
+
+    fn pane_state(&self, pane_id: &str) -> PaneState {
+        self.inner()
+            .pane_by_id(pane_id)
+            .map(|p| p.state.clone())
+            .unwrap_or(PaneState::Missing)
+    }
+
+    fn split_window_with_id(&self, target: &str, command: &str) -> ColonyResult<String> {
+        let mut inner = self.inner();
+        let (session_name, window) = target
+            .rsplit_once(':')
+            .and_then(|(s, w)| Some((s.to_string(), w.parse::<usize>().ok()?)))
+            .ok_or_else(|| not_found(target))?;
+        let index = inner.split(&session_name, window, command)?;
+        Ok(inner.pane(&session_name, window, index)?.id.clone())
+    }
+
+    fn respawn_pane(&self, pane_id: &str, command: &str) -> ColonyResult<()> {
+        let mut inner = self.inner();
+        let pane = inner.pane_by_id(pane_id)?;
+        pane.commands.push(command.to_string());
+        pane.state = PaneState::Running;
+        Ok(())
+    }
+
+    fn kill_pane(&self, pane_id: &str) -> ColonyResult<()> {
+        self.inner().pane_by_id(pane_id)?;
+        self.set_pane_state(pane_id, PaneState::Missing);
+        Ok(())
+    }
+
+    fn set_remain_on_exit(&self, pane_id: &str) -> ColonyResult<()> {
+        self.inner().pane_by_id(pane_id)?.remain_on_exit = true;
+        Ok(())
+    }
+
+    fn pipe_pane_to_file(&self, pane_id: &str, path: &Path) -> ColonyResult<()> {
+        self.inner().pane_by_id(pane_id)?.log_path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    fn set_title(&self, pane_id: &str, title: &str) -> ColonyResult<()> {
+        self.inner().pane_by_id(pane_id)?.title = Some(title.to_string());
+        Ok(())
+    }
+
+    fn send_text(&self, pane_id: &str, text: &str) -> ColonyResult<()> {
+        self.inner()
+            .pane_by_id(pane_id)?
+            .input
+            .push(text.to_string());
+        Ok(())
+    }
+
+    fn capture_pane(&self, pane_id: &str) -> ColonyResult<String> {
+        Ok(self.inner().pane_by_id(pane_id)?.input.join("\n"))
+    }
+}
@@ -0,0 +1,215 @@
+//! Terminal multiplexer backends
+//!
+//! Agents run in panes of a terminal multiplexer session. The [`Multiplexer`]
+//! trait covers everything colony needs from one, so the rest of the code does
+//! not depend on tmux directly. The backend is chosen with `multiplexer:` in
+//! colony.yml.
+
+#[cfg(test)]
+pub mod fake;
+pub mod tmux;
+pub mod zellij;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{ColonyError, ColonyResult};
+
+pub use tmux::TmuxMultiplexer;
+pub use zellij::ZellijMultiplexer;
+
+/// Supported multiplexer backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiplexerKind {
+    #[default]
+    Tmux,
+    Zellij,
+}
+
+impl MultiplexerKind {
+    pub fn is_default(&self) -> bool {
+        *self == MultiplexerKind::default()
+    }
+}
+
+/// Liveness of a pane as seen by the multiplexer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaneState {
+    /// The program started in the pane is still running
+    Running,
+    /// The pane exists but its program exited (or it dropped back to an idle shell)
+    Dead,
+    /// The pane no longer exists
+    Missing,
+}
+
+/// Operations colony performs on a multiplexer session
+///
+/// Panes are addressed either by session, window and pane index (while a
+/// session is being laid out) or by the stable pane ID returned by
+/// [`Multiplexer::pane_id`], which is what gets stored in the agent state.
+pub trait Multiplexer: Send + Sync {
+    /// Name of the backend, as shown to users
+    fn name(&self) -> &'static str;
+
+    /// Check whether the multiplexer is installed
+    fn is_available(&self) -> bool;
+
+    /// Try to install the multiplexer
+    fn install(&self) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "automatic installation"))
+    }
+
+    /// Check whether a session exists
+    fn session_exists(&self, session_name: &str) -> bool;
+
+    /// Create a detached session with a single pane
+    fn create_session(&self, session_name: &str) -> ColonyResult<()>;
+
+    /// Kill a session if it exists
+    fn kill_session(&self, session_name: &str) -> ColonyResult<()>;
+
+    /// Attach the current terminal to a session
+    fn attach_session(&self, session_name: &str) -> ColonyResult<()>;
+
+    /// Whether windows and panes can be laid out from a custom layout
+    fn supports_custom_layouts(&self) -> bool {
+        true
+    }
+
+    /// Whether panes can be addressed by a stable ID, which pane logging,
+    /// restarts, the supervisor, the nudger and message delivery rely on
+    fn supports_pane_ids(&self) -> bool {
+        true
+    }
+
+    /// Create an empty window at an index without taking focus
+    fn create_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()>;
+
+    /// Rename a window
+    fn rename_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()>;
+
+    /// Get the size of a window as (columns, rows)
+    fn window_size(&self, session_name: &str, window: usize) -> ColonyResult<(u16, u16)>;
+
+    /// Split a window:pane, giving the new pane `length` columns (side by side)
+    /// or rows (stacked) and running a shell in it
+    /// Returns the index of the new pane, which comes right after the split one
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        horizontal: bool,
+        length: u16,
+    ) -> ColonyResult<usize>;
+
+    /// Create a window that doesn't take focus, running a command
+    fn create_background_window(
+        &self,
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+    ) -> ColonyResult<()>;
+
+    /// Split the first window side by side, running a command in the new pane
+    /// Returns the index of the new pane
+    fn split_horizontal(&self, session_name: &str, command: &str) -> ColonyResult<usize>;
+
+    /// Split the first window top and bottom, running a command in the new pane
+    /// Returns the index of the new pane
+    fn split_vertical(&self, session_name: &str, command: &str) -> ColonyResult<usize>;
+
+    /// Type a command into a window:pane and press Enter
+    fn send_command_to_window_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        command: &str,
+    ) -> ColonyResult<()>;
+
+    /// Set the title of a window:pane
+    fn set_window_pane_title(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        title: &str,
+    ) -> ColonyResult<()>;
+
+    /// Get the stable ID of a window:pane
+    fn pane_id(&self, session_name: &str, window: usize, pane: usize) -> ColonyResult<String>;
+
+    /// Apply a named layout (e.g. `tiled`) to a window
+    fn select_window_layout(
+        &self,
+        session_name: &str,
+        window: usize,
+        layout: &str,
+    ) -> ColonyResult<()>;
+
+    /// Check whether the program in a pane is still running
+    fn pane_state(&self, pane_id: &str) -> PaneState;
+
+    /// Split the window a target belongs to, running a command in the new pane
+    /// Returns the ID of the new pane
+    fn split_window_with_id(&self, target: &str, command: &str) -> ColonyResult<String>;
+
+    /// Replace whatever is running in a pane with a new command
+    fn respawn_pane(&self, pane_id: &str, command: &str) -> ColonyResult<()>;
+
+    /// Close a pane, killing whatever runs in it
+    fn kill_pane(&self, pane_id: &str) -> ColonyResult<()>;
+
+    /// Keep a pane open after its program exits so it can be respawned in place
+    fn set_remain_on_exit(&self, pane_id: &str) -> ColonyResult<()>;
+
+    /// Append everything a pane outputs to a log file
+    fn pipe_pane_to_file(&self, pane_id: &str, path: &Path) -> ColonyResult<()>;
+
+    /// Set the title of a pane
+    fn set_title(&self, pane_id: &str, title: &str) -> ColonyResult<()>;
+
+    /// Type text into a pane and press Enter
+    fn send_text(&self, pane_id: &str, text: &str) -> ColonyResult<()>;
+
+    /// Capture the visible contents of a pane
+    fn capture_pane(&self, pane_id: &str) -> ColonyResult<String>;
+
+    /// Type a command into a pane of the first window and press Enter
+    fn send_command_to_pane(
+        &self,
+        session_name: &str,
+        pane: usize,
+        command: &str,
+    ) -> ColonyResult<()> {
+        self.send_command_to_window_pane(session_name, 0, pane, command)
+    }
+
+    /// Set the title of a pane in the first window
+    fn set_pane_title(&self, session_name: &str, pane: usize, title: &str) -> ColonyResult<()> {
+        self.set_window_pane_title(session_name, 0, pane, title)
+    }
+
+    /// Tile the panes of the first window
+    fn select_tiled_layout(&self, session_name: &str) -> ColonyResult<()> {
+        self.select_window_layout(session_name, 0, "tiled")
+    }
+}
+
+/// Create the multiplexer backend of the given kind
+pub fn from_kind(kind: MultiplexerKind) -> Arc<dyn Multiplexer> {
+    match kind {
+        MultiplexerKind::Tmux => Arc::new(TmuxMultiplexer),
+        MultiplexerKind::Zellij => Arc::new(ZellijMultiplexer::default()),
+    }
+}
+
+/// Error for an operation a backend can't perform
+pub(crate) fn unsupported(backend: &str, operation: &str) -> ColonyError {
+    ColonyError::Colony(format!("{} does not support {}", backend, operation))
+}
@@ -0,0 +1,166 @@
+//! tmux backend
+
+use std::path::Path;
+
+use super::{Multiplexer, PaneState};
+use crate::colony::tmux;
+use crate::error::ColonyResult;
+
+/// Runs agents in a tmux session
+pub struct TmuxMultiplexer;
+
+impl Multiplexer for TmuxMultiplexer {
+    fn name(&self) -> &'static str {
+        "tmux"
+    }
+
+    fn is_available(&self) -> bool {
+        tmux::is_tmux_available()
+    }
+
+    fn install(&self) -> ColonyResult<()> {
+        tmux::try_install_tmux()
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        tmux::session_exists(session_name)
+    }
+
+    fn create_session(&self, session_name: &str) -> ColonyResult<()> {
+        tmux::create_session(session_name)
+    }
+
+    fn kill_session(&self, session_name: &str) -> ColonyResult<()> {
+        tmux::kill_session(session_name)
+    }
+
+    fn attach_session(&self, session_name: &str) -> ColonyResult<()> {
+        tmux::attach_session(session_name)
+    }
+
+    fn create_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+        tmux::create_window(session_name, window, name)
+    }
+
+    fn rename_window(&self, session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+        tmux::rename_window(session_name, window, name)
+    }
+
+    fn window_size(&self, session_name: &str, window: usize) -> ColonyResult<(u16, u16)> {
+        tmux::window_size(session_name, window)
+    }
+
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        horizontal: bool,
+        length: u16,
+    ) -> ColonyResult<usize> {
+        tmux::split_pane(session_name, window, pane, horizontal, length)
+    }
+
+    fn create_background_window(
+        &self,
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+    ) -> ColonyResult<()> {
+        tmux::create_background_window(session_name, window_name, command)
+    }
+
+    fn split_horizontal(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        tmux::split_horizontal(session_name, command)
+    }
+
+    fn split_vertical(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        tmux::split_vertical(session_name, command)
+    }
+
+    fn send_command_to_window_pane(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        command: &str,
+    ) -> ColonyResult<()> {
+        tmux::send_command_to_window_pane(session_name, window, pane, command)
+    }
+
+    fn set_window_pane_title(
+        &self,
+        session_name: &str,
+        window: usize,
+        pane: usize,
+        title: &str,
+    ) -> ColonyResult<()> {
+        tmux::set_window_pane_title(session_name, window, pane, title)
+    }
+
+    fn pane_id(&self, session_name: &str, window: usize, pane: usize) -> ColonyResult<String> {
+        tmux::pane_id(session_name, window, pane)
+    }
+
+    fn select_window_layout(
+        &self,
+        session_name: &str,
+        window: usize,
+        layout: &str,
+    ) -> ColonyResult<()> {
+        tmux::select_window_layout(session_name, window, layout)
+    }
+
+    fn pane_state(&self, pane_id: &str) -> PaneState {
+        tmux::pane_state(pane_id)
+    }
+
+    fn split_window_with_id(&self, target: &str, command: &str) -> ColonyResult<String> {
+        tmux::split_window_with_id(target, command)
+    }
+
+    fn respawn_pane(&self, pane_id: &str, command: &str) -> ColonyResult<()> {
+        tmux::respawn_pane(pane_id, command)
+    }
+
+    fn kill_pane(&self, pane_id: &str) -> ColonyResult<()> {
+        tmux::kill_pane(pane_id)
+    }
+
+    fn set_remain_on_exit(&self, pane_id: &str) -> ColonyResult<()> {
+        tmux::set_remain_on_exit(pane_id)
+    }
+
+    fn pipe_pane_to_file(&self, pane_id: &str, path: &Path) -> ColonyResult<()> {
+        tmux::pipe_pane_to_file(pane_id, path)
+    }
+
+    fn set_title(&self, pane_id: &str, title: &str) -> ColonyResult<()> {
+        tmux::set_target_title(pane_id, title)
+    }
+
+    fn send_text(&self, pane_id: &str, text: &str) -> ColonyResult<()> {
+        tmux::send_text_to_target(pane_id, text)
+    }
+
+    fn capture_pane(&self, pane_id: &str) -> ColonyResult<String> {
+        tmux::capture_pane(pane_id)
+    }
+
+    fn send_command_to_pane(
+        &self,
+        session_name: &str,
+        pane: usize,
+        command: &str,
+    ) -> ColonyResult<()> {
+        tmux::send_command_to_pane(session_name, pane, command)
+    }
+
+    fn set_pane_title(&self, session_name: &str, pane: usize, title: &str) -> ColonyResult<()> {
+        tmux::set_pane_title(session_name, pane, title)
+    }
+
+    fn select_tiled_layout(&self, session_name: &str) -> ColonyResult<()> {
+        tmux::select_tiled_layout(session_name)
+    }
+}
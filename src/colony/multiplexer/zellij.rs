@@ -0,0 +1,283 @@
+//! Zellij backend
+//!
+//! Zellij is driven through `zellij --session <name> action ...`. Its CLI acts
+//! on the focused pane and has no way to address a pane by index or ID, so
+//! commands and titles go to the pane that was created last (which is what
+//! `colony start` needs), and the per-pane operations the supervisor, nudger
+//! and message delivery rely on are not available.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+use super::{unsupported, Multiplexer, PaneState};
+use crate::error::{ColonyError, ColonyResult};
+
+/// Byte Zellij writes for the Enter key
+const ENTER: &str = "13";
+
+/// Runs agents in a Zellij session
+#[derive(Default)]
+pub struct ZellijMultiplexer {
+    /// Number of panes created in the first tab of each session
+    pane_counts: Mutex<HashMap<String, usize>>,
+}
+
+impl ZellijMultiplexer {
+    fn action(&self, session_name: &str, args: &[&str]) -> ColonyResult<Output> {
+        let output = Command::new("zellij")
+            .arg("--session")
+            .arg(session_name)
+            .arg("action")
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ColonyError::Colony(format!(
+                "zellij action {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        Ok(output)
+    }
+
+    fn new_pane(&self, session_name: &str, direction: &str, command: &str) -> ColonyResult<usize> {
+        self.action(
+            session_name,
+            &[
+                "new-pane",
+                "--direction",
+                direction,
+                "--",
+                "sh",
+                "-c",
+                command,
+            ],
+        )?;
+
+        let mut counts = self.pane_counts.lock().unwrap_or_else(|e| e.into_inner());
+        let count = counts.entry(session_name.to_string()).or_insert(1);
+        *count += 1;
+        Ok(*count - 1)
+    }
+
+    fn type_line(&self, session_name: &str, text: &str) -> ColonyResult<()> {
+        self.action(session_name, &["write-chars", text])?;
+        self.action(session_name, &["write", ENTER])?;
+        Ok(())
+    }
+}
+
+impl Multiplexer for ZellijMultiplexer {
+    fn name(&self) -> &'static str {
+        "zellij"
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new("zellij")
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn session_exists(&self, session_name: &str) -> bool {
+        Command::new("zellij")
+            .args(["list-sessions", "--short", "--no-formatting"])
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .any(|line| line.trim() == session_name)
+            })
+            .unwrap_or(false)
+    }
+
+    fn create_session(&self, session_name: &str) -> ColonyResult<()> {
+        let output = Command::new("zellij")
+            .args(["attach", "--create-background", session_name])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ColonyError::Colony(format!(
+                "Failed to create zellij session: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        self.pane_counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(session_name.to_string(), 1);
+        Ok(())
+    }
+
+    fn kill_session(&self, session_name: &str) -> ColonyResult<()> {
+        if !self.session_exists(session_name) {
+            return Ok(());
+        }
+
+        let output = Command::new("zellij")
+            .args(["kill-session", session_name])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(ColonyError::Colony(format!(
+                "Failed to kill zellij session: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        // Exited sessions are kept for resurrection; remove it so the name can be reused
+        let _ = Command::new("zellij")
+            .args(["delete-session", "--force", session_name])
+            .output();
+
+        self.pane_counts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(session_name);
+        Ok(())
+    }
+
+    fn attach_session(&self, session_name: &str) -> ColonyResult<()> {
+        let status = Command::new("zellij")
+            .args(["attach", session_name])
+            .status()?;
+
+        if !status.success() {
+            return Err(ColonyError::Colony(
+                "Failed to attach to zellij session".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn supports_custom_layouts(&self) -> bool {
+        false
+    }
+
+    fn supports_pane_ids(&self) -> bool {
+        false
+    }
+
+    fn create_window(&self, _session_name: &str, _window: usize, _name: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "custom layouts"))
+    }
+
+    fn rename_window(&self, _session_name: &str, _window: usize, _name: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "custom layouts"))
+    }
+
+    fn window_size(&self, _session_name: &str, _window: usize) -> ColonyResult<(u16, u16)> {
+        Err(unsupported(self.name(), "custom layouts"))
+    }
+
+    fn split_pane(
+        &self,
+        _session_name: &str,
+        _window: usize,
+        _pane: usize,
+        _horizontal: bool,
+        _length: u16,
+    ) -> ColonyResult<usize> {
+        Err(unsupported(self.name(), "custom layouts"))
+    }
+
+    fn create_background_window(
+        &self,
+        session_name: &str,
+        window_name: &str,
+        command: &str,
+    ) -> ColonyResult<()> {
+        self.action(session_name, &["new-tab", "--name", window_name])?;
+        self.type_line(session_name, command)?;
+        // Return to the agents' tab
+        self.action(session_name, &["go-to-tab", "1"])?;
+        Ok(())
+    }
+
+    fn split_horizontal(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        self.new_pane(session_name, "right", command)
+    }
+
+    fn split_vertical(&self, session_name: &str, command: &str) -> ColonyResult<usize> {
+        self.new_pane(session_name, "down", command)
+    }
+
+    fn send_command_to_window_pane(
+        &self,
+        session_name: &str,
+        _window: usize,
+        _pane: usize,
+        command: &str,
+    ) -> ColonyResult<()> {
+        // Zellij can only type into the focused pane, which is the one created last
+        self.type_line(session_name, command)
+    }
+
+    fn set_window_pane_title(
+        &self,
+        session_name: &str,
+        _window: usize,
+        _pane: usize,
+        title: &str,
+    ) -> ColonyResult<()> {
+        self.action(session_name, &["rename-pane", title])?;
+        Ok(())
+    }
+
+    fn pane_id(&self, _session_name: &str, _window: usize, _pane: usize) -> ColonyResult<String> {
+        Err(unsupported(self.name(), "pane IDs"))
+    }
+
+    fn select_window_layout(
+        &self,
+        _session_name: &str,
+        _window: usize,
+        _layout: &str,
+    ) -> ColonyResult<()> {
+        // Zellij arranges panes itself
+        Ok(())
+    }
+
+    fn pane_state(&self, _pane_id: &str) -> PaneState {
+        PaneState::Missing
+    }
+
+    fn split_window_with_id(&self, _target: &str, _command: &str) -> ColonyResult<String> {
+        Err(unsupported(self.name(), "pane IDs"))
+    }
+
+    fn respawn_pane(&self, _pane_id: &str, _command: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "respawning panes"))
+    }
+
+    fn kill_pane(&self, _pane_id: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "addressing panes by ID"))
+    }
+
+    fn set_remain_on_exit(&self, _pane_id: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "remain-on-exit"))
+    }
+
+    fn pipe_pane_to_file(&self, _pane_id: &str, _path: &Path) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "piping pane output"))
+    }
+
+    fn set_title(&self, _pane_id: &str, _title: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "addressing panes by ID"))
+    }
+
+    fn send_text(&self, _pane_id: &str, _text: &str) -> ColonyResult<()> {
+        Err(unsupported(self.name(), "addressing panes by ID"))
+    }
+
+    fn capture_pane(&self, _pane_id: &str) -> ColonyResult<String> {
+        Err(unsupported(self.name(), "addressing panes by ID"))
+    }
+}
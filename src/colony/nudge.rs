@@ -0,0 +1,286 @@
+//! Nudge scheduler
+//!
+//! Agents only check their inbox and the task queue when prompted to, so
+//! messages can pile up while an agent sits idle. The nudger runs alongside
+//! the colony (in a background tmux window started by `colony start`) and
+//! periodically types the agent's nudge prompt into its pane when it has new
+//! messages or claimable tasks and isn't busy.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::colony::config::NudgeConfig;
+use crate::colony::multiplexer::Multiplexer;
+use crate::colony::tasks::queue::TaskQueue;
+use crate::colony::{headless, messaging, Agent, ColonyConfig, ColonyController};
+use crate::error::ColonyResult;
+use crate::utils;
+
+/// tmux window the nudger runs in
+pub const NUDGER_WINDOW: &str = "nudger";
+
+/// How often the nudger wakes up to check whether any agent is due
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Text Claude Code shows in its status line while it is working
+const BUSY_MARKER: &str = "esc to interrupt";
+
+/// Work waiting for an agent
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PendingWork {
+    /// Messages the agent hasn't read
+    pub messages: usize,
+    /// Tasks the agent could claim
+    pub tasks: usize,
+}
+
+impl PendingWork {
+    pub fn is_empty(&self) -> bool {
+        self.messages == 0 && self.tasks == 0
+    }
+}
+
+/// Where an agent's terminal can be read and typed into
+pub(crate) enum Target<'a> {
+    /// A multiplexer pane, by pane ID
+    Pane(&'a dyn Multiplexer, &'a str),
+    /// A PTY hosted by `colony start --headless`
+    Headless(&'a Agent),
+}
+
+impl Target<'_> {
+    pub(crate) fn of<'a>(mux: &'a dyn Multiplexer, agent: &'a Agent) -> Option<Target<'a>> {
+        match &agent.pane_id {
+            Some(pane_id) => Some(Target::Pane(mux, pane_id)),
+            None => agent.pid.map(|_| Target::Headless(agent)),
+        }
+    }
+
+    pub(crate) fn capture(&self) -> ColonyResult<String> {
+        match self {
+            Target::Pane(mux, pane_id) => mux.capture_pane(pane_id),
+            Target::Headless(agent) => headless::capture(agent),
+        }
+    }
+
+    pub(crate) fn send(&self, text: &str) -> ColonyResult<()> {
+        match self {
+            Target::Pane(mux, pane_id) => mux.send_text(pane_id, text),
+            Target::Headless(agent) => headless::send_text(agent, text),
+        }
+    }
+}
+
+/// Per-agent scheduling state
+struct NudgeState {
+    config: NudgeConfig,
+    last_check: Option<Instant>,
+    last_nudge: DateTime<Utc>,
+    last_capture: Option<String>,
+}
+
+/// Check whether any agent in the configuration has nudging enabled
+pub fn is_enabled(config: &ColonyConfig) -> bool {
+    config
+        .agents
+        .iter()
+        .any(|agent| config.nudge_config_for(agent).enabled)
+}
+
+/// Run the nudge scheduler until interrupted
+pub async fn run() -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(crate::error::ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    let config = ColonyConfig::load(config_path)?;
+    let mut controller = ColonyController::new(config)?;
+    controller.initialize_agents()?;
+    let mux = controller.multiplexer();
+
+    let started_at = Utc::now();
+    let mut states: HashMap<String, NudgeState> = controller
+        .config()
+        .agents
+        .iter()
+        .filter_map(|agent| {
+            let config = controller.config().nudge_config_for(agent);
+            config.enabled.then(|| {
+                (
+                    agent.id.clone(),
+                    NudgeState {
+                        config,
+                        last_check: None,
+                        last_nudge: started_at,
+                        last_capture: None,
+                    },
+                )
+            })
+        })
+        .collect();
+
+    if states.is_empty() {
+        utils::info("Nudging is not enabled for any agent");
+        return Ok(());
+    }
+
+    utils::header("Colony Nudger");
+    for (agent_id, state) in &states {
+        println!("  {} every {}s", agent_id, state.config.interval);
+    }
+    println!();
+
+    loop {
+        // Reload state every tick so restarted agents are picked up in their new panes
+        let _ = controller.load_state();
+
+        for (agent_id, state) in states.iter_mut() {
+            let interval = Duration::from_secs(state.config.interval.max(1));
+            if state.last_check.is_some_and(|t| t.elapsed() < interval) {
+                continue;
+            }
+            state.last_check = Some(Instant::now());
+
+            let Some(target) = controller
+                .get_agent(agent_id)
+                .and_then(|agent| Target::of(mux.as_ref(), agent))
+            else {
+                continue;
+            };
+
+            if let Err(e) = check_agent(controller.colony_root(), agent_id, &target, state) {
+                utils::warning(&format!("Failed to nudge '{}': {}", agent_id, e));
+            }
+        }
+
+        tokio::time::sleep(TICK_INTERVAL).await;
+    }
+}
+
+/// Nudge an agent if it has pending work and isn't busy
+fn check_agent(
+    colony_root: &Path,
+    agent_id: &str,
+    target: &Target,
+    state: &mut NudgeState,
+) -> ColonyResult<()> {
+    let capture = target.capture()?;
+    let busy = is_busy(state.last_capture.as_deref(), &capture);
+    state.last_capture = Some(capture);
+
+    if busy {
+        return Ok(());
+    }
+
+    let pending = pending_work(colony_root, agent_id)?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let prompt = state
+        .config
+        .prompt
+        .clone()
+        .unwrap_or_else(|| default_prompt(agent_id, &pending));
+    target.send(&prompt)?;
+    state.last_nudge = Utc::now();
+    // The prompt itself changes the pane, don't mistake that for activity
+    state.last_capture = target.capture().ok();
+
+    println!(
+        "[{}] Nudged {} ({} unread messages, {} claimable tasks)",
+        state.last_nudge.format("%H:%M:%S"),
+        agent_id,
+        pending.messages,
+        pending.tasks
+    );
+
+    Ok(())
+}
+
+/// Count unread messages and tasks the agent could claim
+pub fn pending_work(colony_root: &Path, agent_id: &str) -> ColonyResult<PendingWork> {
+    let messages = messaging::load_unread_messages(colony_root, agent_id)?.len();
+
+    let tasks = TaskQueue::new(colony_root)
+        .find_claimable_tasks(agent_id)?
+        .len();
+
+    Ok(PendingWork { messages, tasks })
+}
+
+/// An agent is busy if Claude Code reports it is working or the pane changed
+/// since the last check
+pub(crate) fn is_busy(previous: Option<&str>, current: &str) -> bool {
+    current.contains(BUSY_MARKER) || previous.is_some_and(|p| p != current)
+}
+
+fn default_prompt(agent_id: &str, pending: &PendingWork) -> String {
+    format!(
+        "You have {} unread message(s) and {} claimable task(s). Check messages with ./colony_message.sh read and tasks with colony tasks claimable {}.",
+        pending.messages, pending.tasks, agent_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::messaging::{Message, MessageType};
+    use crate::colony::tasks::Task;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pending_work() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+
+        let api_ready = Message::new(
+            "backend",
+            "frontend",
+            "API ready".to_string(),
+            MessageType::Info,
+        );
+        api_ready.save(colony_root).unwrap();
+        let hello = Message::new("frontend", "all", "Hello".to_string(), MessageType::Info);
+        hello.save(colony_root).unwrap();
+
+        let queue = TaskQueue::new(colony_root);
+        queue.initialize().unwrap();
+        let mut task = Task::new("t1".to_string(), "Build UI".to_string(), String::new());
+        task.assigned_to = Some("frontend".to_string());
+        queue.create_task(task).unwrap();
+
+        let pending = pending_work(colony_root, "frontend").unwrap();
+        assert_eq!(
+            pending,
+            PendingWork {
+                messages: 1,
+                tasks: 1
+            }
+        );
+
+        messaging::mark_read(colony_root, "frontend", &[&api_ready.id]).unwrap();
+        assert_eq!(pending_work(colony_root, "frontend").unwrap().messages, 0);
+
+        // The broadcast from frontend is unread for backend
+        let pending = pending_work(colony_root, "backend").unwrap();
+        assert_eq!(pending.messages, 1);
+        assert_eq!(pending.tasks, 0);
+        messaging::mark_read(colony_root, "backend", &[&hello.id]).unwrap();
+        assert!(pending_work(colony_root, "backend").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_is_busy() {
+        assert!(!is_busy(None, "> "));
+        assert!(!is_busy(Some("> "), "> "));
+        assert!(is_busy(Some("> "), "Thinking..."));
+        assert!(is_busy(None, "✻ Working… (esc to interrupt)"));
+    }
+}
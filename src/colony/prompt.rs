@@ -0,0 +1,491 @@
+//! Templating for agents' `startup_prompt` and `instructions`
+//!
+//! Prompts can use `{{variable}}` substitutions, include shared fragments from
+//! `.colony/prompts/` with `{{> name}}`, and branch with
+//! `{{#if variable}}...{{else}}...{{/if}}`, where a variable is true when it
+//! renders to anything but whitespace. Block tags on a line of their own don't
+//! leave a blank line behind.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::colony::state::{MemoryEntry, MemoryType};
+use crate::colony::tasks::queue::TaskQueue;
+use crate::colony::tasks::TaskStatus;
+use crate::colony::{Agent, ColonyConfig};
+use crate::error::{ColonyError, ColonyResult};
+
+/// Directory under the colony root that shared prompt fragments are included from
+pub const PROMPTS_DIR: &str = "prompts";
+
+/// Variables a prompt can use
+pub const VARIABLES: &[&str] = &[
+    "agent.id",
+    "agent.role",
+    "agent.focus",
+    "agent.worktree",
+    "colony.name",
+    "colony.agents",
+    "repository.purpose",
+    "tasks.assigned",
+    "memory.pinned",
+];
+
+/// How deep includes can nest, which also stops include cycles
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Values for [`VARIABLES`]
+pub type PromptVariables = HashMap<&'static str, String>;
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Variable(String),
+    Include(String),
+    If {
+        variable: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// A parsed prompt template
+#[derive(Debug)]
+pub struct PromptTemplate {
+    nodes: Vec<Node>,
+}
+
+impl PromptTemplate {
+    /// Parse a template, rejecting malformed tags and unknown variables
+    pub fn parse(source: &str) -> ColonyResult<Self> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, _) = parse_block(&mut tokens, false)?;
+        Ok(Self { nodes })
+    }
+
+    /// Check that every fragment the template includes exists and parses
+    pub fn validate(&self, prompts_dir: &Path) -> ColonyResult<()> {
+        validate_nodes(&self.nodes, prompts_dir, &mut Vec::new())
+    }
+
+    /// Render the template, reading included fragments from `prompts_dir`
+    pub fn render(&self, variables: &PromptVariables, prompts_dir: &Path) -> ColonyResult<String> {
+        let mut out = String::new();
+        render_nodes(
+            &self.nodes,
+            variables,
+            prompts_dir,
+            &mut Vec::new(),
+            &mut out,
+        )?;
+        Ok(out)
+    }
+}
+
+/// Parse and render a prompt in one go
+pub fn render(
+    source: &str,
+    variables: &PromptVariables,
+    prompts_dir: &Path,
+) -> ColonyResult<String> {
+    PromptTemplate::parse(source)?.render(variables, prompts_dir)
+}
+
+/// Gather the prompt variables for an agent
+///
+/// Tasks come from the colony's task queue and pinned memory from the shared
+/// state's context entries; both are empty when there are none.
+pub fn variables(agent: &Agent, config: &ColonyConfig, colony_root: &Path) -> PromptVariables {
+    let roster = config
+        .agents
+        .iter()
+        .map(|a| format!("- **{}**: {} ({})", a.id, a.role, a.focus))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let tasks = TaskQueue::new(colony_root)
+        .load_tasks_for_agent(agent.id())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|t| !matches!(t.status, TaskStatus::Completed | TaskStatus::Cancelled))
+        .map(|t| format!("- [{}] {}", t.id, t.title))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let memory = pinned_memory(config, colony_root)
+        .iter()
+        .map(|entry| match (&entry.key, &entry.value) {
+            (Some(key), Some(value)) => format!("- {}: {}", key, value),
+            _ => format!("- {}", entry.content.as_deref().unwrap_or_default()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    HashMap::from([
+        ("agent.id", agent.id().to_string()),
+        ("agent.role", agent.config.role.clone()),
+        ("agent.focus", agent.config.focus.clone()),
+        ("agent.worktree", agent.worktree_path.display().to_string()),
+        ("colony.name", config.name.clone().unwrap_or_default()),
+        ("colony.agents", roster),
+        (
+            "repository.purpose",
+            config
+                .repository
+                .as_ref()
+                .and_then(|r| r.purpose.clone())
+                .unwrap_or_default(),
+        ),
+        ("tasks.assigned", tasks),
+        ("memory.pinned", memory),
+    ])
+}
+
+/// Context entries from the shared state's memory, read straight from its
+/// JSONL file so rendering a prompt never initializes the state repository
+fn pinned_memory(config: &ColonyConfig, colony_root: &Path) -> Vec<MemoryEntry> {
+    let Some(state_config) = &config.shared_state else {
+        return Vec::new();
+    };
+    let repo_root = colony_root.parent().unwrap_or(colony_root).to_path_buf();
+    let Some(path) = state_config.schema_file_path(&repo_root, "memory") else {
+        return Vec::new();
+    };
+
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<MemoryEntry>(line).ok())
+        .filter(|entry| entry.entry_type == MemoryType::Context)
+        .collect()
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+fn tokenize(source: &str) -> ColonyResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        let mut text = &rest[..start];
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            ColonyError::Other(format!(
+                "unclosed '{{{{' in '{}'",
+                after.lines().next().unwrap_or_default()
+            ))
+        })?;
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+
+        // A block tag alone on its line takes the line with it
+        let is_block = tag.starts_with('#') || tag.starts_with('/') || tag == "else";
+        if is_block {
+            let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+            let at_line_start = text[line_start..].trim().is_empty()
+                && (line_start > 0 || tokens.is_empty() || ends_line(&tokens));
+            let line_end = rest.find('\n');
+            let at_line_end = rest[..line_end.unwrap_or(rest.len())].trim().is_empty();
+            if at_line_start && at_line_end {
+                text = &text[..line_start];
+                rest = line_end.map_or("", |i| &rest[i + 1..]);
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        tokens.push(Token::Tag(tag.to_string()));
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// Whether the output so far ends at the start of a line
+fn ends_line(tokens: &[Token]) -> bool {
+    match tokens.last() {
+        Some(Token::Text(text)) => text.ends_with('\n'),
+        // A standalone block tag consumed its own line ending
+        Some(Token::Tag(tag)) => tag.starts_with('#') || tag.starts_with('/') || tag == "else",
+        None => true,
+    }
+}
+
+/// Parse nodes up to the end of the input, or an `{{else}}`/`{{/if}}` when
+/// inside an `{{#if}}`. Returns the nodes and the tag that ended the block.
+fn parse_block(
+    tokens: &mut std::vec::IntoIter<Token>,
+    in_if: bool,
+) -> ColonyResult<(Vec<Node>, Option<&'static str>)> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        match tag.as_str() {
+            "else" | "/if" if !in_if => {
+                return Err(ColonyError::Other(format!(
+                    "'{{{{{}}}}}' without a matching '{{{{#if}}}}'",
+                    tag
+                )));
+            }
+            "else" => return Ok((nodes, Some("else"))),
+            "/if" => return Ok((nodes, Some("/if"))),
+            _ => {}
+        }
+
+        if let Some(variable) = tag.strip_prefix("#if ") {
+            let variable = check_variable(variable.trim())?;
+            let (then, end) = parse_block(tokens, true)?;
+            let otherwise = if end == Some("else") {
+                let (otherwise, end) = parse_block(tokens, true)?;
+                if end != Some("/if") {
+                    return Err(ColonyError::Other(format!(
+                        "'{{{{#if {}}}}}' has more than one '{{{{else}}}}'",
+                        variable
+                    )));
+                }
+                otherwise
+            } else {
+                then_closed(end, &variable)?;
+                Vec::new()
+            };
+            nodes.push(Node::If {
+                variable,
+                then,
+                otherwise,
+            });
+        } else if let Some(name) = tag.strip_prefix('>') {
+            nodes.push(Node::Include(check_include_name(name.trim())?));
+        } else if tag.starts_with('#') || tag.starts_with('/') {
+            return Err(ColonyError::Other(format!(
+                "unknown block '{{{{{}}}}}'",
+                tag
+            )));
+        } else {
+            nodes.push(Node::Variable(check_variable(&tag)?));
+        }
+    }
+
+    if in_if {
+        return Err(ColonyError::Other(
+            "'{{#if}}' is never closed with '{{/if}}'".to_string(),
+        ));
+    }
+    Ok((nodes, None))
+}
+
+fn then_closed(end: Option<&str>, variable: &str) -> ColonyResult<()> {
+    match end {
+        Some("/if") => Ok(()),
+        _ => Err(ColonyError::Other(format!(
+            "'{{{{#if {}}}}}' is never closed with '{{{{/if}}}}'",
+            variable
+        ))),
+    }
+}
+
+fn check_variable(name: &str) -> ColonyResult<String> {
+    if VARIABLES.contains(&name) {
+        Ok(name.to_string())
+    } else {
+        Err(ColonyError::Other(format!(
+            "unknown variable '{{{{{}}}}}'. Available: {}",
+            name,
+            VARIABLES.join(", ")
+        )))
+    }
+}
+
+/// Fragment names are file names in the prompts directory, never paths
+fn check_include_name(name: &str) -> ColonyResult<String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(ColonyError::Other(format!(
+            "invalid include '{{{{> {}}}}}'. Use the name of a file in .colony/{}/",
+            name, PROMPTS_DIR
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Find an included fragment: `name` as-is, or `name.md`
+fn fragment_path(prompts_dir: &Path, name: &str) -> ColonyResult<PathBuf> {
+    [
+        prompts_dir.join(name),
+        prompts_dir.join(format!("{}.md", name)),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .ok_or_else(|| {
+        ColonyError::Other(format!(
+            "included prompt '{}' not found in {}",
+            name,
+            prompts_dir.display()
+        ))
+    })
+}
+
+/// Load and parse an included fragment, guarding against include cycles
+fn load_fragment(prompts_dir: &Path, name: &str, stack: &[String]) -> ColonyResult<PromptTemplate> {
+    if stack.iter().any(|n| n == name) || stack.len() >= MAX_INCLUDE_DEPTH {
+        return Err(ColonyError::Other(format!(
+            "prompt includes form a cycle or nest too deeply: {} > {}",
+            stack.join(" > "),
+            name
+        )));
+    }
+
+    let source = std::fs::read_to_string(fragment_path(prompts_dir, name)?)?;
+    PromptTemplate::parse(&source)
+        .map_err(|e| ColonyError::Other(format!("in included prompt '{}': {}", name, e)))
+}
+
+fn validate_nodes(nodes: &[Node], prompts_dir: &Path, stack: &mut Vec<String>) -> ColonyResult<()> {
+    for node in nodes {
+        match node {
+            Node::Include(name) => {
+                let fragment = load_fragment(prompts_dir, name, stack)?;
+                stack.push(name.clone());
+                validate_nodes(&fragment.nodes, prompts_dir, stack)?;
+                stack.pop();
+            }
+            Node::If {
+                then, otherwise, ..
+            } => {
+                validate_nodes(then, prompts_dir, stack)?;
+                validate_nodes(otherwise, prompts_dir, stack)?;
+            }
+            Node::Text(_) | Node::Variable(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    variables: &PromptVariables,
+    prompts_dir: &Path,
+    stack: &mut Vec<String>,
+    out: &mut String,
+) -> ColonyResult<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Variable(name) => {
+                out.push_str(variables.get(name.as_str()).map_or("", String::as_str))
+            }
+            Node::Include(name) => {
+                let fragment = load_fragment(prompts_dir, name, stack)?;
+                stack.push(name.clone());
+                render_nodes(&fragment.nodes, variables, prompts_dir, stack, out)?;
+                stack.pop();
+            }
+            Node::If {
+                variable,
+                then,
+                otherwise,
+            } => {
+                let truthy = variables
+                    .get(variable.as_str())
+                    .is_some_and(|v| !v.trim().is_empty());
+                let branch = if truthy { then } else { otherwise };
+                render_nodes(branch, variables, prompts_dir, stack, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn vars(pairs: &[(&'static str, &str)]) -> PromptVariables {
+        pairs.iter().map(|(k, v)| (*k, v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("team.md"), "Team:\n{{colony.agents}}\n").unwrap();
+
+        let template = "You are {{ agent.id }}.\n\
+                        {{#if repository.purpose}}\n\
+                        Purpose: {{repository.purpose}}\n\
+                        {{else}}\n\
+                        No purpose set.\n\
+                        {{/if}}\n\
+                        {{> team}}Done.";
+
+        let rendered = render(
+            template,
+            &vars(&[
+                ("agent.id", "backend"),
+                ("colony.agents", "- backend\n- frontend"),
+                ("repository.purpose", "An API"),
+            ]),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "You are backend.\nPurpose: An API\nTeam:\n- backend\n- frontend\nDone."
+        );
+
+        let rendered = render(template, &vars(&[("agent.id", "qa")]), dir.path()).unwrap();
+        assert_eq!(rendered, "You are qa.\nNo purpose set.\nTeam:\n\nDone.");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        for source in [
+            "{{agent.name}}",
+            "{{agent.id",
+            "{{#if agent.id}}never closed",
+            "{{/if}}",
+            "{{#if agent.id}}a{{else}}b{{else}}c{{/if}}",
+            "{{#each colony.agents}}{{/each}}",
+            "{{> ../secrets}}",
+        ] {
+            assert!(
+                PromptTemplate::parse(source).is_err(),
+                "accepted {}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.md"), "{{> b}}").unwrap();
+        std::fs::write(dir.path().join("b.md"), "{{> a}}").unwrap();
+
+        let template = PromptTemplate::parse("{{> a}}").unwrap();
+        assert!(template.validate(dir.path()).is_err());
+        assert!(PromptTemplate::parse("{{> missing}}")
+            .unwrap()
+            .validate(dir.path())
+            .is_err());
+
+        std::fs::write(dir.path().join("b.md"), "{{agent.nope}}").unwrap();
+        assert!(template.validate(dir.path()).is_err());
+
+        std::fs::write(dir.path().join("b.md"), "ok").unwrap();
+        template.validate(dir.path()).unwrap();
+    }
+}
@@ -185,8 +185,9 @@ impl RelayClient {
         loop {
             update_interval.tick().await;
 
-            // Gather current state
-            let controller_lock = controller.lock().await;
+            // Gather current state, picking up agents restarted outside the relay
+            let mut controller_lock = controller.lock().await;
+            let _ = controller_lock.load_state();
             let (agents, tasks, messages) =
                 match gather_colony_state(&colony_root, &controller_lock).await {
                     Ok(state) => state,
@@ -288,6 +289,7 @@ impl RelayClient {
                 to,
                 content,
                 message_type,
+                reply_to,
             } => {
                 let msg_type = match message_type.as_str() {
                     "task" => crate::colony::messaging::MessageType::Task,
@@ -298,8 +300,18 @@ impl RelayClient {
                     _ => crate::colony::messaging::MessageType::Info,
                 };
 
-                let message =
+                let mut message =
                     crate::colony::messaging::Message::new("relay", &to, content.clone(), msg_type);
+                if let Some(reply_to) = reply_to {
+                    let original = crate::colony::messaging::find_message(colony_root, &reply_to)?
+                        .ok_or_else(|| {
+                            crate::error::ColonyError::Colony(format!(
+                                "Message '{}' not found",
+                                reply_to
+                            ))
+                        })?;
+                    message = message.in_reply_to(&original);
+                }
                 message.save(colony_root)?;
 
                 Ok(format!("Message sent to {}", to))
@@ -342,138 +354,28 @@ impl RelayClient {
                 Ok(format!("Task '{}' created", task_id))
             }
             Command::StopAgent { agent_id } => {
-                // Stop the tmux pane for this agent
-                let controller_lock = controller.lock().await;
-                let session_name = controller_lock.config().session_name();
-                drop(controller_lock);
-
-                let output = tokio::process::Command::new("tmux")
-                    .args(["kill-pane", "-t", &format!("{}:{}", session_name, agent_id)])
-                    .output()
-                    .await
-                    .map_err(|e| ColonyError::Colony(format!("Failed to stop agent: {}", e)))?;
-
-                if output.status.success() {
-                    Ok(format!("Agent '{}' stopped", agent_id))
-                } else {
-                    Err(ColonyError::Colony(format!(
-                        "Failed to stop agent: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    )))
-                }
+                let mut controller = controller.lock().await;
+                let _ = controller.load_state();
+                controller.stop_agent(&agent_id)?;
+
+                Ok(format!("Agent '{}' stopped", agent_id))
             }
             Command::StartAgent { agent_id } => {
-                // Start an agent by recreating its tmux pane
-                let controller_lock = controller.lock().await;
-                let session_name = controller_lock.config().session_name();
-
-                // Find the agent config
-                let agent_config = controller_lock
-                    .config()
-                    .agents
-                    .iter()
-                    .find(|a| a.id == agent_id)
-                    .ok_or_else(|| {
-                        ColonyError::Colony(format!("Agent '{}' not found in config", agent_id))
-                    })?
-                    .clone();
-
-                drop(controller_lock);
-
-                // Create new pane for this agent
-                let pane_name = format!("{}:{}", session_name, agent_id);
-
-                // Split window to create new pane
-                let output = tokio::process::Command::new("tmux")
-                    .args([
-                        "split-window",
-                        "-t",
-                        &session_name,
-                        "-h", // horizontal split
-                        "-P", // print pane info
-                        "-F",
-                        "#{pane_id}",
-                    ])
-                    .output()
-                    .await
-                    .map_err(|e| ColonyError::Colony(format!("Failed to create pane: {}", e)))?;
-
-                if !output.status.success() {
-                    return Err(ColonyError::Colony(format!(
-                        "Failed to create pane: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    )));
-                }
-
-                // Rename the pane/window
-                tokio::process::Command::new("tmux")
-                    .args(["select-pane", "-t", &pane_name, "-T", &agent_id])
-                    .output()
-                    .await
-                    .ok();
+                let mut controller = controller.lock().await;
+                let _ = controller.load_state();
+                let pane_id = controller.start_agent(&agent_id).await?;
 
-                Ok(format!("Agent '{}' started", agent_id))
+                Ok(format!("Agent '{}' started in pane {}", agent_id, pane_id))
             }
             Command::RestartAgent { agent_id } => {
-                // Restart = Stop + Start
-                let controller_lock = controller.lock().await;
-                let session_name = controller_lock.config().session_name();
-
-                // Find the agent config
-                let _agent_config = controller_lock
-                    .config()
-                    .agents
-                    .iter()
-                    .find(|a| a.id == agent_id)
-                    .ok_or_else(|| {
-                        ColonyError::Colony(format!("Agent '{}' not found in config", agent_id))
-                    })?
-                    .clone();
-
-                drop(controller_lock);
-
-                // Stop the agent
-                tokio::process::Command::new("tmux")
-                    .args(["kill-pane", "-t", &format!("{}:{}", session_name, agent_id)])
-                    .output()
-                    .await
-                    .ok();
-
-                // Give it a moment to stop
-                sleep(Duration::from_millis(500)).await;
-
-                // Start it again - inline the StartAgent logic to avoid recursion
-                // Split window to create new pane
-                let output = tokio::process::Command::new("tmux")
-                    .args([
-                        "split-window",
-                        "-t",
-                        &session_name,
-                        "-h", // horizontal split
-                        "-P", // print pane info
-                        "-F",
-                        "#{pane_id}",
-                    ])
-                    .output()
-                    .await
-                    .map_err(|e| ColonyError::Colony(format!("Failed to create pane: {}", e)))?;
-
-                if !output.status.success() {
-                    return Err(ColonyError::Colony(format!(
-                        "Failed to create pane: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    )));
-                }
-
-                // Rename the pane
-                let pane_name = format!("{}:{}", session_name, agent_id);
-                tokio::process::Command::new("tmux")
-                    .args(["select-pane", "-t", &pane_name, "-T", &agent_id])
-                    .output()
-                    .await
-                    .ok();
-
-                Ok(format!("Agent '{}' restarted", agent_id))
+                let mut controller = controller.lock().await;
+                let _ = controller.load_state();
+                let pane_id = controller.restart_agent(&agent_id).await?;
+
+                Ok(format!(
+                    "Agent '{}' restarted in pane {}",
+                    agent_id, pane_id
+                ))
             }
         }
     }
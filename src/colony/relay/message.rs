@@ -91,11 +91,13 @@ pub enum RelayToClientMessage {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "command", rename_all = "snake_case")]
 pub enum Command {
-    /// Send a message to an agent
+    /// Send a message to an agent, or to every agent in a `group:`, `role:` or
+    /// `tag:` address
     SendMessage {
         to: String,
         content: String,
         message_type: String,
+        reply_to: Option<String>,
     },
     /// Broadcast a message to all agents
     BroadcastMessage { content: String },
@@ -165,4 +167,6 @@ pub struct MessageState {
     pub content: String,
     pub timestamp: String,
     pub message_type: String,
+    pub reply_to: Option<String>,
+    pub thread_id: Option<String>,
 }
@@ -1,5 +1,6 @@
 use crate::colony::controller::ColonyController;
 use crate::colony::messaging;
+use crate::colony::multiplexer::{Multiplexer, PaneState};
 use crate::colony::tasks::queue::TaskQueue;
 use crate::error::ColonyResult;
 use chrono::Utc;
@@ -24,12 +25,16 @@ pub async fn gather_colony_state(
     Ok((agents, tasks, messages))
 }
 
-/// Gather agent states from tmux
+/// Gather agent states from the multiplexer
 async fn gather_agent_states(controller: &ColonyController) -> ColonyResult<Vec<AgentState>> {
     let mut agent_states = Vec::new();
+    let mux = controller.multiplexer();
 
     for agent in &controller.config().agents {
-        let status = check_agent_status(&controller.config().session_name(), &agent.id).await;
+        let pane_id = controller
+            .get_agent(&agent.id)
+            .and_then(|a| a.pane_id.as_deref());
+        let status = check_agent_status(mux.as_ref(), pane_id);
 
         agent_states.push(AgentState {
             id: agent.id.clone(),
@@ -42,22 +47,11 @@ async fn gather_agent_states(controller: &ColonyController) -> ColonyResult<Vec<
     Ok(agent_states)
 }
 
-/// Check if an agent tmux pane is running
-async fn check_agent_status(session_name: &str, agent_id: &str) -> AgentStatus {
-    // Try to get pane info from tmux
-    let output = tokio::process::Command::new("tmux")
-        .args([
-            "list-panes",
-            "-t",
-            &format!("{}:{}", session_name, agent_id),
-            "-F",
-            "#{pane_id}",
-        ])
-        .output()
-        .await;
-
-    match output {
-        Ok(output) if output.status.success() && !output.stdout.is_empty() => AgentStatus::Running,
+/// Check whether an agent's pane is running Claude Code
+fn check_agent_status(mux: &dyn Multiplexer, pane_id: Option<&str>) -> AgentStatus {
+    match pane_id.map(|pane_id| mux.pane_state(pane_id)) {
+        Some(PaneState::Running) => AgentStatus::Running,
+        Some(PaneState::Dead) => AgentStatus::Failed,
         _ => AgentStatus::Stopped,
     }
 }
@@ -108,6 +102,8 @@ fn gather_message_states(colony_root: &Path) -> ColonyResult<Vec<MessageState>>
             content: msg.content.clone(),
             timestamp: msg.timestamp.clone(),
             message_type: format!("{:?}", msg.message_type).to_lowercase(),
+            reply_to: msg.reply_to.clone(),
+            thread_id: msg.thread_id.clone(),
         })
         .collect();
 
@@ -25,7 +25,7 @@ pub async fn connect(url: Option<String>, token: Option<String>) -> ColonyResult
     println!();
 
     // Load colony config
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
     if !config_path.exists() {
         return Err(ColonyError::Colony(
             "No colony.yml found. Initialize a colony first with 'colony init'".to_string(),
@@ -94,7 +94,10 @@ pub async fn connect(url: Option<String>, token: Option<String>) -> ColonyResult
         auth_token,
     };
 
-    let controller = ColonyController::new(colony_config)?;
+    let mut controller = ColonyController::new(colony_config)?;
+    controller.initialize_agents()?;
+    // Pane IDs come from the agent state written by 'colony start'
+    let _ = controller.load_state();
     let colony_root = std::env::current_dir()?;
     let client = RelayClient::new(relay_config, controller, colony_root);
 
@@ -0,0 +1,29 @@
+use crate::colony::{ColonyConfig, ColonyController};
+use crate::error::ColonyResult;
+use crate::utils;
+
+/// Restart a single agent in the running colony session
+pub async fn run(agent_id: String) -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(crate::error::ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    let config = ColonyConfig::load(config_path)?;
+    let mut controller = ColonyController::new(config)?;
+    controller.initialize_agents()?;
+    controller.load_state()?;
+
+    utils::info(&format!("Restarting agent: {}", agent_id));
+    let pane_id = controller.restart_agent(&agent_id).await?;
+
+    utils::success(&format!(
+        "Restarted agent '{}' in pane {}",
+        agent_id, pane_id
+    ));
+
+    Ok(())
+}
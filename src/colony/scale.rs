@@ -0,0 +1,141 @@
+//! Change how many replicas of an agent run
+//!
+//! The new count is written to colony.yml. If the colony is running, the
+//! change is applied right away: new replicas get a pane and removed ones are
+//! stopped, as with `colony apply`.
+
+use crate::colony::{apply, multiplexer, ColonyConfig};
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+
+/// Set the number of replicas of an agent and apply it to the running colony
+pub async fn run(agent_id: String, replicas: u32, yes: bool) -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    if replicas == 0 {
+        return Err(ColonyError::Colony(format!(
+            "An agent needs at least 1 replica. Remove '{}' from colony.yml to stop running it",
+            agent_id
+        )));
+    }
+
+    let mut config = ColonyConfig::load_file(config_path)?;
+    set_replicas(&mut config, &agent_id, replicas)?;
+    config.validate()?;
+    config.save(config_path)?;
+    utils::success(&format!(
+        "Set '{}' to {} replica(s) in {}",
+        agent_id,
+        replicas,
+        config_path.display()
+    ));
+
+    let mux = multiplexer::from_kind(config.multiplexer);
+    if !mux.session_exists(&config.session_name()) {
+        utils::info("The colony is not running. The replicas start with 'colony start'");
+        return Ok(());
+    }
+
+    println!();
+    apply::run(yes).await
+}
+
+/// Set the replica count of the agent entry `agent_id`
+fn set_replicas(config: &mut ColonyConfig, agent_id: &str, replicas: u32) -> ColonyResult<()> {
+    match config.agents.iter_mut().find(|a| a.id == agent_id) {
+        Some(agent) => {
+            agent.replicas = Some(replicas);
+            Ok(())
+        }
+        None => {
+            let hint = config
+                .expanded_agents()
+                .into_iter()
+                .find(|a| a.id == agent_id)
+                .and_then(|a| a.replica_of)
+                .map(|set| format!(". It is a replica of '{}'; scale that instead", set))
+                .unwrap_or_default();
+            Err(ColonyError::Colony(format!(
+                "Agent '{}' not found in colony.yml{}",
+                agent_id, hint
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_replicas() {
+        let yaml = r#"
+agents:
+  - id: worker
+    role: Bug Fixer
+    focus: Open bugs
+    replicas: 2
+  - id: reviewer
+    role: Reviewer
+    focus: Pull requests
+"#;
+        let mut config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let ids: Vec<String> = config.expanded_agents().into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["worker-1", "worker-2", "reviewer"]);
+
+        set_replicas(&mut config, "worker", 3).unwrap();
+        set_replicas(&mut config, "reviewer", 2).unwrap();
+        let agents = config.expanded_agents();
+        assert_eq!(agents.len(), 5);
+        assert_eq!(agents[2].id, "worker-3");
+        assert_eq!(agents[2].worktree_name(), "worker-3");
+        assert_eq!(agents[4].replica_of.as_deref(), Some("reviewer"));
+
+        let sets = config.replica_sets();
+        assert_eq!(sets["worker"], vec!["worker-1", "worker-2", "worker-3"]);
+
+        let error = set_replicas(&mut config, "worker-2", 1).unwrap_err();
+        assert!(error.to_string().contains("replica of 'worker'"));
+    }
+
+    #[test]
+    fn test_replica_group_messages() {
+        use crate::colony::messaging::{self, Message, MessageType};
+        use crate::colony::ColonyController;
+
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            "agents:\n  - {{id: worker, role: Fixer, focus: Bugs, replicas: 3, directory: '{}'}}\n",
+            dir.path().display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+        let colony_root = dir.path().join(".colony");
+        let mut controller = ColonyController::with_root(config, colony_root.clone()).unwrap();
+        controller.initialize_agents().unwrap();
+        assert_eq!(controller.agents().len(), 3);
+        assert_eq!(controller.config().agents[0].id, "worker-1");
+
+        Message::new(
+            "worker-1",
+            "worker",
+            "Take #12".to_string(),
+            MessageType::Task,
+        )
+        .save(&colony_root)
+        .unwrap();
+        assert!(messaging::load_messages_for_agent(&colony_root, "worker-1")
+            .unwrap()
+            .is_empty());
+        for replica in ["worker-2", "worker-3"] {
+            let inbox = messaging::load_messages_for_agent(&colony_root, replica).unwrap();
+            assert_eq!(inbox.len(), 1);
+            assert_eq!(inbox[0].to, "worker");
+        }
+    }
+}
@@ -2,16 +2,26 @@ use std::path::Path;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
+use crate::colony::multiplexer::{self, Multiplexer, MultiplexerKind};
 use crate::colony::{
-    agent_skills, executor, layout, messaging, skills, state_integration, tmux, AgentStatus,
-    ColonyConfig, ColonyController,
+    agent_skills, config, delivery, dry_run, executor, headless, layout, messaging, nudge, prompt,
+    secrets, skills, state_integration, supervise, AgentStatus, ColonyConfig, ColonyController,
 };
 use crate::error::ColonyResult;
 use crate::utils;
 
 /// Start all agents in the colony
-pub async fn run(no_attach: bool) -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+///
+/// With `headless`, agents run as child processes on their own PTYs instead of
+/// in a tmux session, and this call hosts them until they exit. With `dry_run`,
+/// nothing is started; see [`dry_run::run`].
+pub async fn run(
+    no_attach: bool,
+    supervise: bool,
+    headless: bool,
+    dry_run: bool,
+) -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
@@ -19,31 +29,68 @@ pub async fn run(no_attach: bool) -> ColonyResult<()> {
         ));
     }
 
-    // Check if tmux is available - try to install if not
-    if !tmux::is_tmux_available() {
-        utils::warning("tmux is not installed. Attempting to install...");
-        if let Err(e) = tmux::try_install_tmux() {
+    // Load configuration
+    let config = ColonyConfig::load(config_path)?;
+    config.validate()?;
+
+    if dry_run {
+        let mut controller = ColonyController::new(config)?;
+        controller.initialize_agents()?;
+        return dry_run::run(&controller).await;
+    }
+
+    // Check if the multiplexer is available - try to install if not
+    let mux = multiplexer::from_kind(config.multiplexer);
+    if !headless && !mux.is_available() {
+        utils::warning(&format!(
+            "{} is not installed. Attempting to install...",
+            mux.name()
+        ));
+        if let Err(e) = mux.install() {
+            let hint = match config.multiplexer {
+                MultiplexerKind::Tmux => {
+                    "- macOS: brew install tmux\n\
+                     - Ubuntu/Debian: sudo apt-get install tmux\n\
+                     - Fedora/RHEL: sudo dnf install tmux"
+                }
+                MultiplexerKind::Zellij => "- See https://zellij.dev/documentation/installation",
+            };
             return Err(crate::error::ColonyError::Colony(format!(
-                "Failed to install tmux: {}\n\
+                "Failed to install {}: {}\n\
                  Please install manually:\n\
-                 - macOS: brew install tmux\n\
-                 - Ubuntu/Debian: sudo apt-get install tmux\n\
-                 - Fedora/RHEL: sudo dnf install tmux",
-                e
+                 {}",
+                mux.name(),
+                e,
+                hint
             )));
         }
-        utils::success("tmux installed successfully!");
+        utils::success(&format!("{} installed successfully!", mux.name()));
     }
 
-    // Load configuration
-    let config = ColonyConfig::load(config_path)?;
-    config.validate()?;
+    if !headless {
+        let unsupported = unsupported_features(&config, mux.as_ref(), supervise);
+        if !unsupported.is_empty() {
+            return Err(crate::error::ColonyError::Colony(format!(
+                "{} can't address panes, which {} need. Use 'multiplexer: tmux' or turn them off.",
+                mux.name(),
+                unsupported.join(", ")
+            )));
+        }
+        if !mux.supports_pane_ids() {
+            utils::warning(&format!(
+                "{} can't capture pane output or relaunch panes, so 'colony logs' and 'colony restart' won't work",
+                mux.name()
+            ));
+        }
+    }
 
     // Get the session name from config
     let session_name = config.session_name();
 
     utils::header("Starting Colony");
-    utils::info(&format!("Session name: {}", session_name));
+    if !headless {
+        utils::info(&format!("Session name: {}", session_name));
+    }
 
     // Install all colony skills to ~/.claude/skills/ (system-wide)
     if let Err(e) = skills::install_all_skills() {
@@ -89,32 +136,142 @@ pub async fn run(no_attach: bool) -> ColonyResult<()> {
     spinner.finish_and_clear();
     utils::success("Message queue system ready");
 
+    if headless {
+        return headless::run(controller).await;
+    }
+
+    let mux = controller.multiplexer();
+    let agent_count = launch_session(&mut controller, mux.as_ref(), supervise).await?;
+
+    // Save state
+    controller.save_state()?;
+
+    // Track colony started event (if telemetry is enabled)
+    if controller.config().telemetry.enabled {
+        let telemetry_client =
+            crate::colony::telemetry::TelemetryClient::new(controller.config().telemetry.clone());
+        let has_executor = controller
+            .config()
+            .executor
+            .as_ref()
+            .is_some_and(|e| e.enabled);
+        telemetry_client
+            .track_colony_started(agent_count, has_executor)
+            .await;
+    }
+
+    utils::header("Colony Started Successfully!");
+    let running_count = controller
+        .agents()
+        .values()
+        .filter(|a| a.is_running())
+        .count();
+    println!(
+        "Running agents: {}/{}",
+        running_count,
+        controller.agents().len()
+    );
+
+    if no_attach {
+        println!("\nNext steps:");
+        println!(
+            "  • Run 'colony attach' to view agents and TUI in {}",
+            mux.name()
+        );
+        println!("  • The orchestration TUI is already running in a dedicated pane");
+        println!("  • Use 'colony status' to check agent status from CLI");
+        println!("  • Use 'colony logs <agent-id>' to view specific logs");
+        println!("\nTip: {}", detach_hint(mux.as_ref()));
+    } else {
+        println!("\nAttaching to {} session...", mux.name());
+        println!("Tip: {}", detach_hint(mux.as_ref()));
+        println!();
+
+        // Small delay to ensure all panes are ready
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+        // Attach to the session
+        mux.attach_session(&session_name)?;
+    }
+
+    Ok(())
+}
+
+/// Features this start would turn on that `mux` can't provide
+fn unsupported_features(
+    config: &ColonyConfig,
+    mux: &dyn Multiplexer,
+    supervise: bool,
+) -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if !mux.supports_pane_ids() {
+        if supervise {
+            features.push("the supervisor (--supervise)");
+        }
+        if nudge::is_enabled(config) {
+            features.push("nudges");
+        }
+        if delivery::is_enabled(config) {
+            features.push("message delivery");
+        }
+    }
+    features
+}
+
+/// How to detach from a session without stopping the agents
+fn detach_hint(mux: &dyn Multiplexer) -> &'static str {
+    match mux.name() {
+        "zellij" => "Press Ctrl+O then D to detach from zellij without stopping agents",
+        _ => "Press Ctrl+B then D to detach from tmux without stopping agents",
+    }
+}
+
+/// Lay out the colony's session in a multiplexer and launch every agent, the
+/// executor, the TUI and any background windows in it
+/// Returns the number of configured agents
+async fn launch_session(
+    controller: &mut ColonyController,
+    mux: &dyn Multiplexer,
+    supervise: bool,
+) -> ColonyResult<usize> {
+    let session_name = controller.config().session_name();
+
     // Kill existing session if it exists
-    if tmux::session_exists(&session_name) {
-        utils::info("Found existing tmux session, cleaning up...");
-        tmux::kill_session(&session_name)?;
+    if mux.session_exists(&session_name) {
+        utils::info(&format!(
+            "Found existing {} session, cleaning up...",
+            mux.name()
+        ));
+        mux.kill_session(&session_name)?;
     }
 
-    // Try to create session with custom layout using moxide
-    let pane_map = layout::create_session_with_moxide(&session_name, &controller)?;
+    // Create the session from the custom layout, if there is one
+    let pane_map = layout::create_session_with_layout(&session_name, controller, mux)?;
 
     let use_custom_layout = !pane_map.is_empty();
 
-    // If no custom layout, create session with default tmux
+    // If no custom layout, create session with default layout
     if !use_custom_layout {
-        utils::info(&format!("Creating tmux session '{}'...", session_name));
-        tmux::create_session(&session_name)?;
+        utils::info(&format!(
+            "Creating {} session '{}'...",
+            mux.name(),
+            session_name
+        ));
+        mux.create_session(&session_name)?;
     }
 
-    // Start each agent in a tmux pane
-    let agent_ids: Vec<String> = controller.agents().keys().cloned().collect();
+    // Start each agent in a pane, in config order so the panes match `--dry-run`
+    let agent_ids: Vec<String> = controller
+        .config()
+        .agents
+        .iter()
+        .map(|a| a.id.clone())
+        .collect();
     let agent_count = agent_ids.len();
 
-    // Clone repository config to avoid borrow checker issues in the loop
-    let repo_config = controller.config().repository.clone();
-    let has_shared_state = controller.config().shared_state.is_some();
-    // Clone global capabilities before agent loop to avoid borrow issues
-    let global_capabilities = controller.config().capabilities.clone();
+    // Clone the config and colony root to avoid borrow checker issues in the loop
+    let config = controller.config().clone();
+    let colony_root = controller.colony_root().to_path_buf();
 
     for (index, agent_id) in agent_ids.iter().enumerate() {
         let agent = controller
@@ -133,217 +290,17 @@ pub async fn run(no_attach: bool) -> ColonyResult<()> {
         println!("  Focus: {}", agent.config.focus);
         println!("  Model: {}", agent.config.model);
 
-        // Create startup prompt file and capture the prompt text
-        let startup_prompt =
-            match create_startup_prompt(agent, repo_config.as_ref(), has_shared_state).await {
-                Ok(prompt) => Some(prompt),
-                Err(e) => {
-                    utils::warning(&format!("  Failed to create startup prompt: {}", e));
-                    None
-                }
-            };
-
-        // Create settings.json file if agent has MCP server configuration
-        if agent.config.has_mcp_servers() {
-            match create_agent_settings(agent).await {
-                Ok(()) => {
-                    utils::info(&"  Created settings.json with MCP server configuration".to_string());
-                }
-                Err(e) => {
-                    utils::warning(&format!("  Failed to create settings.json: {}", e));
-                }
-            }
-        }
-
-        // Install agent skills (tmux, nvim, ollama) and create symlink from worktree
-        if let Err(e) =
-            agent_skills::install_agent_skills(&agent.project_path, &agent.worktree_path)
-        {
-            utils::warning(&format!("  Failed to install agent skills: {}", e));
-        }
-
-        // Build the claude command with properly escaped paths
-        let worktree_path_str = agent.worktree_path.to_str().ok_or_else(|| {
-            crate::error::ColonyError::Colony(format!(
-                "Invalid worktree path for agent '{}': contains non-UTF-8 characters",
-                agent.id()
-            ))
-        })?;
-        let project_path_str = agent.project_path.to_str().ok_or_else(|| {
-            crate::error::ColonyError::Colony(format!(
-                "Invalid project path for agent '{}': contains non-UTF-8 characters",
-                agent.id()
-            ))
-        })?;
-
-        // Build environment variable exports if configured
-        let env_prefix = if let Some(env_vars) = &agent.config.env {
-            let exports: Vec<String> = env_vars
-                .iter()
-                .map(|(key, value)| format!("export {}={}", shell_escape(key), shell_escape(value)))
-                .collect();
-            if exports.is_empty() {
-                String::new()
-            } else {
-                format!("{} && ", exports.join(" && "))
-            }
-        } else {
-            String::new()
-        };
-
-        // Add capability environment variables
-        let capabilities_env = if let Some(caps) = agent
-            .config
-            .resolved_capabilities(global_capabilities.as_ref())
-        {
-            let tools_str = caps.tools.join(",");
-            let mcp_servers_str = caps.mcp_servers.join(",");
-            let pane_tools_str = caps.pane_tools.join(",");
-
-            format!(
-                "export COLONY_TOOLS={} && export COLONY_MCP_SERVERS={} && export COLONY_PANE_TOOLS={} && ",
-                shell_escape(&tools_str),
-                shell_escape(&mcp_servers_str),
-                shell_escape(&pane_tools_str)
-            )
-        } else {
-            String::new()
-        };
-
-        // Build Claude command with optional settings path
-        // Source shell config first to ensure mise/asdf/nvm and other tool managers are loaded
-        let shell_init = "source ~/.zshrc 2>/dev/null || source ~/.bashrc 2>/dev/null || true";
-
-        // Build capabilities section if configured
-        let capabilities_section = if let Some(caps) = agent
-            .config
-            .resolved_capabilities(global_capabilities.as_ref())
-        {
-            format!(
-                r#"
-
-## Your Capabilities
-
-**Tools**: {}
-**MCP Servers**: {}
-**Pane Tools**: {}
-
-Environment variables: `$COLONY_TOOLS`, `$COLONY_MCP_SERVERS`, `$COLONY_PANE_TOOLS`
-"#,
-                if caps.tools.is_empty() {
-                    "None".to_string()
-                } else {
-                    caps.tools.join(", ")
-                },
-                if caps.mcp_servers.is_empty() {
-                    "None".to_string()
-                } else {
-                    caps.mcp_servers.join(", ")
-                },
-                if caps.pane_tools.is_empty() {
-                    "None".to_string()
-                } else {
-                    caps.pane_tools.join(", ")
-                }
-            )
-        } else {
-            String::new()
-        };
-
-        // Build startup prompt as a command-line argument
-        let startup_prompt = format!(
-            r#"# Welcome to Colony
-
-You are **{}** working as part of a multi-agent colony.
-
-## Your Role
-**Role**: {}
-**Focus**: {}
-{}
-## Communication System
-You can communicate with other agents using the message queue system.
-
-**Check messages**: `./colony_message.sh read` or `./colony_message_{}.sh read`
-**Send messages**: `./colony_message.sh send <recipient> "message"`
-
-Use `./colony_message_{}.sh` if your worktree is shared with other agents.
-
-**Important**: Messages automatically include your current directory and git branch as context. When receiving messages, pay attention to the directory context shown in square brackets [/path] and branch in parentheses (branch-name).
-
-## Available Skills
-
-You have access to these skills in `.claude/skills/`:
-
-**Tool Panes:**
-- `tmux-pane-tools.md` - Create/manage separate panes for persistent tools
-- `nvim-pane-editing.md` - Edit files in dedicated nvim pane
-- `ollama-local-llm.md` - Use local LLMs (CodeLlama, Llama3) for fast analysis
-
-**Development:**
-- `bash-scripting.md` - Write reliable bash scripts for automation
-- `git-workflow.md` - Git operations, branching, commits, collaboration
-- `gh-cli.md` - GitHub CLI for PRs, issues, releases
-
-**Data & APIs:**
-- `curl-api.md` - HTTP requests, API interactions, file transfers
-- `jq-json.md` - JSON parsing, filtering, transforming
-
-## Best Practices
-1. Check messages regularly with `./colony_message.sh read`
-2. Announce what you're working on to other agents
-3. Use tool panes (nvim, ollama) for persistent tool sessions
-4. Ask for help when blocked
-5. Share important findings via messages
-6. Coordinate on shared resources
-
-Read the full guide at: .colony/COLONY_COMMUNICATION.md
-
-Now get started on your assigned work!"#,
-            agent.id(),
-            agent.config.role,
-            agent.config.focus,
-            capabilities_section,
-            agent.id(),
-            agent.id()
-        );
-
-        let claude_cmd = if agent.config.has_mcp_servers() {
-            let settings_path = agent.project_path.join(".claude").join("settings.json");
-            let settings_path_str = settings_path.to_str().ok_or_else(|| {
-                crate::error::ColonyError::Colony(format!(
-                    "Invalid settings path for agent '{}': contains non-UTF-8 characters",
-                    agent.id()
-                ))
-            })?;
-            format!(
-                "{} && {}{}cd {} && claude --mcp-config {} --strict-mcp-config --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
-                shell_init,
-                env_prefix,
-                capabilities_env,
-                shell_escape(worktree_path_str),
-                shell_escape(settings_path_str),
-                shell_escape(worktree_path_str),
-                shell_escape(&startup_prompt)
-            )
-        } else {
-            format!(
-                "{} && {}{}cd {} && claude --setting-sources local --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
-                shell_init,
-                env_prefix,
-                capabilities_env,
-                shell_escape(worktree_path_str),
-                shell_escape(worktree_path_str),
-                shell_escape(&startup_prompt)
-            )
-        };
+        // Write the startup prompt, settings.json and skills, then build the launch command
+        prepare_agent(agent, &config, &colony_root).await;
+        let claude_cmd = build_agent_command(agent, &config, &colony_root)?;
 
         // Track the actual pane coordinates (window, pane)
         let (window_idx, pane_idx) = if use_custom_layout {
-            // With custom layout, panes already exist from moxide
+            // With custom layout, panes already exist from the layout
             // Find where this agent should be
             if let Some(coords) = pane_map.get(agent_id) {
                 // Send command to the pre-existing pane
-                tmux::send_command_to_window_pane(&session_name, coords.0, coords.1, &claude_cmd)?;
+                mux.send_command_to_window_pane(&session_name, coords.0, coords.1, &claude_cmd)?;
                 *coords
             } else {
                 // Agent not in layout config, skip
@@ -360,14 +317,14 @@ Now get started on your assigned work!"#,
                 const VERTICAL_SPLIT_MODULO: usize = 1;
 
                 if index % 2 == VERTICAL_SPLIT_MODULO {
-                    tmux::split_vertical(&session_name, &claude_cmd)?
+                    mux.split_vertical(&session_name, &claude_cmd)?
                 } else {
-                    tmux::split_horizontal(&session_name, &claude_cmd)?
+                    mux.split_horizontal(&session_name, &claude_cmd)?
                 }
             } else {
                 // For the first agent (index 0), send the command to the initial pane
                 const FIRST_PANE_INDEX: usize = 0;
-                tmux::send_command_to_pane(&session_name, FIRST_PANE_INDEX, &claude_cmd)?;
+                mux.send_command_to_pane(&session_name, FIRST_PANE_INDEX, &claude_cmd)?;
                 FIRST_PANE_INDEX
             };
             (0, pane_idx)
@@ -375,43 +332,29 @@ Now get started on your assigned work!"#,
 
         // Set pane title
         if use_custom_layout {
-            tmux::set_window_pane_title(
+            mux.set_window_pane_title(
                 &session_name,
                 window_idx,
                 pane_idx,
                 &format!("Agent: {}", agent.id()),
             )?;
         } else {
-            tmux::set_pane_title(&session_name, pane_idx, &format!("Agent: {}", agent.id()))?;
+            mux.set_pane_title(&session_name, pane_idx, &format!("Agent: {}", agent.id()))?;
         }
 
-        // Enable output capture for this pane (pipe to log file)
-        #[cfg(unix)]
-        {
-            let log_path_str = agent.log_path.to_str().ok_or_else(|| {
-                crate::error::ColonyError::Colony(format!(
-                    "Invalid log path for agent '{}': contains non-UTF-8 characters",
-                    agent.id()
-                ))
-            })?;
-            let log_path = shell_escape(log_path_str);
-            let escaped_session = shell_escape(&session_name);
-            let pipe_cmd = if use_custom_layout {
-                format!(
-                    "tmux pipe-pane -t {}:{}.{} 'cat >> {}'",
-                    escaped_session, window_idx, pane_idx, log_path
-                )
-            } else {
-                format!(
-                    "tmux pipe-pane -t {}:0.{} 'cat >> {}'",
-                    escaped_session, pane_idx, log_path
-                )
-            };
-            let _ = std::process::Command::new("sh")
-                .arg("-c")
-                .arg(&pipe_cmd)
-                .output();
+        // Remember the stable pane ID so background tooling can find the agent later
+        agent.pane_id = mux.pane_id(&session_name, window_idx, pane_idx).ok();
+
+        if let Some(pane_id) = &agent.pane_id {
+            // Enable output capture for this pane (pipe to log file)
+            #[cfg(unix)]
+            let _ = mux.pipe_pane_to_file(pane_id, &agent.log_path);
+
+            // Keep the pane around if Claude Code exits so a supervisor can relaunch it in place
+            let _ = mux.set_remain_on_exit(pane_id);
         }
+        agent.restarts = 0;
+        agent.last_restart = None;
 
         agent.set_status(AgentStatus::Running);
         if use_custom_layout {
@@ -456,10 +399,20 @@ Now get started on your assigned work!"#,
 
             // Build environment variables for the executor
             // Set CLAUDE_BASH_MAINTAIN_PROJECT_WORKING_DIR=false to allow cd to project directories
-            let executor_env = format!(
+            let mut executor_env = format!(
                 "export COLONY_AGENT_ID={} && export CLAUDE_BASH_MAINTAIN_PROJECT_WORKING_DIR=false",
                 shell_escape(&executor_config.agent_id)
             );
+            let executor_exports =
+                secrets::mcp_exports(executor_config.mcp_servers.as_ref(), false)?;
+            let executor_env_file = executor_project_path.join(ENV_FILE);
+            write_env_file(&executor_env_file, &executor_exports)?;
+            if !executor_exports.is_empty() {
+                executor_env.push_str(&format!(
+                    " && source {}",
+                    shell_escape(&executor_env_file.to_string_lossy())
+                ));
+            }
 
             // Create settings.json for executor if it has MCP servers configured
             if executor_config.has_mcp_servers() {
@@ -521,6 +474,14 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
                 executor_config.agent_id
             );
 
+            let executor_models: Vec<String> = executor_config
+                .model_candidates()
+                .into_iter()
+                .map(String::from)
+                .collect();
+            let executor_model_file = executor_project_path.join("active_model");
+            let _ = std::fs::remove_file(&executor_model_file);
+
             let executor_cmd = if executor_config.has_mcp_servers() {
                 let executor_settings_path = executor_project_path.join(".claude/settings.json");
                 let executor_settings_str = executor_settings_path.to_str().ok_or_else(|| {
@@ -546,22 +507,36 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
                 }
 
                 format!(
-                    "{} && {} && cd {} && claude --mcp-config {} --strict-mcp-config --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                    "{} && {} && cd {} && {}",
                     shell_init,
                     executor_env,
                     shell_escape(executor_work_dir_str),
-                    shell_escape(executor_settings_str),
-                    shell_escape(executor_work_dir_str),
-                    shell_escape(&executor_prompt)
+                    claude_invocation(
+                        &executor_models,
+                        &executor_model_file,
+                        &format!(
+                            "--mcp-config {} --strict-mcp-config --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                            shell_escape(executor_settings_str),
+                            shell_escape(executor_work_dir_str),
+                            shell_escape(&executor_prompt)
+                        )
+                    )
                 )
             } else {
                 format!(
-                    "{} && {} && cd {} && claude --setting-sources local --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                    "{} && {} && cd {} && {}",
                     shell_init,
                     executor_env,
                     shell_escape(executor_work_dir_str),
-                    shell_escape(executor_work_dir_str),
-                    shell_escape(&executor_prompt)
+                    claude_invocation(
+                        &executor_models,
+                        &executor_model_file,
+                        &format!(
+                            "--setting-sources local --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                            shell_escape(executor_work_dir_str),
+                            shell_escape(&executor_prompt)
+                        )
+                    )
                 )
             };
 
@@ -570,7 +545,7 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
                 // With custom layout, find executor's position in pane_map
                 if let Some(coords) = pane_map.get("mcp-executor") {
                     // Send command to pre-existing pane
-                    tmux::send_command_to_window_pane(
+                    mux.send_command_to_window_pane(
                         &session_name,
                         coords.0,
                         coords.1,
@@ -580,9 +555,9 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
                 } else {
                     // Not in layout, use default position
                     let pane_idx = if agent_count > 0 {
-                        tmux::split_vertical(&session_name, &executor_cmd)?
+                        mux.split_vertical(&session_name, &executor_cmd)?
                     } else {
-                        tmux::send_command_to_pane(&session_name, 0, &executor_cmd)?;
+                        mux.send_command_to_pane(&session_name, 0, &executor_cmd)?;
                         0
                     };
                     (0, pane_idx)
@@ -590,9 +565,9 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
             } else {
                 // Default layout
                 let pane_idx = if agent_count > 0 {
-                    tmux::split_vertical(&session_name, &executor_cmd)?
+                    mux.split_vertical(&session_name, &executor_cmd)?
                 } else {
-                    tmux::send_command_to_pane(&session_name, 0, &executor_cmd)?;
+                    mux.send_command_to_pane(&session_name, 0, &executor_cmd)?;
                     0
                 };
                 (0, pane_idx)
@@ -601,14 +576,14 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
             executor_pane_index = Some(executor_pane_idx);
 
             if use_custom_layout {
-                tmux::set_window_pane_title(
+                mux.set_window_pane_title(
                     &session_name,
                     executor_window_idx,
                     executor_pane_idx,
                     &format!("MCP Executor: {}", executor_config.agent_id),
                 )?;
             } else {
-                tmux::set_pane_title(
+                mux.set_pane_title(
                     &session_name,
                     executor_pane_idx,
                     &format!("MCP Executor: {}", executor_config.agent_id),
@@ -622,28 +597,11 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
                     .colony_root()
                     .join("logs")
                     .join(format!("{}.log", executor_config.agent_id));
-                let log_path_str = executor_log_path.to_str().ok_or_else(|| {
-                    crate::error::ColonyError::Colony(
-                        "Invalid executor log path: contains non-UTF-8 characters".to_string(),
-                    )
-                })?;
-                let log_path = shell_escape(log_path_str);
-                let escaped_session = shell_escape(&session_name);
-                let pipe_cmd = if use_custom_layout {
-                    format!(
-                        "tmux pipe-pane -t {}:{}.{} 'cat >> {}'",
-                        escaped_session, executor_window_idx, executor_pane_idx, log_path
-                    )
-                } else {
-                    format!(
-                        "tmux pipe-pane -t {}:0.{} 'cat >> {}'",
-                        escaped_session, executor_pane_idx, log_path
-                    )
-                };
-                let _ = std::process::Command::new("sh")
-                    .arg("-c")
-                    .arg(&pipe_cmd)
-                    .output();
+                if let Ok(pane_id) =
+                    mux.pane_id(&session_name, executor_window_idx, executor_pane_idx)
+                {
+                    let _ = mux.pipe_pane_to_file(&pane_id, &executor_log_path);
+                }
             }
 
             if use_custom_layout {
@@ -674,6 +632,9 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
         )
     })?;
 
+    // Background commands use the same configuration as this one
+    let options = config::options();
+
     // Get current directory for the TUI to run in
     let current_dir = std::env::current_dir().map_err(|e| {
         crate::error::ColonyError::Colony(format!("Failed to get current directory: {}", e))
@@ -686,39 +647,39 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
 
     // Create a pane for the TUI
     if agent_count > 0 {
-        let tui_cmd = format!("cd {} && {} tui", current_dir_str, colony_path);
+        let tui_cmd = colony_command(current_dir_str, colony_path, &options, "tui");
 
         let (tui_window_idx, tui_pane_idx) = if use_custom_layout {
             // With custom layout, find TUI's position in pane_map
             if let Some(coords) = pane_map.get("tui") {
                 // Send TUI command to pre-existing pane
-                tmux::send_command_to_window_pane(&session_name, coords.0, coords.1, &tui_cmd)?;
+                mux.send_command_to_window_pane(&session_name, coords.0, coords.1, &tui_cmd)?;
                 *coords
             } else {
                 // Not in layout, use default
-                let pane_idx = tmux::split_vertical(&session_name, "bash")?;
+                let pane_idx = mux.split_vertical(&session_name, "bash")?;
                 tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                tmux::send_command_to_pane(&session_name, pane_idx, &tui_cmd)?;
+                mux.send_command_to_pane(&session_name, pane_idx, &tui_cmd)?;
                 (0, pane_idx)
             }
         } else {
             // Default layout: split with bash then send command
-            let pane_idx = tmux::split_vertical(&session_name, "bash")?;
+            let pane_idx = mux.split_vertical(&session_name, "bash")?;
             tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-            tmux::send_command_to_pane(&session_name, pane_idx, &tui_cmd)?;
+            mux.send_command_to_pane(&session_name, pane_idx, &tui_cmd)?;
             (0, pane_idx)
         };
 
         // Set title
         if use_custom_layout {
-            tmux::set_window_pane_title(
+            mux.set_window_pane_title(
                 &session_name,
                 tui_window_idx,
                 tui_pane_idx,
                 "Orchestration TUI",
             )?;
         } else {
-            tmux::set_pane_title(&session_name, tui_pane_idx, "Orchestration TUI")?;
+            mux.set_pane_title(&session_name, tui_pane_idx, "Orchestration TUI")?;
         }
 
         if use_custom_layout {
@@ -736,71 +697,338 @@ Read: .claude/skills/mcp-executor/COLONY-EXECUTOR.md"#,
 
     // Apply layout (only for default, custom layout already applied)
     if agent_count > 0 && !use_custom_layout {
-        tmux::select_tiled_layout(&session_name)?;
+        mux.select_tiled_layout(&session_name)?;
     }
 
-    // Save state
-    controller.save_state()?;
+    // Start the supervisor in a background window if requested
+    if supervise {
+        let supervise_cmd = colony_command(current_dir_str, colony_path, &options, "supervise");
+        match mux.create_background_window(
+            &session_name,
+            supervise::SUPERVISOR_WINDOW,
+            &supervise_cmd,
+        ) {
+            Ok(()) => utils::success("  Supervisor started"),
+            Err(e) => utils::warning(&format!("  Failed to start supervisor: {}", e)),
+        }
+    }
 
-    // Track colony started event (if telemetry is enabled)
-    if controller.config().telemetry.enabled {
-        let telemetry_client =
-            crate::colony::telemetry::TelemetryClient::new(controller.config().telemetry.clone());
-        let has_executor = controller
-            .config()
-            .executor
-            .as_ref()
-            .is_some_and(|e| e.enabled);
-        telemetry_client
-            .track_colony_started(agent_count, has_executor)
-            .await;
+    // Start the nudge scheduler in a background window if any agent uses it
+    if nudge::is_enabled(controller.config()) {
+        let nudge_cmd = colony_command(current_dir_str, colony_path, &options, "nudge");
+        match mux.create_background_window(&session_name, nudge::NUDGER_WINDOW, &nudge_cmd) {
+            Ok(()) => utils::success("  Nudge scheduler started"),
+            Err(e) => utils::warning(&format!("  Failed to start nudge scheduler: {}", e)),
+        }
     }
 
-    utils::header("Colony Started Successfully!");
-    let running_count = controller
-        .agents()
-        .values()
-        .filter(|a| a.is_running())
-        .count();
-    println!(
-        "Running agents: {}/{}",
-        running_count,
-        controller.agents().len()
-    );
+    // Start the message delivery watcher in a background window if any agent uses it
+    if delivery::is_enabled(controller.config()) {
+        let deliver_cmd = colony_command(current_dir_str, colony_path, &options, "deliver");
+        match mux.create_background_window(&session_name, delivery::DELIVERY_WINDOW, &deliver_cmd) {
+            Ok(()) => utils::success("  Message delivery started"),
+            Err(e) => utils::warning(&format!("  Failed to start message delivery: {}", e)),
+        }
+    }
 
-    if no_attach {
-        println!("\nNext steps:");
-        println!("  • Run 'colony attach' to view agents and TUI in tmux");
-        println!("  • The orchestration TUI is already running in a dedicated pane");
-        println!("  • Use 'colony status' to check agent status from CLI");
-        println!("  • Use 'colony logs <agent-id>' to view specific logs");
-        println!("\nTip: Press Ctrl+B then D to detach from tmux without stopping agents");
-    } else {
-        println!("\nAttaching to tmux session...");
-        println!("Tip: Press Ctrl+B then D to detach from tmux without stopping agents");
-        println!();
+    Ok(agent_count)
+}
 
-        // Small delay to ensure all panes are ready
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+/// Shell command that runs a colony subcommand in `dir` with the same
+/// `--config` and `--profile` as this process
+fn colony_command(
+    dir: &str,
+    colony_path: &str,
+    options: &config::ConfigOptions,
+    subcommand: &str,
+) -> String {
+    let mut command = format!("cd {} && {}", shell_escape(dir), shell_escape(colony_path));
+    if let Some(path) = &options.path {
+        let path = path.to_string_lossy();
+        command.push_str(&format!(" --config {}", shell_escape(&path)));
+    }
+    if let Some(profile) = &options.profile {
+        command.push_str(&format!(" --profile {}", shell_escape(profile)));
+    }
+    format!("{} {}", command, subcommand)
+}
 
-        // Attach to the session
-        tmux::attach_session(&session_name)?;
+/// Prepare an agent's project directory before launch: the startup prompt file,
+/// settings.json (if the agent has MCP servers) and agent skills
+pub(crate) async fn prepare_agent(
+    agent: &crate::colony::Agent,
+    config: &ColonyConfig,
+    colony_root: &Path,
+) {
+    // Create startup prompt file
+    if let Err(e) = create_startup_prompt(agent, config, colony_root).await {
+        utils::warning(&format!("  Failed to create startup prompt: {}", e));
     }
 
+    // Create settings.json file if agent has MCP server configuration
+    if agent.config.has_mcp_servers() {
+        match create_agent_settings(agent).await {
+            Ok(()) => {
+                utils::info(&"  Created settings.json with MCP server configuration".to_string());
+            }
+            Err(e) => {
+                utils::warning(&format!("  Failed to create settings.json: {}", e));
+            }
+        }
+    }
+
+    // Install agent skills (tmux, nvim, ollama) and create symlink from worktree
+    if let Err(e) = agent_skills::install_agent_skills(&agent.project_path, &agent.worktree_path) {
+        utils::warning(&format!("  Failed to install agent skills: {}", e));
+    }
+}
+
+/// Build the shell command that launches Claude Code for an agent
+/// Also records the primary model on the agent, since the command may fall back to another one,
+/// and the configuration the agent is launched with
+pub(crate) fn build_agent_command(
+    agent: &mut crate::colony::Agent,
+    config: &ColonyConfig,
+    colony_root: &Path,
+) -> ColonyResult<String> {
+    let models = agent.config.model_candidates();
+    if models.len() > 1 {
+        println!("  Fallback models: {}", models[1..].join(", "));
+    }
+    agent.model = models.first().map(|m| m.to_string());
+    let _ = std::fs::remove_file(agent.model_file());
+    agent.applied_config = Some(agent.config.clone());
+
+    write_env_file(
+        &agent.project_path.join(ENV_FILE),
+        &agent_env(agent, false)?,
+    )?;
+    agent_command(agent, config, colony_root)
+}
+
+/// Name of the file in an agent's project directory that holds its resolved
+/// environment variables
+pub(crate) const ENV_FILE: &str = "env.sh";
+
+/// Environment variables an agent is launched with, resolving secret
+/// references. MCP servers' secrets are included too, so that settings.json
+/// only refers to them. With `redact`, references are checked but kept as
+/// written.
+pub(crate) fn agent_env(
+    agent: &crate::colony::Agent,
+    redact: bool,
+) -> ColonyResult<Vec<(String, String)>> {
+    let mut env_vars = match &agent.config.env {
+        Some(env) => secrets::resolve_env(env, &format!("agents[{}].env", agent.id()), redact)?,
+        None => Vec::new(),
+    };
+    env_vars.extend(secrets::mcp_exports(
+        agent.config.mcp_servers.as_ref(),
+        redact,
+    )?);
+    Ok(env_vars)
+}
+
+/// Shell script exporting `env_vars`, for the launch command to source
+pub(crate) fn env_file_contents(env_vars: &[(String, String)]) -> String {
+    env_vars
+        .iter()
+        .map(|(key, value)| format!("export {}={}\n", shell_escape(key), shell_escape(value)))
+        .collect()
+}
+
+/// Write the environment file a launch command sources, readable only by its
+/// owner. Values are sourced rather than typed into the pane, so secrets stay
+/// out of its scrollback, log and shell history. Without variables, any old
+/// file is removed.
+fn write_env_file(path: &Path, env_vars: &[(String, String)]) -> ColonyResult<()> {
+    if env_vars.is_empty() {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => return Ok(()),
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // An existing file keeps its mode when opened
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, env_file_contents(env_vars).as_bytes())?;
     Ok(())
 }
 
+/// Render the shell command that launches Claude Code for an agent, without
+/// touching the agent or its files. Secret references are checked, but the
+/// command only sources the agent's environment file, which
+/// [`build_agent_command`] writes.
+pub(crate) fn agent_command(
+    agent: &crate::colony::Agent,
+    config: &ColonyConfig,
+    colony_root: &Path,
+) -> ColonyResult<String> {
+    // Build the claude command with properly escaped paths
+    let worktree_path_str = agent.worktree_path.to_str().ok_or_else(|| {
+        crate::error::ColonyError::Colony(format!(
+            "Invalid worktree path for agent '{}': contains non-UTF-8 characters",
+            agent.id()
+        ))
+    })?;
+
+    // Environment variables, including resolved secrets, come from the
+    // agent's environment file
+    let env_prefix = if agent_env(agent, true)?.is_empty() {
+        String::new()
+    } else {
+        let env_file = agent.project_path.join(ENV_FILE);
+        format!("source {} && ", shell_escape(&env_file.to_string_lossy()))
+    };
+
+    // Add capability environment variables
+    let resolved_capabilities = agent
+        .config
+        .resolved_capabilities(config.capabilities.as_ref());
+    let capabilities_env = if let Some(caps) = &resolved_capabilities {
+        let tools_str = caps.tools.join(",");
+        let mcp_servers_str = caps.mcp_servers.join(",");
+        let pane_tools_str = caps.pane_tools.join(",");
+
+        format!(
+            "export COLONY_TOOLS={} && export COLONY_MCP_SERVERS={} && export COLONY_PANE_TOOLS={} && ",
+            shell_escape(&tools_str),
+            shell_escape(&mcp_servers_str),
+            shell_escape(&pane_tools_str)
+        )
+    } else {
+        String::new()
+    };
+
+    // Build Claude command with optional settings path
+    // Source shell config first to ensure mise/asdf/nvm and other tool managers are loaded
+    let shell_init = "source ~/.zshrc 2>/dev/null || source ~/.bashrc 2>/dev/null || true";
+
+    // The rendered startup prompt, see `render_startup_prompt`
+    let startup_prompt = render_startup_prompt(agent, config, colony_root)?;
+
+    // Models to try in order; the launch command records the one in use
+    let models: Vec<String> = agent
+        .config
+        .model_candidates()
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let claude_cmd = if agent.config.has_mcp_servers() {
+        let settings_path = agent.project_path.join(".claude").join("settings.json");
+        let settings_path_str = settings_path.to_str().ok_or_else(|| {
+            crate::error::ColonyError::Colony(format!(
+                "Invalid settings path for agent '{}': contains non-UTF-8 characters",
+                agent.id()
+            ))
+        })?;
+        format!(
+            "{} && {}{}cd {} && {}",
+            shell_init,
+            env_prefix,
+            capabilities_env,
+            shell_escape(worktree_path_str),
+            claude_invocation(
+                &models,
+                &agent.model_file(),
+                &format!(
+                    "--mcp-config {} --strict-mcp-config --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                    shell_escape(settings_path_str),
+                    shell_escape(worktree_path_str),
+                    shell_escape(&startup_prompt)
+                )
+            )
+        )
+    } else {
+        format!(
+            "{} && {}{}cd {} && {}",
+            shell_init,
+            env_prefix,
+            capabilities_env,
+            shell_escape(worktree_path_str),
+            claude_invocation(
+                &models,
+                &agent.model_file(),
+                &format!(
+                    "--setting-sources local --permission-mode bypassPermissions --add-dir {} --append-system-prompt {}",
+                    shell_escape(worktree_path_str),
+                    shell_escape(&startup_prompt)
+                )
+            )
+        )
+    };
+
+    Ok(claude_cmd)
+}
+
 /// Create a startup prompt file for an agent and return the prompt text
 async fn create_startup_prompt(
     agent: &crate::colony::Agent,
-    repo_config: Option<&crate::colony::config::RepositoryConfig>,
-    has_shared_state: bool,
+    config: &ColonyConfig,
+    colony_root: &Path,
 ) -> ColonyResult<String> {
     let prompt_path = agent.project_path.join("startup_prompt.txt");
+    let prompt = render_startup_prompt(agent, config, colony_root)?;
 
-    // If a custom startup prompt is provided, use it directly
-    let prompt = if let Some(custom_prompt) = &agent.config.startup_prompt {
-        custom_prompt.clone()
+    let mut file = File::create(&prompt_path).await?;
+    file.write_all(prompt.as_bytes()).await?;
+    file.flush().await?;
+
+    Ok(prompt)
+}
+
+/// Render an agent's startup prompt: its custom prompt if it has one, otherwise
+/// the default colony prompt. Custom prompts and instructions are templates,
+/// see [`prompt`].
+pub(crate) fn render_startup_prompt(
+    agent: &crate::colony::Agent,
+    config: &ColonyConfig,
+    colony_root: &Path,
+) -> ColonyResult<String> {
+    let repo_config = config.repository.as_ref();
+    let has_shared_state = config.shared_state.is_some();
+    let variables = prompt::variables(agent, config, colony_root);
+    let prompts_dir = colony_root.join(prompt::PROMPTS_DIR);
+
+    // Build working style section from the agent's behavior settings
+    let guidance = agent
+        .config
+        .behavior
+        .as_ref()
+        .map(|b| b.guidance())
+        .unwrap_or_default();
+    let behavior_section = if guidance.is_empty() {
+        String::new()
+    } else {
+        let mut section = "\n## Working Style\n\n".to_string();
+        for line in guidance {
+            section.push_str(&format!("- {}\n", line));
+        }
+        section
+    };
+
+    // If a custom startup prompt is provided, render it and append the working style
+    if let Some(custom_prompt) = &agent.config.startup_prompt {
+        let mut prompt = prompt::render(custom_prompt, &variables, &prompts_dir)?;
+        if !behavior_section.is_empty() && !prompt.ends_with('\n') {
+            prompt.push('\n');
+        }
+        prompt.push_str(&behavior_section);
+        Ok(prompt)
     } else {
         // Build repository context section if available
         let repo_context = if let Some(repo_cfg) = repo_config {
@@ -892,6 +1120,28 @@ For complete documentation:
             ""
         };
 
+        // Build capabilities section if configured
+        let capabilities_section = match agent
+            .config
+            .resolved_capabilities(config.capabilities.as_ref())
+        {
+            Some(caps) => format!(
+                r#"
+## Your Capabilities
+
+**Tools**: {}
+**MCP Servers**: {}
+**Pane Tools**: {}
+
+Environment variables: `$COLONY_TOOLS`, `$COLONY_MCP_SERVERS`, `$COLONY_PANE_TOOLS`
+"#,
+                or_none(&caps.tools),
+                or_none(&caps.mcp_servers),
+                or_none(&caps.pane_tools)
+            ),
+            None => String::new(),
+        };
+
         // Otherwise, generate the default colony prompt
         let mut prompt = format!(
             r#"# Welcome to Colony
@@ -901,7 +1151,7 @@ You are **{}** working as part of a multi-agent colony.
 ## Your Role
 **Role**: {}
 **Focus**: {}
-
+{}{}
 ## Communication System
 
 You can communicate with other agents using the message queue system:
@@ -918,6 +1168,10 @@ You can communicate with other agents using the message queue system:
 
 # Broadcast to all agents
 ./colony_message.sh send all "Important announcement"
+
+# Hand off work or ask something (--type is info by default)
+./colony_message.sh send --type task <agent-id> "Please review the API changes"
+./colony_message.sh send --type question <agent-id> "Which schema version?"
 ```
 
 ### List Other Agents
@@ -925,6 +1179,9 @@ You can communicate with other agents using the message queue system:
 ./colony_message.sh list-agents
 ```
 
+If your worktree is shared with other agents, use `./colony_message_{}.sh` in
+place of `./colony_message.sh` so messages are sent as you.
+
 ## Best Practices
 
 1. **Check messages regularly** - Run `./colony_message.sh read` periodically
@@ -962,35 +1219,60 @@ For detailed messaging guidance, see the Colony Message skill:
             repo_context,
             agent.config.role,
             agent.config.focus,
+            capabilities_section,
+            agent.id(),
+            behavior_section,
             state_section
         );
 
         // Append custom instructions if provided
         if let Some(instructions) = &agent.config.instructions {
             prompt.push_str("\n\n---\n\n## Additional Instructions\n\n");
-            prompt.push_str(instructions);
+            prompt.push_str(&prompt::render(instructions, &variables, &prompts_dir)?);
             prompt.push('\n');
         }
 
         prompt.push_str("\nNow get started on your assigned work! Remember to check for messages from your teammates.\n");
-        prompt
-    };
-
-    let mut file = File::create(&prompt_path).await?;
-    file.write_all(prompt.as_bytes()).await?;
-    file.flush().await?;
+        Ok(prompt)
+    }
+}
 
-    Ok(prompt)
+fn or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "None".to_string()
+    } else {
+        items.join(", ")
+    }
 }
 
 /// Create a settings.json file for an agent with MCP server configuration
 async fn create_agent_settings(agent: &crate::colony::Agent) -> ColonyResult<()> {
-    use serde_json::Value;
-
     // Create .claude directory in the project path
     let claude_dir = agent.project_path.join(".claude");
     tokio::fs::create_dir_all(&claude_dir).await?;
 
+    let merged_settings = merged_agent_settings(agent).await?;
+
+    // Write merged settings.json file
+    let settings_json = serde_json::to_string_pretty(&merged_settings).map_err(|e| {
+        crate::error::ColonyError::Colony(format!("Failed to serialize merged settings: {}", e))
+    })?;
+
+    let settings_path = claude_dir.join("settings.json");
+    let mut file = File::create(&settings_path).await?;
+    file.write_all(settings_json.as_bytes()).await?;
+    file.flush().await?;
+
+    Ok(())
+}
+
+/// Merge an agent's MCP server configuration into the settings.json in its
+/// working directory, if there is one
+pub(crate) async fn merged_agent_settings(
+    agent: &crate::colony::Agent,
+) -> ColonyResult<serde_json::Value> {
+    use serde_json::Value;
+
     // Check if there's an existing settings.json in the working directory
     let worktree_settings_path = agent.worktree_path.join(".claude").join("settings.json");
     let mut merged_settings: Value = serde_json::json!({});
@@ -1046,49 +1328,15 @@ async fn create_agent_settings(agent: &crate::colony::Agent) -> ColonyResult<()>
         merged_settings = agent_settings;
     }
 
-    // Write merged settings.json file
-    let settings_json = serde_json::to_string_pretty(&merged_settings).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to serialize merged settings: {}", e))
-    })?;
-
-    let settings_path = claude_dir.join("settings.json");
-    let mut file = File::create(&settings_path).await?;
-    file.write_all(settings_json.as_bytes()).await?;
-    file.flush().await?;
-
-    Ok(())
+    Ok(merged_settings)
 }
 
 /// Set up messaging infrastructure for the colony
-fn setup_messaging_infrastructure(controller: &ColonyController) -> ColonyResult<()> {
+pub(crate) fn setup_messaging_infrastructure(controller: &ColonyController) -> ColonyResult<()> {
     let colony_root = controller.colony_root();
 
-    // Create messaging directory structure
-    let messages_dir = colony_root.join("messages");
-    std::fs::create_dir_all(&messages_dir)?;
-
-    // Create broadcast directory
-    std::fs::create_dir_all(messages_dir.join("broadcast"))?;
-
-    // Create inbox for each agent
-    for agent in controller.agents().values() {
-        let inbox_dir = messages_dir.join(agent.id());
-        std::fs::create_dir_all(&inbox_dir)?;
-
-        let sent_dir = inbox_dir.join("sent");
-        std::fs::create_dir_all(&sent_dir)?;
-    }
-
-    // Create inbox for executor if enabled
-    if let Some(executor_config) = &controller.config().executor {
-        if executor_config.enabled {
-            let inbox_dir = messages_dir.join(&executor_config.agent_id);
-            std::fs::create_dir_all(&inbox_dir)?;
-
-            let sent_dir = inbox_dir.join("sent");
-            std::fs::create_dir_all(&sent_dir)?;
-        }
-    }
+    // Create the message store, importing messages from older colonies
+    messaging::open_store(colony_root)?;
 
     // Create messaging README
     messaging::create_messaging_readme(colony_root)?;
@@ -1165,6 +1413,60 @@ fn shell_escape(s: &str) -> String {
     format!("'{}'", s.replace('\'', "'\\''"))
 }
 
+/// Claude Code's error output when the model it was given is rejected, as an
+/// extended regular expression
+const MODEL_ERROR_PATTERN: &str = "not_found_error|invalid model|model.*(not found|not available|not supported|does not exist)|issue with the selected model";
+
+/// Lines at the start of Claude Code's error output that are searched for
+/// [`MODEL_ERROR_PATTERN`]. A rejected model is reported right away, so later
+/// output that happens to mention a model isn't mistaken for it.
+const MODEL_ERROR_LINES: usize = 5;
+
+/// Build the `claude` invocation for a pane command
+/// Each model is tried in order, falling back to the next one only when Claude Code exits with
+/// an error and its first lines of error output say the model was rejected. Quitting, crashes
+/// and other errors end the command. Error output is still shown in the pane, and copied to a
+/// file to check it. The model being launched is written to `model_file` so the model actually
+/// in use can be recorded in the agent state, and removed again if Claude Code fails.
+fn claude_invocation(models: &[String], model_file: &Path, args: &str) -> String {
+    if models.is_empty() {
+        return format!("claude {}", args);
+    }
+
+    let error_file = shell_escape(&model_file.with_extension("err").to_string_lossy());
+    let status_file = shell_escape(&model_file.with_extension("status").to_string_lossy());
+    let model_file = shell_escape(&model_file.to_string_lossy());
+    let rejected = format!(
+        "head -n {} {} | grep -qiE {}",
+        MODEL_ERROR_LINES,
+        error_file,
+        shell_escape(MODEL_ERROR_PATTERN)
+    );
+
+    // Built from the last model back, each attempt falling back to the ones after it
+    let failed = format!("rm -f {}; (exit $rc)", model_file);
+    let mut command: Option<String> = None;
+    for model in models.iter().rev() {
+        let model = shell_escape(model);
+        // stderr goes through tee and stdout straight to the pane; the exit
+        // status is passed out of the pipeline in a file
+        let attempt = format!(
+            "printf '%s' {} > {}; {{ {{ claude --model {} {}; echo $? > {}; }} 2>&1 1>&3 3>&- | tee {} >&2; }} 3>&1; rc=$(cat {})",
+            model, model_file, model, args, status_file, error_file, status_file
+        );
+        let on_error = match &command {
+            Some(fallback) => format!("if {}; then {}; else {}; fi", rejected, fallback, failed),
+            None => failed.clone(),
+        };
+        command = Some(format!(
+            "{}; if [ \"$rc\" -ne 0 ]; then {}; fi",
+            attempt, on_error
+        ));
+    }
+
+    command.unwrap_or_default()
+}
+
 /// Send a prompt to a tmux pane using send-keys
 /// The prompt is sent directly to Claude Code running in the pane
 fn send_prompt_to_pane(session_name: &str, pane_index: usize, prompt: &str) -> ColonyResult<()> {
@@ -1230,3 +1532,317 @@ async fn create_executor_settings(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::multiplexer::fake::FakeMultiplexer;
+    use crate::colony::multiplexer::PaneState;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_claude_invocation_fallback() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::Command;
+
+        let dir = tempdir().unwrap();
+        // A stand-in for Claude Code that logs each launch and fails like the real one
+        let claude = dir.path().join("claude");
+        std::fs::write(
+            &claude,
+            r#"#!/bin/sh
+echo "$2" >> "$(dirname "$0")/launches"
+case "$2" in
+  retired) echo 'API Error: 404 {"type":"error","error":{"type":"not_found_error","message":"model: retired"}}' >&2; exit 1 ;;
+  crashing) echo 'Segmentation fault' >&2; exit 139 ;;
+  chatty) for i in 1 2 3 4 5 6; do echo 'Working' >&2; done; echo 'Error: model not found in the test data' >&2; exit 1 ;;
+  talking) echo 'The model was not found, using defaults' >&2; exit 0 ;;
+  *) echo 'Hello' ;;
+esac
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&claude, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let model_file = dir.path().join("active_model");
+        let path = std::env::var("PATH").unwrap();
+        let run = |models: &[&str]| {
+            let _ = std::fs::remove_file(dir.path().join("launches"));
+            let models: Vec<String> = models.iter().map(|m| m.to_string()).collect();
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(claude_invocation(&models, &model_file, "--verbose"))
+                .env("PATH", format!("{}:{}", dir.path().display(), path))
+                .output()
+                .unwrap();
+            let launches = std::fs::read_to_string(dir.path().join("launches")).unwrap();
+            (output, launches)
+        };
+
+        // A rejected model falls back to the next one
+        let (output, launches) = run(&["retired", "sonnet", "haiku"]);
+        assert!(output.status.success());
+        assert_eq!(launches, "retired\nsonnet\n");
+        assert_eq!(std::fs::read_to_string(&model_file).unwrap(), "sonnet");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello\n");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not_found_error"));
+
+        // Quitting normally doesn't launch the fallback
+        let (output, launches) = run(&["sonnet", "haiku"]);
+        assert!(output.status.success());
+        assert_eq!(launches, "sonnet\n");
+
+        // Neither does any other failure, which is still shown and leaves no
+        // model recorded
+        let (output, launches) = run(&["crashing", "haiku"]);
+        assert_eq!(output.status.code(), Some(139));
+        assert_eq!(launches, "crashing\n");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Segmentation fault"));
+        assert!(!model_file.exists());
+
+        // Nor a failure that mentions a model only after its first lines
+        let (output, launches) = run(&["chatty", "haiku"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(launches, "chatty\n");
+
+        // Nor a successful run that mentions one
+        let (output, launches) = run(&["talking", "haiku"]);
+        assert!(output.status.success());
+        assert_eq!(launches, "talking\n");
+    }
+
+    #[test]
+    fn test_colony_command() {
+        let options = config::ConfigOptions::default();
+        assert_eq!(
+            colony_command("/repo", "/bin/colony", &options, "nudge"),
+            "cd '/repo' && '/bin/colony' nudge"
+        );
+
+        let options = config::ConfigOptions {
+            path: Some("ops/colony.yml".into()),
+            profile: Some("ci".to_string()),
+        };
+        assert_eq!(
+            colony_command("/repo", "/bin/colony", &options, "deliver"),
+            "cd '/repo' && '/bin/colony' --config 'ops/colony.yml' --profile 'ci' deliver"
+        );
+    }
+
+    #[test]
+    fn test_startup_prompt_behavior() {
+        let dir = tempdir().unwrap();
+        let yaml = format!(
+            r#"
+name: test
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    directory: {dir}
+    behavior:
+      thoroughness: high
+  - id: reviewer
+    role: Reviewer
+    focus: Code review
+    directory: {dir}
+    startup_prompt: Review every open pull request.
+    behavior:
+      communication_style: terse
+"#,
+            dir = dir.path().display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+        let colony_root = dir.path().join(".colony");
+        let mut controller = ColonyController::with_root(config, colony_root.clone()).unwrap();
+        controller.initialize_agents().unwrap();
+        let config = controller.config();
+
+        // The working style is part of both the default and a custom prompt,
+        // and of the command Claude is launched with
+        let backend = controller.get_agent("backend").unwrap();
+        let prompt = render_startup_prompt(backend, config, &colony_root).unwrap();
+        assert!(prompt.contains("## Working Style\n\n- Cover edge cases"));
+        let command = agent_command(backend, config, &colony_root).unwrap();
+        assert!(command.contains("- Cover edge cases"));
+
+        let reviewer = controller.get_agent("reviewer").unwrap();
+        let prompt = render_startup_prompt(reviewer, config, &colony_root).unwrap();
+        assert!(prompt.starts_with("Review every open pull request.\n\n## Working Style"));
+        assert!(prompt.contains("- Keep messages to a line or two"));
+        let command = agent_command(reviewer, config, &colony_root).unwrap();
+        assert!(command.contains("- Keep messages to a line or two"));
+    }
+
+    #[test]
+    fn test_secrets_stay_out_of_command() {
+        let dir = tempdir().unwrap();
+        let secret_path = dir.path().join("token");
+        std::fs::write(&secret_path, "s3cr3t-token\n").unwrap();
+        let yaml = format!(
+            r#"
+name: test
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    directory: {dir}
+    env:
+      API_TOKEN: ${{file:{secret}}}
+    mcp_servers:
+      github:
+        command: npx
+        env:
+          GITHUB_TOKEN: ${{file:{secret}}}
+"#,
+            dir = dir.path().display(),
+            secret = secret_path.display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+        let colony_root = dir.path().join(".colony");
+        let mut controller =
+            ColonyController::with_root(config.clone(), colony_root.clone()).unwrap();
+        controller.initialize_agents().unwrap();
+        let agent = controller.get_agent_mut("backend").unwrap();
+
+        let command = build_agent_command(agent, &config, &colony_root).unwrap();
+        assert!(!command.contains("s3cr3t-token"));
+        let env_file = agent.project_path.join(ENV_FILE);
+        assert!(command.contains(&format!("source '{}'", env_file.display())));
+
+        let contents = std::fs::read_to_string(&env_file).unwrap();
+        assert!(contents.contains("export 'API_TOKEN'='s3cr3t-token'\n"));
+        assert!(contents.contains("export 'COLONY_SECRET_GITHUB_GITHUB_TOKEN'='s3cr3t-token'\n"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&env_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        let yaml = r#"
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    delivery:
+      enabled: true
+"#;
+        let config: ColonyConfig = serde_yaml::from_str(yaml).unwrap();
+        let zellij = multiplexer::ZellijMultiplexer::default();
+        assert_eq!(
+            unsupported_features(&config, &zellij, true),
+            vec!["the supervisor (--supervise)", "message delivery"]
+        );
+        assert!(unsupported_features(&config, &FakeMultiplexer::default(), true).is_empty());
+
+        let plain: ColonyConfig = serde_yaml::from_str(&yaml.replace("true", "false")).unwrap();
+        assert!(unsupported_features(&plain, &zellij, false).is_empty());
+    }
+
+    #[test]
+    fn test_template_prompt_in_command() {
+        let dir = tempdir().unwrap();
+        let value: serde_yaml::Value = serde_yaml::from_str(&format!(
+            "name: test\nagents:\n  - id: security\n    template: security-auditor\n    directory: {}\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        let colony_root = dir.path().join(".colony");
+        let config = ColonyConfig::from_value(value, dir.path()).unwrap();
+
+        let mut controller = ColonyController::with_root(config, colony_root.clone()).unwrap();
+        controller.initialize_agents().unwrap();
+        let agent = controller.get_agent("security").unwrap();
+
+        // The template's instructions reach Claude through the startup prompt
+        let command = agent_command(agent, controller.config(), &colony_root).unwrap();
+        assert!(command.contains("## Additional Instructions"));
+        assert!(command.contains("OWASP"));
+    }
+
+    #[tokio::test]
+    async fn test_launch_session() {
+        let dir = tempdir().unwrap();
+        let yaml = format!(
+            r#"
+name: test
+agents:
+  - id: backend
+    role: Backend Engineer
+    focus: API
+    directory: {dir}
+  - id: frontend
+    role: Frontend Engineer
+    focus: UI
+    directory: {dir}
+"#,
+            dir = dir.path().display()
+        );
+        let config: ColonyConfig = serde_yaml::from_str(&yaml).unwrap();
+
+        let fake = Arc::new(FakeMultiplexer::default());
+        let mut controller = ColonyController::with_root(config, dir.path().join(".colony"))
+            .unwrap()
+            .with_multiplexer(fake.clone());
+        controller.initialize_agents().unwrap();
+
+        let agent_count = launch_session(&mut controller, fake.as_ref(), true)
+            .await
+            .unwrap();
+        assert_eq!(agent_count, 2);
+
+        let session = fake.session("colony-test").unwrap();
+        assert_eq!(session.windows.len(), 2);
+        assert_eq!(session.windows[0].panes.len(), 3);
+        assert_eq!(session.windows[1].name, supervise::SUPERVISOR_WINDOW);
+        assert_eq!(session.windows[0].layout.as_deref(), Some("tiled"));
+
+        for agent_id in ["backend", "frontend"] {
+            let pane = session
+                .pane_titled(&format!("Agent: {}", agent_id))
+                .unwrap();
+            assert_eq!(pane.commands.len(), 1);
+            assert!(pane.commands[0].contains("claude --model"));
+            assert!(pane.remain_on_exit);
+
+            let agent = controller.get_agent(agent_id).unwrap();
+            assert_eq!(agent.status, AgentStatus::Running);
+            assert_eq!(agent.pane_id.as_deref(), Some(pane.id.as_str()));
+            #[cfg(unix)]
+            assert_eq!(pane.log_path.as_ref(), Some(&agent.log_path));
+        }
+
+        // Panes are where `--dry-run` says they will be
+        for (key, (window, pane)) in layout::planned_pane_map(controller.config(), true) {
+            let expected = match key.as_str() {
+                "tui" => "Orchestration TUI".to_string(),
+                agent_id => format!("Agent: {}", agent_id),
+            };
+            let title = session.windows[window].panes[pane].title.as_deref();
+            assert_eq!(title, Some(expected.as_str()));
+        }
+
+        let tui = session.pane_titled("Orchestration TUI").unwrap();
+        assert_eq!(tui.commands[0], "bash");
+        assert!(tui.commands[1].ends_with(" tui"));
+
+        // A dead agent is relaunched in its own pane
+        let pane_id = controller
+            .get_agent("backend")
+            .unwrap()
+            .pane_id
+            .clone()
+            .unwrap();
+        fake.set_pane_state(&pane_id, PaneState::Dead);
+        assert_eq!(controller.restart_agent("backend").await.unwrap(), pane_id);
+        let pane = fake.pane(&pane_id).unwrap();
+        assert_eq!(pane.state, PaneState::Running);
+        assert_eq!(pane.commands.len(), 2);
+    }
+}
@@ -420,20 +420,20 @@ impl GitBackedState {
     // ========================================================================
 
     /// Get all memory entries
+    ///
+    /// Configs that list their own schemas without a "memory" one have no memory.
     pub async fn get_memory(&self) -> ColonyResult<Vec<MemoryEntry>> {
-        // For now, we don't have a "memory" schema in the default config
-        // This would need to be added to state_config.rs default_schemas()
-        // For now, return empty vec
-        Ok(Vec::new())
+        if self.config.get_schema("memory").is_none() {
+            return Ok(Vec::new());
+        }
+        self.sync_cache_from_jsonl::<MemoryEntry>("memory").await
     }
 
     /// Add a memory entry
     pub async fn add_memory(&self, entry: MemoryEntry) -> ColonyResult<()> {
         let mut entries = self.get_memory().await?;
         entries.push(entry);
-        // Would need "memory" schema defined
-        // self.export_to_jsonl("memory", &entries).await?;
-        Ok(())
+        self.export_to_jsonl("memory", &entries).await
     }
 }
 
@@ -521,6 +521,31 @@ mod tests {
         assert_eq!(retrieved.unwrap().name, "Test Workflow");
     }
 
+    #[tokio::test]
+    async fn test_memory_operations() {
+        let dir = tempdir().unwrap();
+        let repo_root = dir.path().to_path_buf();
+
+        let config = SharedStateConfig::default();
+        let state = GitBackedState::new(config, repo_root).unwrap();
+
+        state
+            .add_memory(MemoryEntry {
+                timestamp: Utc::now(),
+                entry_type: MemoryType::Context,
+                key: Some("API_URL".to_string()),
+                value: Some("https://api.example.com".to_string()),
+                content: None,
+            })
+            .await
+            .unwrap();
+
+        let entries = state.get_memory().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key.as_deref(), Some("API_URL"));
+        assert!(state.state_dir().join("memory.jsonl").exists());
+    }
+
     #[tokio::test]
     async fn test_ready_tasks() {
         let dir = tempdir().unwrap();
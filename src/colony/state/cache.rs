@@ -1,10 +1,13 @@
 //! SQLite cache layer for fast state queries
 
+use crate::colony::messaging::Message;
 use crate::colony::state::types::{MemoryEntry, Task, TaskStatus, Workflow, WorkflowStatus};
 use crate::error::{ColonyError, ColonyResult};
-use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Utc};
+use rusqlite::types::{ToSql, Type};
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use std::path::Path;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// SQLite cache for state queries
 pub struct StateCache {
@@ -24,6 +27,10 @@ impl StateCache {
         let conn = Connection::open(path).map_err(|e| {
             ColonyError::InvalidConfig(format!("Failed to open cache database: {}", e))
         })?;
+        // The TUI, nudger and CLI may all write to the cache at once
+        conn.busy_timeout(Duration::from_secs(5)).map_err(|e| {
+            ColonyError::InvalidConfig(format!("Failed to configure cache database: {}", e))
+        })?;
 
         let cache = Self { conn };
         cache.initialize_schema()?;
@@ -82,6 +89,39 @@ impl StateCache {
                 CREATE INDEX IF NOT EXISTS idx_memory_type ON memory(type);
                 CREATE INDEX IF NOT EXISTS idx_memory_key ON memory(key);
 
+                -- Messages between agents
+                CREATE TABLE IF NOT EXISTS messages (
+                    id TEXT PRIMARY KEY,
+                    sender TEXT NOT NULL,
+                    recipient TEXT NOT NULL,
+                    sent_at INTEGER NOT NULL,  -- Unix timestamp in nanoseconds
+                    thread_id TEXT,
+                    content TEXT NOT NULL,
+                    data TEXT NOT NULL         -- JSON of the whole message
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_messages_sender ON messages(sender);
+                CREATE INDEX IF NOT EXISTS idx_messages_sent_at ON messages(sent_at);
+                CREATE INDEX IF NOT EXISTS idx_messages_thread ON messages(thread_id);
+
+                -- Inboxes each message was delivered to (agent IDs or "broadcast")
+                CREATE TABLE IF NOT EXISTS message_inboxes (
+                    message_id TEXT NOT NULL,
+                    inbox TEXT NOT NULL,
+                    PRIMARY KEY (message_id, inbox)
+                );
+
+                -- Full-text index of message content
+                CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                    content,
+                    content='messages',
+                    content_rowid='rowid'
+                );
+
+                CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages BEGIN
+                    INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+                END;
+
                 -- Metadata table for tracking JSONL file modification times
                 CREATE TABLE IF NOT EXISTS cache_metadata (
                     schema_name TEXT PRIMARY KEY,
@@ -501,6 +541,195 @@ impl StateCache {
 
         Ok(entries)
     }
+
+    // ========================================================================
+    // Message Operations
+    // ========================================================================
+
+    /// Store a new message, delivered to each of `inboxes` (agent IDs or
+    /// "broadcast")
+    pub fn save_message(&mut self, message: &Message, inboxes: &[&str]) -> ColonyResult<()> {
+        let tx = self.conn.transaction().map_err(|e| {
+            ColonyError::InvalidConfig(format!("Failed to start transaction: {}", e))
+        })?;
+
+        insert_message(&tx, message)?;
+        for inbox in inboxes {
+            insert_inbox(&tx, message, inbox)?;
+        }
+
+        tx.commit().map_err(|e| {
+            ColonyError::InvalidConfig(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Import messages found in `inbox` (None for a sender's outbox). Messages
+    /// already stored are kept; returns how many were new.
+    pub fn import_messages(
+        &mut self,
+        inbox: Option<&str>,
+        messages: &[Message],
+    ) -> ColonyResult<usize> {
+        let tx = self.conn.transaction().map_err(|e| {
+            ColonyError::InvalidConfig(format!("Failed to start transaction: {}", e))
+        })?;
+
+        let mut imported = 0;
+        for message in messages {
+            imported += insert_message(&tx, message)?;
+            if let Some(inbox) = inbox {
+                insert_inbox(&tx, message, inbox)?;
+            }
+        }
+
+        tx.commit().map_err(|e| {
+            ColonyError::InvalidConfig(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(imported)
+    }
+
+    /// Get all messages, oldest first
+    pub fn get_messages(&self) -> ColonyResult<Vec<Message>> {
+        self.query_messages("SELECT data FROM messages ORDER BY sent_at, id", &[])
+    }
+
+    /// Get the messages in an agent's inbox and all broadcasts, oldest first
+    pub fn get_inbox_messages(&self, agent_id: &str) -> ColonyResult<Vec<Message>> {
+        self.query_messages(
+            r#"
+            SELECT data FROM messages WHERE id IN (
+                SELECT message_id FROM message_inboxes WHERE inbox IN (?, 'broadcast')
+            )
+            ORDER BY sent_at, id
+            "#,
+            &[&agent_id],
+        )
+    }
+
+    /// Get message by ID
+    pub fn get_message(&self, id: &str) -> ColonyResult<Option<Message>> {
+        Ok(self
+            .query_messages("SELECT data FROM messages WHERE id = ?", &[&id])?
+            .into_iter()
+            .next())
+    }
+
+    /// Search messages, returning the most recent matches oldest first
+    pub fn search_messages(&self, search: &MessageSearch) -> ColonyResult<Vec<Message>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        if let Some(text) = search.text.as_deref().filter(|t| !t.trim().is_empty()) {
+            conditions.push(
+                "rowid IN (SELECT rowid FROM messages_fts WHERE messages_fts MATCH ?)".to_string(),
+            );
+            values.push(Box::new(fts_query(text)));
+        }
+        if let Some(from) = &search.from {
+            conditions.push("sender = ?".to_string());
+            values.push(Box::new(from.clone()));
+        }
+        if let Some(since) = search.since {
+            conditions.push("sent_at >= ?".to_string());
+            values.push(Box::new(since.timestamp_nanos_opt().unwrap_or(0)));
+        }
+
+        let mut sql = "SELECT data FROM messages".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        sql.push_str(" ORDER BY sent_at DESC, id DESC");
+        if let Some(limit) = search.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        let values: Vec<&dyn ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        let mut messages = self.query_messages(&sql, &values)?;
+        messages.reverse();
+        Ok(messages)
+    }
+
+    fn query_messages(&self, sql: &str, values: &[&dyn ToSql]) -> ColonyResult<Vec<Message>> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| ColonyError::InvalidConfig(format!("Failed to prepare query: {}", e)))?;
+
+        let messages = stmt
+            .query_map(values, message_from_row)
+            .map_err(|e| ColonyError::InvalidConfig(format!("Failed to query messages: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                ColonyError::InvalidConfig(format!("Failed to parse message row: {}", e))
+            })?;
+
+        Ok(messages)
+    }
+}
+
+/// Filters for [`StateCache::search_messages`]
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearch {
+    /// Words that must all appear in the message content
+    pub text: Option<String>,
+    /// Sender agent ID
+    pub from: Option<String>,
+    /// Only messages sent at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum number of messages, keeping the most recent
+    pub limit: Option<usize>,
+}
+
+/// Insert a message unless one with its ID is stored, returning the rows added
+fn insert_message(conn: &Connection, message: &Message) -> ColonyResult<usize> {
+    let sent_at = DateTime::parse_from_rfc3339(&message.timestamp)
+        .ok()
+        .and_then(|t| t.timestamp_nanos_opt())
+        .unwrap_or(0);
+
+    conn.execute(
+        r#"
+        INSERT OR IGNORE INTO messages (id, sender, recipient, sent_at, thread_id, content, data)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        params![
+            message.id,
+            message.from,
+            message.to,
+            sent_at,
+            message.thread(),
+            message.content,
+            serde_json::to_string(message)?,
+        ],
+    )
+    .map_err(|e| ColonyError::InvalidConfig(format!("Failed to insert message: {}", e)))
+}
+
+/// Record that a message was delivered to `inbox`
+fn insert_inbox(conn: &Connection, message: &Message, inbox: &str) -> ColonyResult<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO message_inboxes (message_id, inbox) VALUES (?, ?)",
+        params![message.id, inbox],
+    )
+    .map_err(|e| ColonyError::InvalidConfig(format!("Failed to record message inbox: {}", e)))?;
+    Ok(())
+}
+
+fn message_from_row(row: &Row) -> rusqlite::Result<Message> {
+    let data: String = row.get(0)?;
+    serde_json::from_str(&data)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))
+}
+
+/// Quote each word so text like `backend-1` isn't read as FTS5 query syntax
+fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[cfg(test)]
@@ -664,4 +893,105 @@ mod tests {
         let later = SystemTime::now();
         assert!(cache.needs_refresh("tasks", later).unwrap());
     }
+
+    #[test]
+    fn test_cache_messages() {
+        use crate::colony::messaging::MessageType;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let mut cache = StateCache::open(&db_path).unwrap();
+
+        let mut migration = Message::new(
+            "backend-1",
+            "frontend",
+            "Schema migration is done".to_string(),
+            MessageType::Completed,
+        );
+        migration.timestamp = (Utc::now() - chrono::Duration::hours(3)).to_rfc3339();
+        let question = Message::new(
+            "frontend",
+            "backend-1",
+            "Does the migration touch users?".to_string(),
+            MessageType::Question,
+        );
+        let broadcast = Message::new(
+            "backend-1",
+            "all",
+            "Deploying backend-1 now".to_string(),
+            MessageType::Info,
+        );
+
+        assert_eq!(
+            cache
+                .import_messages(Some("frontend"), std::slice::from_ref(&migration))
+                .unwrap(),
+            1
+        );
+        cache
+            .import_messages(Some("backend-1"), std::slice::from_ref(&question))
+            .unwrap();
+        cache
+            .import_messages(Some("broadcast"), std::slice::from_ref(&broadcast))
+            .unwrap();
+        // Copies of a message in other directories aren't imported twice
+        assert_eq!(
+            cache
+                .import_messages(None, &[migration.clone(), question.clone()])
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(cache.get_messages().unwrap().len(), 3);
+        let inbox: Vec<String> = cache
+            .get_inbox_messages("frontend")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(inbox, vec![migration.id.clone(), broadcast.id.clone()]);
+        assert_eq!(
+            cache.get_message(&question.id).unwrap().unwrap().content,
+            question.content
+        );
+
+        let search = |search: MessageSearch| -> Vec<String> {
+            cache
+                .search_messages(&search)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.id)
+                .collect()
+        };
+        assert_eq!(
+            search(MessageSearch {
+                text: Some("migration".to_string()),
+                ..Default::default()
+            }),
+            vec![migration.id.clone(), question.id.clone()]
+        );
+        assert_eq!(
+            search(MessageSearch {
+                text: Some("migration".to_string()),
+                from: Some("backend-1".to_string()),
+                ..Default::default()
+            }),
+            vec![migration.id.clone()]
+        );
+        assert_eq!(
+            search(MessageSearch {
+                from: Some("backend-1".to_string()),
+                since: Some(Utc::now() - chrono::Duration::hours(2)),
+                ..Default::default()
+            }),
+            vec![broadcast.id.clone()]
+        );
+        assert_eq!(
+            search(MessageSearch {
+                text: Some("backend-1".to_string()),
+                ..Default::default()
+            }),
+            vec![broadcast.id.clone()]
+        );
+    }
 }
@@ -10,6 +10,7 @@ mod state_config;
 mod types;
 
 pub use backend::GitBackedState;
+pub use cache::{MessageSearch, StateCache};
 pub use state_config::SharedStateConfig;
 pub use types::{
     MemoryEntry, MemoryType, StepStatus, Task, TaskIdGenerator, TaskStatus, Workflow,
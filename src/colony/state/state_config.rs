@@ -1,10 +1,11 @@
 //! Configuration for git-backed state
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 /// State backend type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 #[derive(Default)]
 pub enum StateBackend {
@@ -17,7 +18,7 @@ pub enum StateBackend {
 
 
 /// State storage location
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 #[derive(Default)]
 pub enum StateLocation {
@@ -30,7 +31,7 @@ pub enum StateLocation {
 
 
 /// State file schema configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StateSchema {
     /// Schema name (e.g., "tasks", "workflows")
     pub name: String,
@@ -46,7 +47,7 @@ fn default_cache() -> bool {
 }
 
 /// Shared state configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SharedStateConfig {
     /// Backend type
     #[serde(default)]
@@ -145,6 +146,11 @@ fn default_schemas() -> Vec<StateSchema> {
             file: "workflows.jsonl".to_string(),
             cache: true,
         },
+        StateSchema {
+            name: "memory".to_string(),
+            file: "memory.jsonl".to_string(),
+            cache: false,
+        },
     ]
 }
 
@@ -204,6 +210,12 @@ impl SharedStateConfig {
     pub fn get_schema(&self, name: &str) -> Option<&StateSchema> {
         self.schemas.iter().find(|s| s.name == name)
     }
+
+    /// Get the full path to a schema's JSONL file, if the schema is configured
+    pub fn schema_file_path(&self, repo_root: &PathBuf, name: &str) -> Option<PathBuf> {
+        self.get_schema(name)
+            .map(|schema| self.state_dir_path(repo_root).join(&schema.file))
+    }
 }
 
 #[cfg(test)]
@@ -11,7 +11,7 @@ use colored::Colorize;
 
 /// Initialize state backend from config
 async fn init_state() -> ColonyResult<GitBackedState> {
-    let config_path = std::path::Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(ColonyError::Colony(
@@ -1,13 +1,12 @@
 use colored::Colorize;
-use std::path::Path;
 
-use crate::colony::{AgentStatus, ColonyConfig, ColonyController};
+use crate::colony::{messaging, AgentStatus, ColonyConfig, ColonyController};
 use crate::error::ColonyResult;
 use crate::utils;
 
 /// Show status of all agents
 pub async fn run() -> ColonyResult<()> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
@@ -34,10 +33,10 @@ pub async fn run() -> ColonyResult<()> {
 
     // Print header
     println!(
-        "{:<15} {:<20} {:<12} {:<10}",
-        "AGENT ID", "ROLE", "STATUS", "PID"
+        "{:<15} {:<20} {:<12} {:<10} {:<28} {:<6}",
+        "AGENT ID", "ROLE", "STATUS", "PID", "MODEL", "UNREAD"
     );
-    println!("{}", "─".repeat(70));
+    println!("{}", "─".repeat(105));
 
     // Print each agent
     for agent in controller.agents().values() {
@@ -46,13 +45,19 @@ pub async fn run() -> ColonyResult<()> {
             .pid
             .map(|p| p.to_string())
             .unwrap_or_else(|| "-".to_string());
+        let model_str = agent.active_model().unwrap_or_else(|| "-".to_string());
+        let unread = messaging::load_unread_messages(controller.colony_root(), agent.id())
+            .map(|messages| messages.len())
+            .unwrap_or(0);
 
         println!(
-            "{:<15} {:<20} {:<12} {:<10}",
+            "{:<15} {:<20} {:<12} {:<10} {:<28} {:<6}",
             agent.id(),
             truncate(&agent.config.role, 20),
             status_str,
-            pid_str
+            pid_str,
+            model_str,
+            unread
         );
     }
 
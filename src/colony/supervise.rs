@@ -0,0 +1,197 @@
+//! Agent supervisor
+//!
+//! Polls each running agent's tmux pane and relaunches Claude Code in the same
+//! pane when it exits. Restarts back off exponentially and stop after a
+//! per-agent limit, at which point the agent is marked as failed. Every
+//! restart is recorded in the agent state file.
+
+use chrono::Utc;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::colony::multiplexer::PaneState;
+use crate::colony::{AgentStatus, ColonyConfig, ColonyController};
+use crate::error::ColonyResult;
+use crate::utils;
+
+/// tmux window the supervisor runs in when started by `colony start --supervise`
+pub const SUPERVISOR_WINDOW: &str = "supervisor";
+
+/// Default interval between pane checks, in seconds
+pub const DEFAULT_INTERVAL_SECS: u64 = 10;
+
+/// Default number of restarts allowed per agent
+pub const DEFAULT_MAX_RESTARTS: u32 = 5;
+
+/// Delay before the first restart; doubled for each restart after that
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Upper bound on the delay between restarts
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Time a freshly launched agent gets before its pane is checked again
+const LAUNCH_GRACE: Duration = Duration::from_secs(30);
+
+/// Per-agent supervision state
+#[derive(Default)]
+struct Watch {
+    next_attempt: Option<Instant>,
+    launched_at: Option<Instant>,
+}
+
+/// Run the supervisor until interrupted
+pub async fn run(interval_secs: u64, max_restarts: u32) -> ColonyResult<()> {
+    let config_path = &crate::colony::config::config_path();
+
+    if !config_path.exists() {
+        return Err(crate::error::ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+
+    let config = ColonyConfig::load(config_path)?;
+    let mut controller = ColonyController::new(config)?;
+    controller.initialize_agents()?;
+
+    let interval = Duration::from_secs(interval_secs.max(1));
+    let mut watches: HashMap<String, Watch> = HashMap::new();
+
+    utils::header("Colony Supervisor");
+    println!(
+        "Checking agents every {}s (max {} restarts per agent)",
+        interval.as_secs(),
+        max_restarts
+    );
+    println!();
+
+    loop {
+        // Reload state so agents stopped or restarted elsewhere are respected
+        let _ = controller.load_state();
+
+        let agent_ids: Vec<String> = controller.agents().keys().cloned().collect();
+        for agent_id in agent_ids {
+            if let Err(e) = supervise_agent(
+                &mut controller,
+                &agent_id,
+                watches.entry(agent_id.clone()).or_default(),
+                max_restarts,
+            )
+            .await
+            {
+                utils::warning(&format!("Failed to supervise '{}': {}", agent_id, e));
+            }
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Check one agent and relaunch it if its pane died and it is due for a restart
+async fn supervise_agent(
+    controller: &mut ColonyController,
+    agent_id: &str,
+    watch: &mut Watch,
+    max_restarts: u32,
+) -> ColonyResult<()> {
+    let Some(agent) = controller.get_agent(agent_id) else {
+        return Ok(());
+    };
+
+    // Only agents the colony believes are running are supervised
+    if agent.status != AgentStatus::Running {
+        return Ok(());
+    }
+    let Some(pane_id) = agent.pane_id.clone() else {
+        return Ok(());
+    };
+
+    // Give a fresh launch time to get Claude Code up before judging it
+    if watch
+        .launched_at
+        .is_some_and(|t| t.elapsed() < LAUNCH_GRACE)
+    {
+        return Ok(());
+    }
+
+    if controller.multiplexer().pane_state(&pane_id) == PaneState::Running {
+        watch.next_attempt = None;
+        return Ok(());
+    }
+
+    let restarts = agent.restarts;
+    if restarts >= max_restarts {
+        utils::warning(&format!(
+            "Agent '{}' exited and reached its restart limit ({}), marking as failed",
+            agent_id, max_restarts
+        ));
+        if let Some(agent) = controller.get_agent_mut(agent_id) {
+            agent.set_status(AgentStatus::Failed);
+        }
+        controller.save_state()?;
+        return Ok(());
+    }
+
+    let Some(next_attempt) = watch.next_attempt else {
+        let delay = backoff_delay(restarts);
+        utils::warning(&format!(
+            "Agent '{}' exited, restarting in {}s (restart {}/{})",
+            agent_id,
+            delay.as_secs(),
+            restarts + 1,
+            max_restarts
+        ));
+        watch.next_attempt = Some(Instant::now() + delay);
+        return Ok(());
+    };
+
+    if Instant::now() < next_attempt {
+        return Ok(());
+    }
+
+    relaunch_agent(controller, agent_id).await?;
+    watch.next_attempt = None;
+    watch.launched_at = Some(Instant::now());
+
+    Ok(())
+}
+
+/// Relaunch an agent's Claude Code session in its pane (or a new pane if it was closed)
+async fn relaunch_agent(controller: &mut ColonyController, agent_id: &str) -> ColonyResult<()> {
+    let pane_id = controller.restart_agent(agent_id).await?;
+
+    let agent = controller.get_agent_mut(agent_id).ok_or_else(|| {
+        crate::error::ColonyError::Colony(format!("Agent '{}' not found", agent_id))
+    })?;
+    agent.restarts += 1;
+    agent.last_restart = Some(Utc::now().to_rfc3339());
+
+    utils::success(&format!(
+        "Restarted agent '{}' in pane {} (restart #{})",
+        agent_id, pane_id, agent.restarts
+    ));
+
+    controller.save_state()?;
+
+    Ok(())
+}
+
+/// Delay before the next restart of an agent that has already been restarted `restarts` times
+fn backoff_delay(restarts: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(restarts))
+        .min(MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0), Duration::from_secs(5));
+        assert_eq!(backoff_delay(1), Duration::from_secs(10));
+        assert_eq!(backoff_delay(3), Duration::from_secs(40));
+        assert_eq!(backoff_delay(10), MAX_BACKOFF);
+        assert_eq!(backoff_delay(u32::MAX), MAX_BACKOFF);
+    }
+}
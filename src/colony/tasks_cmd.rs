@@ -1,5 +1,3 @@
-use std::path::Path;
-
 use crate::colony::config::ColonyConfig;
 use crate::colony::controller::ColonyController;
 use crate::colony::tasks::board::{
@@ -13,7 +11,7 @@ use crate::utils;
 
 /// Helper function to load task queue (reduces boilerplate)
 fn load_task_queue() -> ColonyResult<TaskQueue> {
-    let config_path = Path::new("colony.yml");
+    let config_path = &crate::colony::config::config_path();
 
     if !config_path.exists() {
         return Err(crate::error::ColonyError::Colony(
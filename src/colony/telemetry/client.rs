@@ -215,7 +215,7 @@ impl TelemetryClient {
         config_guard.get_or_create_anonymous_id();
 
         // Load full config, update telemetry section, and save
-        let mut colony_config = crate::colony::config::ColonyConfig::load(config_path)?;
+        let mut colony_config = crate::colony::config::ColonyConfig::load_file(config_path)?;
         colony_config.telemetry = config_guard.clone();
         colony_config.save(config_path)?;
 
@@ -228,7 +228,7 @@ impl TelemetryClient {
         config_guard.enabled = false;
 
         // Load full config, update telemetry section, and save
-        let mut colony_config = crate::colony::config::ColonyConfig::load(config_path)?;
+        let mut colony_config = crate::colony::config::ColonyConfig::load_file(config_path)?;
         colony_config.telemetry = config_guard.clone();
         colony_config.save(config_path)?;
 
@@ -7,6 +7,11 @@ author: Colony Community
 description: "Code quality and best practices review agent"
 license: MIT
 
+parameters:
+  - name: language
+    description: Language whose idioms the review follows
+    default: the project's languages
+
 agent:
   role: Code Reviewer
   focus: Review code for quality, best practices, and potential issues
@@ -21,6 +26,7 @@ agent:
     - Security vulnerabilities
     - Documentation completeness
 
+    Hold the code to the idioms and conventions of {{language}}.
     Provide constructive feedback with specific suggestions for improvement.
 
 requirements:
@@ -36,6 +42,12 @@ author: Colony Community
 description: "OWASP Top 10 focused security auditing agent"
 license: MIT
 
+parameters:
+  - name: severity_threshold
+    description: Lowest severity worth reporting
+    choices: [low, medium, high, critical]
+    default: low
+
 agent:
   role: Security Auditor
   focus: Identify and document security vulnerabilities
@@ -52,6 +64,7 @@ agent:
     - Security misconfigurations
 
     Document findings with severity ratings and remediation steps.
+    Report findings of {{severity_threshold}} severity and above.
 
   behavior:
     initiative_level: high
@@ -71,6 +84,15 @@ author: Colony Community
 description: "Automated testing and QA specialist"
 license: MIT
 
+parameters:
+  - name: test_command
+    description: Command that runs the test suite
+    default: the project's test command
+  - name: coverage_target
+    type: integer
+    description: Line coverage to aim for, in percent
+    default: 80
+
 agent:
   role: Test Engineer
   focus: Create and maintain comprehensive test coverage
@@ -85,6 +107,8 @@ agent:
     - Maintaining test infrastructure
     - Ensuring tests are maintainable and reliable
 
+    Aim for at least {{coverage_target}}% line coverage, and run the tests with
+    {{test_command}} before reporting work as done.
     Focus on testing best practices and preventing regressions.
 
 requirements:
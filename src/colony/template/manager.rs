@@ -1,3 +1,4 @@
+use super::source::read_source;
 use super::types::{AgentTemplate, TemplateMetadata};
 use crate::error::{ColonyError, ColonyResult};
 use std::collections::HashMap;
@@ -103,6 +104,7 @@ impl TemplateManager {
             template,
             path: template_dir.to_path_buf(),
             is_builtin,
+            source: read_source(template_dir)?,
         })
     }
 
@@ -1,7 +1,14 @@
 pub mod builtin;
 pub mod manager;
+pub mod params;
+pub mod resolve;
+pub mod source;
 pub mod types;
 
 pub use builtin::get_builtin_templates;
 pub use manager::TemplateManager;
+pub use resolve::{
+    check_requirements, find_template, resolve_agent_templates, unresolve_agent_templates,
+    TEMPLATES_DIR,
+};
 pub use types::AgentTemplate;
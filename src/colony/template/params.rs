@@ -0,0 +1,205 @@
+//! Typed template parameters
+//!
+//! A template declares parameters that are substituted for `{{name}}` in its
+//! agent configuration, when it is installed or when an agent references it.
+//! Values are checked against the parameter's type and choices first. Other
+//! `{{...}}` placeholders, like prompt template variables, are left alone.
+
+use std::collections::HashMap;
+
+use serde_yaml::Value;
+
+use super::types::{AgentTemplate, ParameterType, TemplateParameter};
+use crate::error::{ColonyError, ColonyResult};
+
+impl TemplateParameter {
+    /// Check a value against the parameter's type and choices
+    pub fn check(&self, value: &str) -> ColonyResult<()> {
+        let valid = match self.param_type {
+            ParameterType::String => true,
+            ParameterType::Integer => value.parse::<i64>().is_ok(),
+            ParameterType::Number => value.parse::<f64>().is_ok(),
+            ParameterType::Boolean => matches!(value, "true" | "false"),
+        };
+        if !valid {
+            return Err(ColonyError::Other(format!(
+                "'{}' must be a{} {}, not '{}'",
+                self.name,
+                if self.param_type == ParameterType::Integer {
+                    "n"
+                } else {
+                    ""
+                },
+                self.param_type,
+                value
+            )));
+        }
+
+        if let Some(choices) = &self.choices {
+            if !choices.iter().any(|c| c == value) {
+                return Err(ColonyError::Other(format!(
+                    "'{}' must be one of {}, not '{}'",
+                    self.name,
+                    choices.join(", "),
+                    value
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The default value as it is substituted
+    pub fn default_value(&self) -> Option<String> {
+        self.default.as_ref().and_then(value_to_string)
+    }
+}
+
+/// A parameter value from YAML as it is substituted; `None` for anything but a
+/// string, number or boolean
+pub fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Check the values given for a template's parameters and fill in defaults
+pub fn resolve_values(
+    template: &AgentTemplate,
+    given: &HashMap<String, String>,
+) -> ColonyResult<HashMap<String, String>> {
+    if let Some(unknown) = given
+        .keys()
+        .find(|name| !template.parameters.iter().any(|p| &p.name == *name))
+    {
+        return Err(ColonyError::Other(format!(
+            "template '{}' has no parameter '{}'",
+            template.name, unknown
+        )));
+    }
+
+    let mut values = HashMap::new();
+    for parameter in &template.parameters {
+        let value = given
+            .get(&parameter.name)
+            .cloned()
+            .or_else(|| parameter.default_value())
+            .ok_or_else(|| {
+                ColonyError::Other(format!(
+                    "template '{}' needs a value for '{}'",
+                    template.name, parameter.name
+                ))
+            })?;
+        parameter.check(&value)?;
+        values.insert(parameter.name.clone(), value);
+    }
+    Ok(values)
+}
+
+/// Substitute parameter values into a template's agent configuration. The
+/// template has no parameters left afterwards.
+pub fn apply(template: &mut AgentTemplate, values: &HashMap<String, String>) -> ColonyResult<()> {
+    let mut agent = serde_yaml::to_value(&template.agent)?;
+    substitute(&mut agent, values);
+    template.agent = serde_yaml::from_value(agent)?;
+    template.parameters.clear();
+    Ok(())
+}
+
+/// Replace `{{name}}` with its value in every string
+pub fn substitute(value: &mut Value, values: &HashMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            for (name, replacement) in values {
+                for placeholder in [format!("{{{{{}}}}}", name), format!("{{{{ {} }}}}", name)] {
+                    if s.contains(&placeholder) {
+                        *s = s.replace(&placeholder, replacement);
+                    }
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(|item| substitute(item, values)),
+        Value::Mapping(map) => map.values_mut().for_each(|item| substitute(item, values)),
+        Value::Tagged(tagged) => substitute(&mut tagged.value, values),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = r#"
+name: linter
+version: 1.0.0
+parameters:
+  - name: language
+    description: Language to lint
+  - name: max_warnings
+    type: integer
+    default: 10
+  - name: severity
+    choices: [low, high]
+    default: low
+agent:
+  role: "{{language}} Linter"
+  focus: "Keep {{language}} warnings under {{max_warnings}}"
+  model: claude-sonnet-4-20250514
+  instructions: "Report {{severity}} and above to {{agent.id}}'s inbox"
+  mcp_servers:
+    lint:
+      command: "{{language}}-lint"
+"#;
+
+    fn given(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_parameters() {
+        let mut template: AgentTemplate = serde_yaml::from_str(TEMPLATE).unwrap();
+        let values = resolve_values(&template, &given(&[("language", "rust")])).unwrap();
+        assert_eq!(values["max_warnings"], "10");
+
+        apply(&mut template, &values).unwrap();
+        assert!(template.parameters.is_empty());
+        assert_eq!(template.agent.role, "rust Linter");
+        assert_eq!(template.agent.focus, "Keep rust warnings under 10");
+        assert_eq!(
+            template.agent.instructions.as_deref(),
+            Some("Report low and above to {{agent.id}}'s inbox")
+        );
+        assert_eq!(
+            template.agent.mcp_servers.as_ref().unwrap()["lint"]["command"],
+            "rust-lint"
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let template: AgentTemplate = serde_yaml::from_str(TEMPLATE).unwrap();
+        for values in [
+            given(&[]),
+            given(&[("language", "rust"), ("max_warnings", "ten")]),
+            given(&[("language", "rust"), ("severity", "medium")]),
+            given(&[("language", "rust"), ("colour", "blue")]),
+        ] {
+            assert!(
+                resolve_values(&template, &values).is_err(),
+                "accepted {:?}",
+                values
+            );
+        }
+
+        let flag: TemplateParameter =
+            serde_yaml::from_str("{name: strict, type: boolean}").unwrap();
+        flag.check("true").unwrap();
+        assert!(flag.check("yes").is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Resolving agents' `template:` references in colony.yml
+//!
+//! An agent can name a template as `name` or `name@version`. Templates installed
+//! in `.colony/templates/` take precedence over the built-ins. The template's
+//! role, focus, model, startup_prompt, instructions and mcp_servers fill in any
+//! of those fields the agent doesn't set itself, after the agent's
+//! `template_params` are substituted into them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+use super::types::AgentTemplate;
+use super::{get_builtin_templates, params, TemplateManager};
+use crate::colony::agent_skills;
+use crate::colony::config::{AgentConfig, ColonyConfig};
+use crate::error::{ColonyError, ColonyResult};
+
+/// Directory under the colony root that user templates are installed in
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Agent fields a template provides
+const TEMPLATE_FIELDS: &[&str] = &[
+    "role",
+    "focus",
+    "model",
+    "startup_prompt",
+    "instructions",
+    "mcp_servers",
+    "behavior",
+];
+
+/// Find the template an agent refers to, checking its version if one is given
+///
+/// A version matches when it is a prefix of the template's, so `1.0` matches
+/// `1.0.0` and `1.0.3` but not `1.1.0`.
+pub fn find_template(reference: &str, templates_dir: &Path) -> ColonyResult<AgentTemplate> {
+    let (name, version) = match reference.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (reference, None),
+    };
+
+    let mut manager = TemplateManager::new(templates_dir.to_path_buf(), None);
+    manager.discover_templates()?;
+
+    let template = match manager.get_template(name) {
+        Some(metadata) => metadata.template.clone(),
+        None => {
+            let (_, yaml) = get_builtin_templates()
+                .into_iter()
+                .find(|(builtin, _)| *builtin == name)
+                .ok_or_else(|| {
+                    ColonyError::Other(format!(
+                        "unknown template '{}'. Install it in {} or use a built-in: {}",
+                        name,
+                        templates_dir.display(),
+                        get_builtin_templates()
+                            .iter()
+                            .map(|(builtin, _)| *builtin)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+            serde_yaml::from_str(yaml)?
+        }
+    };
+
+    if let Some(version) = version {
+        if !version_matches(version, &template.version) {
+            return Err(ColonyError::Other(format!(
+                "template '{}' is version {}, not {}",
+                name, template.version, version
+            )));
+        }
+    }
+
+    Ok(template)
+}
+
+fn version_matches(requested: &str, actual: &str) -> bool {
+    let requested: Vec<&str> = requested.split('.').collect();
+    let actual: Vec<&str> = actual.split('.').collect();
+    requested.len() <= actual.len() && requested.iter().zip(&actual).all(|(r, a)| r == a)
+}
+
+/// Fill in the fields of agents in a parsed colony.yml from their templates
+///
+/// This works on the raw YAML so that a field is only taken from the template
+/// when the agent leaves it out, even for fields like `model` that have a default.
+pub fn resolve_agent_templates(config: &mut Value, templates_dir: &Path) -> ColonyResult<()> {
+    let Some(agents) = config.get_mut("agents").and_then(Value::as_sequence_mut) else {
+        return Ok(());
+    };
+
+    for agent in agents.iter_mut().filter_map(Value::as_mapping_mut) {
+        let Some(reference) = agent.get("template").and_then(Value::as_str) else {
+            continue;
+        };
+        let reference = reference.to_string();
+        let agent_id = agent
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        let template = find_template(&reference, templates_dir)
+            .and_then(|mut template| {
+                let values = params::resolve_values(&template, &template_params(agent)?)?;
+                params::apply(&mut template, &values)?;
+                Ok(template)
+            })
+            .map_err(|e| ColonyError::InvalidConfig(format!("agent '{}': {}", agent_id, e)))?;
+
+        let Value::Mapping(fields) = serde_yaml::to_value(&template.agent)? else {
+            continue;
+        };
+        for field in TEMPLATE_FIELDS {
+            if agent.contains_key(*field) {
+                continue;
+            }
+            if let Some(value) = fields.get(*field) {
+                agent.insert(Value::from(*field), value.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Undo [`resolve_agent_templates`] on configuration about to be saved over
+/// `original`, the file as the user wrote it
+///
+/// A template field an agent didn't set in `original` is dropped again, unless
+/// it was changed from what the template provides.
+pub fn unresolve_agent_templates(
+    config: &mut Value,
+    original: &Value,
+    templates_dir: &Path,
+) -> ColonyResult<()> {
+    let mut resolved = original.clone();
+    resolve_agent_templates(&mut resolved, templates_dir)?;
+
+    let Some(agents) = config.get_mut("agents").and_then(Value::as_sequence_mut) else {
+        return Ok(());
+    };
+
+    for agent in agents.iter_mut().filter_map(Value::as_mapping_mut) {
+        if !agent.contains_key("template") {
+            continue;
+        }
+        let agent_id = agent.get("id").and_then(Value::as_str).unwrap_or_default();
+        let (Some(written), Some(resolved)) = (
+            find_agent(original, agent_id),
+            find_agent(&resolved, agent_id),
+        ) else {
+            continue;
+        };
+
+        for field in TEMPLATE_FIELDS {
+            if written.contains_key(*field) {
+                continue;
+            }
+            let value = agent.get(*field);
+            if value == resolved.get(*field) || value == Some(&Value::Null) {
+                agent.remove(*field);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn find_agent<'a>(config: &'a Value, agent_id: &str) -> Option<&'a Mapping> {
+    config
+        .get("agents")?
+        .as_sequence()?
+        .iter()
+        .filter_map(Value::as_mapping)
+        .find(|agent| agent.get("id").and_then(Value::as_str) == Some(agent_id))
+}
+
+/// An agent's `template_params`, as the strings they are substituted as
+fn template_params(agent: &Mapping) -> ColonyResult<HashMap<String, String>> {
+    let Some(given) = agent.get("template_params").and_then(Value::as_mapping) else {
+        return Ok(HashMap::new());
+    };
+
+    let mut values = HashMap::new();
+    for (name, value) in given {
+        let name = name.as_str().unwrap_or_default();
+        let value = params::value_to_string(value).ok_or_else(|| {
+            ColonyError::Other(format!(
+                "template parameter '{}' must be a string, number or boolean",
+                name
+            ))
+        })?;
+        values.insert(name.to_string(), value);
+    }
+    Ok(values)
+}
+
+/// Check that the colony has what a template says it requires: the repository
+/// type, MCP servers configured on the agent, and installed skills
+pub fn check_requirements(
+    template: &AgentTemplate,
+    agent: &AgentConfig,
+    config: &ColonyConfig,
+) -> ColonyResult<()> {
+    let Some(requirements) = &template.requirements else {
+        return Ok(());
+    };
+
+    if let Some(repo_types) = &requirements.repo_types {
+        let repo_type = config
+            .repository
+            .as_ref()
+            .map(|r| r.repo_type.clone())
+            .unwrap_or_default();
+        let repo_type = serde_yaml::to_value(&repo_type)?;
+        let repo_type = repo_type.as_str().unwrap_or_default();
+        if !repo_types.iter().any(|t| t == repo_type) {
+            return Err(ColonyError::Other(format!(
+                "it needs a {} repository, but repository.repo_type is '{}'",
+                repo_types.join(" or "),
+                repo_type
+            )));
+        }
+    }
+
+    for server in requirements.mcp_servers.iter().flatten() {
+        let configured = agent
+            .mcp_servers
+            .as_ref()
+            .is_some_and(|servers| servers.contains_key(server));
+        if !configured {
+            return Err(ColonyError::Other(format!(
+                "it needs the '{}' MCP server in the agent's mcp_servers",
+                server
+            )));
+        }
+    }
+
+    for skill in requirements.skills.iter().flatten() {
+        if !skill_installed(skill) {
+            return Err(ColonyError::Other(format!(
+                "it needs the '{}' skill, which isn't installed in .claude/skills/ or ~/.claude/skills/",
+                skill
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a skill is one colony gives every agent, or is installed in the
+/// repository's or the user's `.claude/skills/`
+fn skill_installed(name: &str) -> bool {
+    if agent_skills::SKILL_NAMES.contains(&name) {
+        return true;
+    }
+
+    let mut skills_dirs = vec![PathBuf::from(".claude/skills")];
+    if let Some(home) = dirs::home_dir() {
+        skills_dirs.push(home.join(".claude/skills"));
+    }
+    skills_dirs
+        .iter()
+        .any(|dir| dir.join(format!("{}.md", name)).is_file() || dir.join(name).is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colony::config::BehaviorLevel;
+    use tempfile::tempdir;
+
+    fn resolve(yaml: &str, templates_dir: &Path) -> ColonyResult<ColonyConfig> {
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        resolve_agent_templates(&mut value, templates_dir)?;
+        Ok(serde_yaml::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_resolve_agent_templates() {
+        let dir = tempdir().unwrap();
+        let config = resolve(
+            r#"
+agents:
+  - id: security
+    template: security-auditor@1.0
+    focus: OWASP Top 10 only
+  - id: backend
+    role: Backend
+    focus: API
+"#,
+            dir.path(),
+        )
+        .unwrap();
+
+        let security = &config.agents[0];
+        assert_eq!(security.role, "Security Auditor");
+        assert_eq!(security.focus, "OWASP Top 10 only");
+        assert_eq!(security.model, "claude-opus-4-20250514");
+        assert!(security.instructions.as_ref().unwrap().contains("OWASP"));
+        assert_eq!(
+            security.behavior.as_ref().unwrap().initiative_level,
+            Some(BehaviorLevel::High)
+        );
+        assert_eq!(security.template.as_deref(), Some("security-auditor@1.0"));
+        assert!(config.agents[1].template.is_none());
+
+        for reference in ["security-auditor@2", "security-auditor@1.0.0.1", "nope"] {
+            let yaml = format!("agents:\n  - id: a\n    template: {}\n", reference);
+            assert!(
+                resolve(&yaml, dir.path()).is_err(),
+                "accepted {}",
+                reference
+            );
+        }
+
+        // Installed templates shadow the built-ins
+        let installed = dir.path().join("code-reviewer");
+        std::fs::create_dir_all(&installed).unwrap();
+        std::fs::write(
+            installed.join("template.yaml"),
+            r#"
+name: code-reviewer
+version: 2.1.0
+parameters:
+  - name: max_comments
+    type: integer
+    default: 5
+agent:
+  role: Strict Reviewer
+  focus: "At most {{max_comments}} comments per review"
+  model: claude-sonnet-4-20250514
+  mcp_servers:
+    github:
+      command: npx
+      args: [-y, "@modelcontextprotocol/server-github"]
+"#,
+        )
+        .unwrap();
+        let config = resolve(
+            "agents:\n  - id: reviewer\n    template: code-reviewer@2\n",
+            dir.path(),
+        )
+        .unwrap();
+        let reviewer = &config.agents[0];
+        assert_eq!(reviewer.role, "Strict Reviewer");
+        assert_eq!(reviewer.focus, "At most 5 comments per review");
+        assert_eq!(
+            reviewer.mcp_servers.as_ref().unwrap()["github"].command,
+            "npx"
+        );
+
+        let yaml = "agents:\n  - id: reviewer\n    template: code-reviewer\n    template_params: {max_comments: 12}\n";
+        assert_eq!(
+            resolve(yaml, dir.path()).unwrap().agents[0].focus,
+            "At most 12 comments per review"
+        );
+        assert!(resolve(&yaml.replace("12", "many"), dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_check_requirements() {
+        let mut template: AgentTemplate =
+            serde_yaml::from_str(get_builtin_templates()[0].1).unwrap();
+        let mut config: ColonyConfig =
+            serde_yaml::from_str("agents:\n  - {id: reviewer, role: Reviewer, focus: Code}\n")
+                .unwrap();
+        let agent = config.agents[0].clone();
+
+        // No repository section means a source repository
+        check_requirements(&template, &agent, &config).unwrap();
+
+        config.repository = serde_yaml::from_str("repo_type: memory").unwrap();
+        assert!(check_requirements(&template, &agent, &config).is_err());
+        config.repository = None;
+
+        template.requirements.as_mut().unwrap().mcp_servers = Some(vec!["github".to_string()]);
+        assert!(check_requirements(&template, &agent, &config).is_err());
+        template.requirements.as_mut().unwrap().mcp_servers = None;
+
+        template.requirements.as_mut().unwrap().skills = Some(vec!["git-workflow".to_string()]);
+        check_requirements(&template, &agent, &config).unwrap();
+        template.requirements.as_mut().unwrap().skills = Some(vec!["no-such-skill".to_string()]);
+        assert!(check_requirements(&template, &agent, &config).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Template sources: built-ins, git repositories, tarballs and agents
+//!
+//! An installed template keeps a `source.yaml` next to its `template.yaml`
+//! recording where it came from and which version was installed, so that
+//! `colony template update` can fetch it again and install newer versions.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::Utc;
+use semver::Version;
+
+use super::builtin::get_builtin_templates;
+use super::types::{AgentTemplate, AgentTemplateConfig, SourceType, TemplateSource};
+use crate::colony::blueprint::{clone_shallow, is_git_url};
+use crate::colony::config::AgentConfig;
+use crate::error::{ColonyError, ColonyResult};
+
+/// File an installed template records its source in
+pub const SOURCE_FILE: &str = "source.yaml";
+
+/// File a template directory, repository or tarball keeps its template in
+const MANIFEST: &str = "template.yaml";
+
+/// Fetch a template from a built-in name, a git URL or a tarball path
+pub fn fetch(source: &str) -> ColonyResult<(AgentTemplate, TemplateSource)> {
+    let (template, source_type, location) = if let Some((_, yaml)) = get_builtin_templates()
+        .into_iter()
+        .find(|(n, _)| *n == source)
+    {
+        (
+            serde_yaml::from_str(yaml)?,
+            SourceType::Builtin,
+            source.to_string(),
+        )
+    } else if is_git_url(source) {
+        (load_git(source)?, SourceType::Git, source.to_string())
+    } else if Path::new(source).is_file() {
+        // Record an absolute path so updates work from any directory
+        let path = fs::canonicalize(source)?;
+        (
+            load_tarball(&path)?,
+            SourceType::Tarball,
+            path.display().to_string(),
+        )
+    } else {
+        return Err(ColonyError::Colony(format!(
+            "Template '{}' not found. Use a built-in template, a git URL or a tarball path",
+            source
+        )));
+    };
+
+    check_name(&template.name)?;
+    let version = parse_version(&template)?;
+    Ok((
+        template,
+        TemplateSource {
+            source_type,
+            location,
+            version: version.to_string(),
+            installed_at: Utc::now(),
+            parameters: Default::default(),
+        },
+    ))
+}
+
+/// Check that a template name can be used as its directory name in
+/// `.colony/templates/`: letters, digits, `-` and `_` only
+pub fn check_name(name: &str) -> ColonyResult<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ColonyError::Colony(format!(
+            "Invalid template name '{}': use only letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// A template's version, which must be a semantic version like `1.2.0`
+pub fn parse_version(template: &AgentTemplate) -> ColonyResult<Version> {
+    Version::parse(&template.version).map_err(|e| {
+        ColonyError::Colony(format!(
+            "Template '{}' has version '{}', which is not a semantic version: {}",
+            template.name, template.version, e
+        ))
+    })
+}
+
+/// Build a template from an agent's configuration
+pub fn from_agent(
+    name: &str,
+    version: &str,
+    description: Option<String>,
+    agent: &AgentConfig,
+) -> ColonyResult<AgentTemplate> {
+    let mcp_servers = match &agent.mcp_servers {
+        Some(servers) => Some(
+            servers
+                .iter()
+                .map(|(name, server)| Ok((name.clone(), serde_json::to_value(server)?)))
+                .collect::<ColonyResult<_>>()?,
+        ),
+        None => None,
+    };
+
+    let template = AgentTemplate {
+        name: name.to_string(),
+        version: version.to_string(),
+        author: None,
+        description,
+        license: None,
+        parameters: Vec::new(),
+        agent: AgentTemplateConfig {
+            role: agent.role.clone(),
+            focus: agent.focus.clone(),
+            model: agent.model.clone(),
+            startup_prompt: agent.startup_prompt.clone(),
+            instructions: agent.instructions.clone(),
+            mcp_servers,
+            behavior: agent.behavior.clone(),
+        },
+        requirements: None,
+    };
+    parse_version(&template)?;
+    Ok(template)
+}
+
+/// Write a template and its source to a template directory
+pub fn write_installed(
+    template_dir: &Path,
+    template: &AgentTemplate,
+    source: &TemplateSource,
+) -> ColonyResult<()> {
+    fs::create_dir_all(template_dir)
+        .map_err(|e| ColonyError::Colony(format!("Failed to create template directory: {}", e)))?;
+    fs::write(
+        template_dir.join(MANIFEST),
+        serde_yaml::to_string(template)?,
+    )
+    .map_err(|e| ColonyError::Colony(format!("Failed to write template file: {}", e)))?;
+    fs::write(
+        template_dir.join(SOURCE_FILE),
+        serde_yaml::to_string(source)?,
+    )
+    .map_err(|e| ColonyError::Colony(format!("Failed to write template source: {}", e)))?;
+    Ok(())
+}
+
+/// The recorded source of an installed template, if it has one
+pub fn read_source(template_dir: &Path) -> ColonyResult<Option<TemplateSource>> {
+    let path = template_dir.join(SOURCE_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| ColonyError::Colony(format!("Failed to read template source: {}", e)))?;
+    serde_yaml::from_str(&content)
+        .map(Some)
+        .map_err(|e| ColonyError::Colony(format!("Failed to parse template source: {}", e)))
+}
+
+/// Shallow-clone a repository and load the template at its root
+fn load_git(url: &str) -> ColonyResult<AgentTemplate> {
+    let checkout = clone_shallow(url)?;
+    let template = load_dir(&checkout);
+    let _ = fs::remove_dir_all(&checkout);
+    template
+}
+
+/// Extract a tarball (optionally compressed) and load the template in it
+fn load_tarball(path: &Path) -> ColonyResult<AgentTemplate> {
+    let extracted = scratch_dir();
+    fs::create_dir_all(&extracted)?;
+
+    let output = Command::new("tar")
+        .arg("-xf")
+        .arg(path)
+        .arg("-C")
+        .arg(&extracted)
+        .output()?;
+    let template = if output.status.success() {
+        load_dir(&extracted)
+    } else {
+        Err(ColonyError::Colony(format!(
+            "Failed to extract template from {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    };
+
+    let _ = fs::remove_dir_all(&extracted);
+    template
+}
+
+/// Load `template.yaml` from a directory, or from its only subdirectory as
+/// tarballs usually wrap their contents in one
+fn load_dir(dir: &Path) -> ColonyResult<AgentTemplate> {
+    let mut manifest = dir.join(MANIFEST);
+    if !manifest.is_file() {
+        let subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir() && !path.ends_with(".git"))
+            .collect();
+        if let [subdir] = subdirs.as_slice() {
+            manifest = subdir.join(MANIFEST);
+        }
+    }
+
+    let content = fs::read_to_string(&manifest)
+        .map_err(|_| ColonyError::Colony(format!("No {} found in template source", MANIFEST)))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| ColonyError::Colony(format!("Failed to parse template manifest: {}", e)))
+}
+
+fn scratch_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("colony-template-{}", uuid::Uuid::new_v4()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_fetch_tarball() {
+        let dir = tempdir().unwrap();
+        let template_dir = dir.path().join("reviewer");
+        fs::create_dir_all(&template_dir).unwrap();
+        fs::write(
+            template_dir.join(MANIFEST),
+            r#"
+name: team-reviewer
+version: 2.1.0
+agent:
+  role: Reviewer
+  focus: Team conventions
+  model: claude-sonnet-4-20250514
+"#,
+        )
+        .unwrap();
+
+        let tarball = dir.path().join("reviewer.tar.gz");
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(dir.path())
+            .arg("reviewer")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let (template, source) = fetch(tarball.to_str().unwrap()).unwrap();
+        assert_eq!(template.name, "team-reviewer");
+        assert_eq!(source.source_type, SourceType::Tarball);
+        assert_eq!(source.version, "2.1.0");
+        assert!(Path::new(&source.location).is_absolute());
+
+        // Versions must be semantic versions
+        fs::write(
+            template_dir.join(MANIFEST),
+            "{name: x, version: '2.1', agent: {role: R, focus: F, model: m}}",
+        )
+        .unwrap();
+        fs::remove_file(&tarball).unwrap();
+        Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(dir.path())
+            .arg("reviewer")
+            .status()
+            .unwrap();
+        assert!(fetch(tarball.to_str().unwrap()).is_err());
+
+        // Names can't leave the templates directory
+        fs::write(
+            template_dir.join(MANIFEST),
+            "{name: ../../x, version: 1.0.0, agent: {role: R, focus: F, model: m}}",
+        )
+        .unwrap();
+        fs::remove_file(&tarball).unwrap();
+        Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(dir.path())
+            .arg("reviewer")
+            .status()
+            .unwrap();
+        assert!(fetch(tarball.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_check_name() {
+        for name in ["code-reviewer", "team_reviewer2"] {
+            assert!(check_name(name).is_ok(), "rejected {}", name);
+        }
+        for name in ["", "../../x", "/tmp/x", "a/b", "a b", ".."] {
+            assert!(check_name(name).is_err(), "accepted {}", name);
+        }
+    }
+
+    #[test]
+    fn test_from_agent_round_trip() {
+        let agent: AgentConfig = serde_yaml::from_str(
+            r#"
+id: backend-1
+role: Backend Engineer
+focus: APIs
+instructions: Keep handlers thin
+mcp_servers:
+  db:
+    command: db-mcp
+    args: [--readonly]
+"#,
+        )
+        .unwrap();
+
+        assert!(from_agent("backend", "1.0", None, &agent).is_err());
+        let template = from_agent("backend", "1.0.0", None, &agent).unwrap();
+        assert_eq!(template.agent.role, "Backend Engineer");
+        assert_eq!(
+            template.agent.mcp_servers.as_ref().unwrap()["db"]["command"],
+            "db-mcp"
+        );
+
+        let dir = tempdir().unwrap();
+        let source = TemplateSource {
+            source_type: SourceType::Agent,
+            location: agent.id.clone(),
+            version: "1.0.0".to_string(),
+            installed_at: Utc::now(),
+            parameters: Default::default(),
+        };
+        write_installed(dir.path(), &template, &source).unwrap();
+        assert_eq!(load_dir(dir.path()).unwrap().agent.focus, "APIs");
+        let recorded = read_source(dir.path()).unwrap().unwrap();
+        assert_eq!(recorded.source_type, SourceType::Agent);
+        assert_eq!(recorded.location, "backend-1");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::colony::config::BehaviorConfig;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -9,6 +11,9 @@ pub struct AgentTemplate {
     pub author: Option<String>,
     pub description: Option<String>,
     pub license: Option<String>,
+    /// Values substituted for `{{name}}` in the agent configuration
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<TemplateParameter>,
     pub agent: AgentTemplateConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub requirements: Option<TemplateRequirements>,
@@ -30,12 +35,41 @@ pub struct AgentTemplateConfig {
     pub behavior: Option<BehaviorConfig>,
 }
 
-/// Behavior configuration
+/// A typed template parameter
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BehaviorConfig {
-    pub initiative_level: Option<String>,
-    pub communication_style: Option<String>,
-    pub thoroughness: Option<String>,
+pub struct TemplateParameter {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub param_type: ParameterType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_yaml::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The only values allowed, if set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
+}
+
+/// Type of a template parameter's value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterType {
+    #[default]
+    String,
+    Integer,
+    Number,
+    Boolean,
+}
+
+impl std::fmt::Display for ParameterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterType::String => write!(f, "string"),
+            ParameterType::Integer => write!(f, "integer"),
+            ParameterType::Number => write!(f, "number"),
+            ParameterType::Boolean => write!(f, "boolean"),
+        }
+    }
 }
 
 /// Template requirements
@@ -49,12 +83,52 @@ pub struct TemplateRequirements {
     pub skills: Option<Vec<String>>,
 }
 
+/// Where an installed template came from, kept next to its manifest so
+/// `colony template update` can fetch it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSource {
+    #[serde(rename = "type")]
+    pub source_type: SourceType,
+    /// Built-in template name, git URL, tarball path or agent ID
+    pub location: String,
+    /// Semantic version that was installed
+    pub version: String,
+    pub installed_at: DateTime<Utc>,
+    /// Parameter values substituted at install time, applied again on update
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub parameters: HashMap<String, String>,
+}
+
+/// Kind of template source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    Builtin,
+    Git,
+    Tarball,
+    /// Created from an agent in colony.yml
+    Agent,
+}
+
+impl std::fmt::Display for SourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceType::Builtin => write!(f, "built-in"),
+            SourceType::Git => write!(f, "git"),
+            SourceType::Tarball => write!(f, "tarball"),
+            SourceType::Agent => write!(f, "agent"),
+        }
+    }
+}
+
 /// Template metadata
 #[derive(Debug, Clone)]
 pub struct TemplateMetadata {
     pub template: AgentTemplate,
     pub path: std::path::PathBuf,
     pub is_builtin: bool,
+    /// Recorded source, for templates installed or created by colony
+    pub source: Option<TemplateSource>,
 }
 
 impl TemplateMetadata {
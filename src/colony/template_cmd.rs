@@ -1,7 +1,13 @@
-use crate::colony::template::{get_builtin_templates, TemplateManager};
+use crate::colony::config::ColonyConfig;
+use crate::colony::template::types::{SourceType, TemplateMetadata, TemplateSource};
+use crate::colony::template::{
+    self, get_builtin_templates, params, AgentTemplate, TemplateManager,
+};
 use crate::error::ColonyResult;
 use crate::utils;
+use chrono::Utc;
 use colored::Colorize;
+use std::collections::HashMap;
 use std::fs;
 
 /// List all available templates
@@ -34,10 +40,10 @@ pub fn list_templates() -> ColonyResult<()> {
     println!();
 
     for template in templates {
-        let source = if template.is_builtin {
-            "built-in".dimmed()
-        } else {
-            "custom".cyan()
+        let source = match &template.source {
+            Some(source) => source.source_type.to_string().cyan(),
+            None if template.is_builtin => "built-in".dimmed(),
+            None => "custom".cyan(),
         };
 
         println!(
@@ -76,14 +82,23 @@ pub fn show_template(name: &str) -> ColonyResult<()> {
     println!();
 
     println!("Version: {}", template.version);
-    println!(
-        "Source: {}",
-        if template_meta.is_builtin {
-            "built-in"
-        } else {
-            "custom"
+    match &template_meta.source {
+        Some(source) => {
+            println!("Source: {} ({})", source.location, source.source_type);
+            println!(
+                "Installed: {}",
+                source.installed_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
         }
-    );
+        None => println!(
+            "Source: {}",
+            if template_meta.is_builtin {
+                "built-in"
+            } else {
+                "custom"
+            }
+        ),
+    }
     println!();
 
     if let Some(desc) = &template.description {
@@ -107,6 +122,38 @@ pub fn show_template(name: &str) -> ColonyResult<()> {
     println!("  Model: {}", template.agent.model);
     println!();
 
+    if let Some(behavior) = &template.agent.behavior {
+        println!("Behavior:");
+        if let Some(level) = behavior.initiative_level {
+            println!("  Initiative: {}", level);
+        }
+        if let Some(style) = behavior.communication_style {
+            println!("  Communication: {}", style);
+        }
+        if let Some(level) = behavior.thoroughness {
+            println!("  Thoroughness: {}", level);
+        }
+        println!();
+    }
+
+    if !template.parameters.is_empty() {
+        println!("Parameters:");
+        for parameter in &template.parameters {
+            let mut line = format!("  {} ({})", parameter.name, parameter.param_type);
+            if let Some(default) = parameter.default_value() {
+                line.push_str(&format!(", default: {}", default));
+            }
+            if let Some(choices) = &parameter.choices {
+                line.push_str(&format!(", one of: {}", choices.join(", ")));
+            }
+            println!("{}", line);
+            if let Some(description) = &parameter.description {
+                println!("    {}", description.dimmed());
+            }
+        }
+        println!();
+    }
+
     if let Some(reqs) = &template.requirements {
         if let Some(repo_types) = &reqs.repo_types {
             println!("Compatible Repository Types:");
@@ -120,15 +167,14 @@ pub fn show_template(name: &str) -> ColonyResult<()> {
     Ok(())
 }
 
-/// Install a built-in template to .colony/templates/
-pub fn install_template(name: &str) -> ColonyResult<()> {
-    // Find the built-in template
-    let builtin = get_builtin_templates()
-        .into_iter()
-        .find(|(n, _)| n == &name)
-        .ok_or_else(|| {
-            crate::error::ColonyError::Colony(format!("Built-in template not found: {}", name))
-        })?;
+/// Install a template to .colony/templates/ from a built-in name, a git URL or
+/// a tarball path
+///
+/// Parameter values come from `--set key=value`, and any that aren't set are
+/// asked for. They are substituted into the installed template and recorded
+/// with its source so `colony template update` can apply them again.
+pub fn install_template(source: &str, sets: &[String]) -> ColonyResult<()> {
+    let (mut template, mut source) = template::source::fetch(source)?;
 
     // Create templates directory
     let templates_dir = TemplateManager::default_templates_dir()?;
@@ -136,35 +182,227 @@ pub fn install_template(name: &str) -> ColonyResult<()> {
         crate::error::ColonyError::Colony(format!("Failed to create templates directory: {}", e))
     })?;
 
-    // Create template directory
-    let template_dir = templates_dir.join(name);
+    let template_dir = templates_dir.join(&template.name);
     if template_dir.exists() {
         return Err(crate::error::ColonyError::Colony(format!(
-            "Template '{}' is already installed",
-            name
+            "Template '{}' is already installed. Use 'colony template update {}' to refresh it",
+            template.name, template.name
         )));
     }
 
-    fs::create_dir_all(&template_dir).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to create template directory: {}", e))
-    })?;
-
-    // Write template.yaml
-    let template_file = template_dir.join("template.yaml");
-    fs::write(&template_file, builtin.1).map_err(|e| {
-        crate::error::ColonyError::Colony(format!("Failed to write template file: {}", e))
-    })?;
+    let values = parameter_values(&template, parse_sets(&template, sets)?)?;
+    params::apply(&mut template, &values)?;
+    source.parameters = values;
+    template::source::write_installed(&template_dir, &template, &source)?;
 
-    utils::success(&format!("Installed template: {}", name));
+    utils::success(&format!(
+        "Installed template: {} {}",
+        template.name, template.version
+    ));
     println!();
     println!("Template location: {}", template_dir.display());
+    println!("Source: {} ({})", source.location, source.source_type);
     println!();
     println!("You can now use this template when creating agents:");
-    println!("  colony agent create --template {} --id my-agent", name);
+    println!(
+        "  colony agent create --template {} --id my-agent",
+        template.name
+    );
 
     Ok(())
 }
 
+/// Save an agent from colony.yml as a template in .colony/templates/
+pub fn create_template(
+    name: &str,
+    agent_id: &str,
+    version: &str,
+    description: Option<String>,
+) -> ColonyResult<()> {
+    template::source::check_name(name)?;
+
+    let config_path = &crate::colony::config::config_path();
+    if !config_path.exists() {
+        return Err(crate::error::ColonyError::Colony(
+            "colony.yml not found. Run 'colony init' first.".to_string(),
+        ));
+    }
+    let config = ColonyConfig::load(config_path)?;
+    // Replicas like `worker-1` can be saved as well as the agents in colony.yml
+    let agent = config
+        .expanded_agents()
+        .into_iter()
+        .chain(config.agents.iter().cloned())
+        .find(|a| a.id == agent_id)
+        .ok_or_else(|| {
+            crate::error::ColonyError::Colony(format!(
+                "Agent '{}' not found in colony.yml",
+                agent_id
+            ))
+        })?;
+
+    let template_dir = TemplateManager::default_templates_dir()?.join(name);
+    if template_dir.exists() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Template '{}' already exists",
+            name
+        )));
+    }
+
+    let template = template::source::from_agent(name, version, description, &agent)?;
+    let source = TemplateSource {
+        source_type: SourceType::Agent,
+        location: agent.id.clone(),
+        version: template.version.clone(),
+        installed_at: Utc::now(),
+        parameters: HashMap::new(),
+    };
+    template::source::write_installed(&template_dir, &template, &source)?;
+
+    utils::success(&format!(
+        "Created template {} {} from agent '{}'",
+        name, version, agent_id
+    ));
+    println!();
+    println!("Template location: {}", template_dir.display());
+    println!();
+    println!("Share it by committing the directory, or packaging it as a tarball:");
+    println!("  tar -czf {}.tar.gz -C .colony/templates {}", name, name);
+
+    Ok(())
+}
+
+/// Fetch installed templates from their recorded sources again and install
+/// newer versions, keeping the parameter values they were installed with
+pub fn update_templates(name: Option<&str>) -> ColonyResult<()> {
+    let templates_dir = TemplateManager::default_templates_dir()?;
+    let mut manager = TemplateManager::new(templates_dir, TemplateManager::builtin_templates_dir());
+    manager.discover_templates()?;
+
+    let installed: Vec<&TemplateMetadata> = match name {
+        Some(name) => vec![manager.get_template(name).ok_or_else(|| {
+            crate::error::ColonyError::Colony(format!("Template not installed: {}", name))
+        })?],
+        None => manager.list_user_templates(),
+    };
+
+    let mut updated = 0;
+    for installed in installed {
+        let name = installed.name();
+        let Some(recorded) = &installed.source else {
+            utils::info(&format!("{}: no recorded source, skipping", name));
+            continue;
+        };
+        if recorded.source_type == SourceType::Agent {
+            utils::info(&format!(
+                "{}: created from agent '{}', skipping",
+                name, recorded.location
+            ));
+            continue;
+        }
+
+        let (mut template, mut source) = template::source::fetch(&recorded.location)?;
+        let current = semver::Version::parse(&recorded.version).map_err(|e| {
+            crate::error::ColonyError::Colony(format!(
+                "Template '{}' has invalid recorded version '{}': {}",
+                name, recorded.version, e
+            ))
+        })?;
+        let latest = template::source::parse_version(&template)?;
+        if latest <= current {
+            println!("  {} {} is up to date", name.bold(), current);
+            continue;
+        }
+
+        // Keep values for parameters the new version still has
+        let given = recorded
+            .parameters
+            .iter()
+            .filter(|(key, _)| template.parameters.iter().any(|p| &p.name == *key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let values = parameter_values(&template, given)?;
+        params::apply(&mut template, &values)?;
+        source.parameters = values;
+
+        // A template installed under another name keeps its directory
+        template.name = name.to_string();
+        template::source::write_installed(&installed.path, &template, &source)?;
+        utils::success(&format!("Updated {} {} -> {}", name, current, latest));
+        updated += 1;
+    }
+
+    if updated == 0 {
+        utils::info("No templates were updated");
+    }
+
+    Ok(())
+}
+
+/// Parse `--set key=value` pairs, checking each against its parameter
+fn parse_sets(template: &AgentTemplate, sets: &[String]) -> ColonyResult<HashMap<String, String>> {
+    let mut given = HashMap::new();
+    for set in sets {
+        let (key, value) = set.split_once('=').ok_or_else(|| {
+            crate::error::ColonyError::Colony(format!(
+                "Invalid --set '{}'. Use --set key=value",
+                set
+            ))
+        })?;
+        let key = key.trim();
+        let parameter = template
+            .parameters
+            .iter()
+            .find(|p| p.name == key)
+            .ok_or_else(|| {
+                crate::error::ColonyError::Colony(format!(
+                    "Template '{}' has no parameter '{}'",
+                    template.name, key
+                ))
+            })?;
+        parameter.check(value)?;
+        given.insert(key.to_string(), value.to_string());
+    }
+    Ok(given)
+}
+
+/// Values for a template's parameters, asking for the ones not given
+fn parameter_values(
+    template: &AgentTemplate,
+    mut given: HashMap<String, String>,
+) -> ColonyResult<HashMap<String, String>> {
+    for parameter in &template.parameters {
+        if given.contains_key(&parameter.name) {
+            continue;
+        }
+
+        let mut label = format!("{} ({})", parameter.name, parameter.param_type);
+        if let Some(choices) = &parameter.choices {
+            label = format!("{} ({})", parameter.name, choices.join("/"));
+        }
+        if let Some(description) = &parameter.description {
+            label = format!("{} - {}", label, description);
+        }
+
+        // Ask again until the value fits the parameter's type
+        loop {
+            let default = parameter.default_value();
+            let value = utils::prompt(&label, default.as_deref()).ok_or_else(|| {
+                crate::error::ColonyError::Colony("Installation cancelled".to_string())
+            })?;
+            match parameter.check(&value) {
+                Ok(()) => {
+                    given.insert(parameter.name.clone(), value);
+                    break;
+                }
+                Err(e) => utils::warning(&e.to_string()),
+            }
+        }
+    }
+
+    params::resolve_values(template, &given)
+}
+
 /// List built-in templates available for installation
 pub fn list_builtin() -> ColonyResult<()> {
     utils::header("Built-in Templates");
@@ -1,3 +1,4 @@
+use crate::colony::multiplexer::PaneState;
 use crate::error::ColonyResult;
 use std::process::Command;
 
@@ -282,6 +283,400 @@ pub fn send_command_to_window_pane(
     Ok(())
 }
 
+/// Get the stable pane ID (e.g. `%3`) of a window:pane
+/// Pane IDs survive other panes being closed, unlike window:pane indexes
+pub fn pane_id(session_name: &str, window: usize, pane: usize) -> ColonyResult<String> {
+    let target = format!("{}:{}.{}", session_name, window, pane);
+
+    let output = Command::new(tmux_bin())
+        .arg("display-message")
+        .arg("-p")
+        .arg("-t")
+        .arg(&target)
+        .arg("#{pane_id}")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to get pane ID for {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Type text into a pane (by any tmux target, e.g. a pane ID) and press Enter
+pub fn send_text_to_target(target: &str, text: &str) -> ColonyResult<()> {
+    // Send the text literally so tmux doesn't interpret key names in it
+    let output = Command::new(tmux_bin())
+        .arg("send-keys")
+        .arg("-t")
+        .arg(target)
+        .arg("-l")
+        .arg(text)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to send text to pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let output = Command::new(tmux_bin())
+        .arg("send-keys")
+        .arg("-t")
+        .arg(target)
+        .arg("Enter")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to send Enter to pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Capture the visible contents of a pane
+pub fn capture_pane(target: &str) -> ColonyResult<String> {
+    let output = Command::new(tmux_bin())
+        .arg("capture-pane")
+        .arg("-p")
+        .arg("-t")
+        .arg(target)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to capture pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Create a detached window in a session running a command
+/// Used for background helpers that shouldn't take up space in the agent layout
+pub fn create_background_window(
+    session_name: &str,
+    window_name: &str,
+    command: &str,
+) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("new-window")
+        .arg("-d")
+        .arg("-t")
+        .arg(format!("{}:", session_name))
+        .arg("-n")
+        .arg(window_name)
+        .arg(command)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to create window '{}': {}",
+            window_name,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Create an empty window at a specific index without switching to it
+pub fn create_window(session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("new-window")
+        .arg("-d")
+        .arg("-t")
+        .arg(format!("{}:{}", session_name, window))
+        .arg("-n")
+        .arg(name)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to create window '{}': {}",
+            name,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rename a window
+pub fn rename_window(session_name: &str, window: usize, name: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("rename-window")
+        .arg("-t")
+        .arg(format!("{}:{}", session_name, window))
+        .arg(name)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to rename window: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Get the size of a window as (columns, rows)
+pub fn window_size(session_name: &str, window: usize) -> ColonyResult<(u16, u16)> {
+    let target = format!("{}:{}", session_name, window);
+
+    let output = Command::new(tmux_bin())
+        .arg("display-message")
+        .arg("-p")
+        .arg("-t")
+        .arg(&target)
+        .arg("#{window_width} #{window_height}")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to get size of window {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut parts = stdout.split_whitespace().map(|n| n.parse::<u16>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(width)), Some(Ok(height))) => Ok((width, height)),
+        _ => Err(crate::error::ColonyError::Colony(format!(
+            "Failed to parse window size: '{}'",
+            stdout.trim()
+        ))),
+    }
+}
+
+/// Split a pane, giving the new pane `length` columns (`horizontal`) or rows
+/// The new pane runs the default shell and doesn't take focus
+/// Returns the index of the new pane
+pub fn split_pane(
+    session_name: &str,
+    window: usize,
+    pane: usize,
+    horizontal: bool,
+    length: u16,
+) -> ColonyResult<usize> {
+    let target = format!("{}:{}.{}", session_name, window, pane);
+
+    let output = Command::new(tmux_bin())
+        .arg("split-window")
+        .arg("-d")
+        .arg(if horizontal { "-h" } else { "-v" })
+        .arg("-l")
+        .arg(length.to_string())
+        .arg("-t")
+        .arg(&target)
+        .arg("-P")
+        .arg("-F")
+        .arg("#{pane_index}")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to split pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let pane_index_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    pane_index_str.parse::<usize>().map_err(|_| {
+        crate::error::ColonyError::Colony(format!(
+            "Failed to parse pane index: '{}'",
+            pane_index_str
+        ))
+    })
+}
+
+/// Check whether the program in a pane is still running
+/// A pane counts as dead when tmux reports it dead, or when its process (the
+/// shell wrapping the command) has no child processes left
+pub fn pane_state(target: &str) -> PaneState {
+    let output = Command::new(tmux_bin())
+        .arg("display-message")
+        .arg("-p")
+        .arg("-t")
+        .arg(target)
+        .arg("#{pane_dead} #{pane_pid}")
+        .output();
+
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        _ => return PaneState::Missing,
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (dead, pid) = stdout.trim().split_once(' ').unwrap_or((stdout.trim(), ""));
+
+    if dead == "1" || pid.is_empty() {
+        return PaneState::Dead;
+    }
+
+    let has_children = Command::new("pgrep")
+        .arg("-P")
+        .arg(pid)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(true);
+
+    if has_children {
+        PaneState::Running
+    } else {
+        PaneState::Dead
+    }
+}
+
+/// Keep a pane open after its program exits so it can be respawned in place
+pub fn set_remain_on_exit(target: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("set-option")
+        .arg("-p")
+        .arg("-t")
+        .arg(target)
+        .arg("remain-on-exit")
+        .arg("on")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to set remain-on-exit for {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Replace whatever is running in a pane with a new command, keeping the pane in place
+pub fn respawn_pane(target: &str, command: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("respawn-pane")
+        .arg("-k")
+        .arg("-t")
+        .arg(target)
+        .arg("sh")
+        .arg("-c")
+        .arg(command)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to respawn pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Close a pane, killing whatever runs in it
+pub fn kill_pane(target: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("kill-pane")
+        .arg("-t")
+        .arg(target)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to kill pane {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Split a window and run a command in the new pane
+/// Returns the stable pane ID of the new pane
+pub fn split_window_with_id(target: &str, command: &str) -> ColonyResult<String> {
+    let output = Command::new(tmux_bin())
+        .arg("split-window")
+        .arg("-t")
+        .arg(target)
+        .arg("-P")
+        .arg("-F")
+        .arg("#{pane_id}")
+        .arg("sh")
+        .arg("-c")
+        .arg(command)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to split window {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Append everything a pane outputs to a log file
+pub fn pipe_pane_to_file(target: &str, path: &std::path::Path) -> ColonyResult<()> {
+    let path = path.to_string_lossy().replace('\'', "'\\''");
+
+    let output = Command::new(tmux_bin())
+        .arg("pipe-pane")
+        .arg("-t")
+        .arg(target)
+        .arg(format!("cat >> '{}'", path))
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to pipe pane {} to log: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Set the title of a pane by any tmux target (e.g. a pane ID)
+pub fn set_target_title(target: &str, title: &str) -> ColonyResult<()> {
+    let output = Command::new(tmux_bin())
+        .arg("select-pane")
+        .arg("-t")
+        .arg(target)
+        .arg("-T")
+        .arg(title)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(crate::error::ColonyError::Colony(format!(
+            "Failed to set pane title for {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
 /// Set pane title for a specific window:pane (for multi-window sessions)
 pub fn set_window_pane_title(
     session_name: &str,
@@ -367,53 +762,6 @@ pub fn select_window_layout(session_name: &str, window: usize, layout: &str) ->
     Ok(())
 }
 
-/// Resize a pane by percentage
-pub fn resize_pane_percentage(
-    session_name: &str,
-    window: usize,
-    pane: usize,
-    width_pct: Option<u16>,
-    height_pct: Option<u16>,
-) -> ColonyResult<()> {
-    let target = format!("{}:{}.{}", session_name, window, pane);
-
-    if let Some(width) = width_pct {
-        let output = Command::new(tmux_bin())
-            .arg("resize-pane")
-            .arg("-t")
-            .arg(&target)
-            .arg("-x")
-            .arg(format!("{}%", width))
-            .output()?;
-
-        if !output.status.success() {
-            return Err(crate::error::ColonyError::Colony(format!(
-                "Failed to resize pane width: {}",
-                String::from_utf8_lossy(&output.stderr)
-            )));
-        }
-    }
-
-    if let Some(height) = height_pct {
-        let output = Command::new(tmux_bin())
-            .arg("resize-pane")
-            .arg("-t")
-            .arg(&target)
-            .arg("-y")
-            .arg(format!("{}%", height))
-            .output()?;
-
-        if !output.status.success() {
-            return Err(crate::error::ColonyError::Colony(format!(
-                "Failed to resize pane height: {}",
-                String::from_utf8_lossy(&output.stderr)
-            )));
-        }
-    }
-
-    Ok(())
-}
-
 /// Swap two panes
 pub fn swap_panes(
     session_name: &str,
@@ -72,6 +72,7 @@ pub enum Dialog {
     AddExecutor { step: usize },
     AddMcpServer { step: usize },
     ConfigMenu,
+    RestartAgent,
 }
 
 impl Dialog {
@@ -85,6 +86,7 @@ impl Dialog {
             Dialog::AddExecutor { .. } => "Enable MCP Executor",
             Dialog::AddMcpServer { .. } => "Add MCP Server",
             Dialog::ConfigMenu => "Configuration Menu",
+            Dialog::RestartAgent => "Restart Agent",
         }
     }
 
@@ -127,6 +129,7 @@ impl Dialog {
             Dialog::ConfigMenu => {
                 "Select option: 1=Add Agent, 2=Add Executor, 3=Add MCP Server, ESC=Cancel"
             }
+            Dialog::RestartAgent => "Agent ID:",
         }
     }
 
@@ -140,6 +143,7 @@ impl Dialog {
             Dialog::AddExecutor { .. } => 2,
             Dialog::AddMcpServer { .. } => 3,
             Dialog::ConfigMenu => 1,
+            Dialog::RestartAgent => 1,
         }
     }
 }
@@ -342,6 +346,11 @@ impl App {
                 self.input_buffer.clear();
                 self.dialog_inputs.clear();
             }
+            Action::RestartAgent => {
+                self.active_dialog = Some(Dialog::RestartAgent);
+                self.input_buffer.clear();
+                self.dialog_inputs.clear();
+            }
             _ => {}
         }
     }
@@ -367,6 +376,14 @@ impl App {
                     self.active_dialog = None;
                     self.input_buffer.clear();
                 }
+                Dialog::RestartAgent => {
+                    let agent_id = self.input_buffer.trim().to_string();
+                    if !agent_id.is_empty() {
+                        self.execute_restart_agent(&agent_id);
+                    }
+                    self.active_dialog = None;
+                    self.input_buffer.clear();
+                }
                 Dialog::CreateTask { step } => {
                     // Multi-step dialog
                     self.dialog_inputs
@@ -603,6 +620,33 @@ impl App {
         }
     }
 
+    /// Execute restart agent
+    fn execute_restart_agent(&mut self, agent_id: &str) {
+        use crate::colony::{ColonyConfig, ColonyController};
+
+        let result = ColonyConfig::load(Path::new(&self.config_path))
+            .and_then(ColonyController::new)
+            .and_then(|mut controller| {
+                controller.initialize_agents()?;
+                controller.load_state()?;
+                // The TUI runs synchronously inside the tokio runtime
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current().block_on(controller.restart_agent(agent_id))
+                })
+            });
+
+        match result {
+            Ok(pane_id) => {
+                self.set_status(
+                    &format!("Agent '{}' restarted in pane {}", agent_id, pane_id),
+                    false,
+                );
+                self.refresh_data();
+            }
+            Err(e) => self.set_status(&format!("Error: {}", e), true),
+        }
+    }
+
     /// Execute add agent
     fn execute_add_agent(&mut self) {
         use crate::colony::config::{AgentConfig, ColonyConfig};
@@ -636,19 +680,27 @@ impl App {
             role: role.clone(),
             focus: focus.clone(),
             model: model.to_string(),
+            fallback_models: Vec::new(),
             directory: None,
             worktree,
             env: None,
             mcp_servers: None,
             instructions: None,
             startup_prompt: None,
+            template: None,
+            template_params: None,
+            behavior: None,
+            replicas: None,
+            tags: Vec::new(),
+            replica_of: None,
             capabilities: None,
             nudge: None,
+            delivery: None,
         };
 
         // Load config, add agent, save
         let config_path = Path::new(&self.config_path);
-        match ColonyConfig::load(config_path) {
+        match ColonyConfig::load_file(config_path) {
             Ok(mut config) => {
                 // Check for duplicate ID
                 if config.agents.iter().any(|a| a.id == *agent_id) {
@@ -728,11 +780,13 @@ impl App {
                 Some(mcp_servers)
             },
             languages: vec!["typescript".to_string(), "python".to_string()],
+            model: None,
+            fallback_models: Vec::new(),
         };
 
         // Load config, set executor, save
         let config_path = Path::new(&self.config_path);
-        match ColonyConfig::load(config_path) {
+        match ColonyConfig::load_file(config_path) {
             Ok(mut config) => {
                 config.executor = Some(executor);
                 match config.save(config_path) {
@@ -796,12 +850,13 @@ impl App {
                 command: command.clone(),
                 args,
                 env: None,
+                inline_secrets: false,
             }
         };
 
         // Load config, add MCP server to executor, save
         let config_path = Path::new(&self.config_path);
-        match ColonyConfig::load(config_path) {
+        match ColonyConfig::load_file(config_path) {
             Ok(mut config) => {
                 if let Some(ref mut executor) = config.executor {
                     let mut servers = executor.mcp_servers.take().unwrap_or_default();
@@ -3,7 +3,8 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::colony::agent::AgentStatus;
-use crate::colony::messaging::Message;
+use crate::colony::messaging::{self, Message};
+use crate::colony::state::MessageSearch;
 use crate::colony::tasks::{Task, TaskStatus};
 use crate::colony::ColonyConfig;
 
@@ -26,6 +27,7 @@ pub struct AgentInfo {
     pub status: AgentStatus,
     pub pid: Option<u32>,
     pub current_task: Option<String>,
+    pub unread: usize,
 }
 
 impl ColonyData {
@@ -97,13 +99,16 @@ impl ColonyData {
         // Build map of agent ID -> current task
         let task_assignments = Self::load_task_assignments(colony_root)?;
 
-        for agent_config in &config.agents {
+        for agent_config in &config.expanded_agents() {
             let (status, pid) = states
                 .get(&agent_config.id)
                 .cloned()
                 .unwrap_or((AgentStatus::Idle, None));
 
             let current_task = task_assignments.get(&agent_config.id).cloned();
+            let unread = messaging::load_unread_messages(colony_root, &agent_config.id)
+                .map(|messages| messages.len())
+                .unwrap_or(0);
 
             agents.push(AgentInfo {
                 id: agent_config.id.clone(),
@@ -111,6 +116,7 @@ impl ColonyData {
                 status,
                 pid,
                 current_task,
+                unread,
             });
         }
 
@@ -193,47 +199,14 @@ impl ColonyData {
     }
 
     fn load_messages(colony_root: &Path) -> Result<Vec<Message>, String> {
-        let messages_dir = colony_root.join("messages");
-
-        if !messages_dir.exists() {
-            return Ok(Vec::new());
-        }
-
-        let mut messages = Vec::new();
-
-        // Recursively walk all message directories
-        fn walk_messages(dir: &Path, messages: &mut Vec<Message>) -> Result<(), String> {
-            if !dir.exists() {
-                return Ok(());
-            }
-
-            if let Ok(entries) = fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-
-                    if path.is_dir() {
-                        walk_messages(&path, messages)?;
-                    } else if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Ok(content) = fs::read_to_string(&path) {
-                            if let Ok(message) = serde_json::from_str::<Message>(&content) {
-                                messages.push(message);
-                            }
-                        }
-                    }
-                }
-            }
-
-            Ok(())
-        }
-
-        walk_messages(&messages_dir, &mut messages)?;
-
-        // Sort by timestamp (most recent first) and deduplicate
-        messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        messages.dedup_by(|a, b| a.id == b.id);
-
-        // Keep only the most recent 100 messages
-        messages.truncate(100);
+        // The most recent 100 messages, most recent first
+        let search = MessageSearch {
+            limit: Some(100),
+            ..Default::default()
+        };
+        let mut messages = messaging::search_messages(colony_root, &search)
+            .map_err(|e| format!("Failed to load messages: {}", e))?;
+        messages.reverse();
 
         Ok(messages)
     }
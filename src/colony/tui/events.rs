@@ -64,6 +64,8 @@ pub enum Action {
     CreateTask,
     /// Send message to agent
     SendMessage,
+    /// Restart an agent
+    RestartAgent,
     /// Cancel current action
     Cancel,
     /// Confirm current action
@@ -140,6 +142,7 @@ impl Action {
             (KeyCode::Char('b'), KeyModifiers::NONE) => Action::BroadcastMessage,
             (KeyCode::Char('t'), KeyModifiers::NONE) => Action::CreateTask,
             (KeyCode::Char('m'), KeyModifiers::NONE) => Action::SendMessage,
+            (KeyCode::Char('R'), _) => Action::RestartAgent,
 
             _ => Action::None,
         }
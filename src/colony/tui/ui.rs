@@ -346,11 +346,17 @@ fn render_agents(f: &mut Frame, app: &App, area: Rect) {
                 .map(|t| truncate(t, 30))
                 .unwrap_or_else(|| "-".to_string());
 
+            let unread_text = match agent.unread {
+                0 => "-".to_string(),
+                n => n.to_string(),
+            };
+
             Row::new(vec![
                 agent.id.clone(),
                 truncate(&agent.role, 25),
                 status_text,
                 pid_text,
+                unread_text,
                 task_text,
             ])
             .style(status_style)
@@ -364,13 +370,21 @@ fn render_agents(f: &mut Frame, app: &App, area: Rect) {
             Constraint::Length(25), // Role
             Constraint::Length(12), // Status
             Constraint::Length(10), // PID
+            Constraint::Length(8),  // Unread
             Constraint::Min(30),    // Current Task
         ],
     )
     .header(
-        Row::new(vec!["Agent ID", "Role", "Status", "PID", "Current Task"])
-            .style(Style::default().add_modifier(Modifier::BOLD))
-            .bottom_margin(1),
+        Row::new(vec![
+            "Agent ID",
+            "Role",
+            "Status",
+            "PID",
+            "Unread",
+            "Current Task",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .bottom_margin(1),
     )
     .block(
         Block::default()
@@ -497,9 +511,10 @@ fn render_task_list(f: &mut Frame, app: &App, area: Rect) {
 fn render_messages(f: &mut Frame, app: &App, area: Rect) {
     let messages = &app.data.messages;
 
-    let items: Vec<ListItem> = messages
-        .iter()
-        .map(|msg| {
+    // Replies are indented under the message they answer
+    let items: Vec<ListItem> = crate::colony::messaging::thread_tree(messages)
+        .into_iter()
+        .map(|(depth, msg)| {
             let time = msg
                 .timestamp
                 .split('T')
@@ -513,9 +528,16 @@ fn render_messages(f: &mut Frame, app: &App, area: Rect) {
                 format!("→ {}", msg.to)
             };
 
+            let branch = if depth > 0 {
+                format!("{}└─ ", "   ".repeat(depth - 1))
+            } else {
+                String::new()
+            };
+
             let line = Line::from(vec![
                 Span::styled(time, Style::default().fg(Color::Gray)),
                 Span::raw(" "),
+                Span::styled(branch, Style::default().fg(Color::DarkGray)),
                 Span::styled(&msg.from, Style::default().fg(Color::Cyan)),
                 Span::raw(" "),
                 Span::styled(to_display, Style::default().fg(Color::Yellow)),
@@ -827,6 +849,7 @@ fn render_help(f: &mut Frame, area: Rect) {
         Line::from("    b              Broadcast message (opens Compose tab)"),
         Line::from("    m              Send message (opens Compose tab)"),
         Line::from("    t              Create task (coming soon)"),
+        Line::from("    R              Restart an agent"),
         Line::from("    ?              Show this help"),
         Line::from(""),
         Line::from("  Compose Tab:"),
@@ -849,7 +872,7 @@ fn render_help(f: &mut Frame, area: Rect) {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
-        Line::from("  1: Agents      - View running agents and their current tasks"),
+        Line::from("  1: Agents      - View running agents, unread messages and current tasks"),
         Line::from("  2: Tasks       - Monitor task queue (pending, in progress, completed)"),
         Line::from("  3: Messages    - See message flow between agents and colony"),
         Line::from("  4: State       - Git-backed shared state (tasks, workflows)"),
@@ -218,6 +218,25 @@ fn is_valid_duration(duration: &str) -> bool {
     num_part.parse::<u64>().is_ok()
 }
 
+/// Parse a duration string like "30s", "5m", "1h" or "2d"
+pub fn parse_duration(duration: &str) -> Option<std::time::Duration> {
+    if !is_valid_duration(duration) {
+        return None;
+    }
+
+    let (num_part, unit) = duration.split_at(duration.len() - 1);
+    let value = num_part.parse::<u64>().ok()?;
+    let seconds = match unit {
+        "s" => value,
+        "m" => value.checked_mul(60)?,
+        "h" => value.checked_mul(3600)?,
+        "d" => value.checked_mul(86400)?,
+        _ => return None,
+    };
+
+    Some(std::time::Duration::from_secs(seconds))
+}
+
 /// Get the topological order of steps (for execution planning)
 pub fn topological_sort(definition: &WorkflowDefinition) -> ColonyResult<Vec<Vec<String>>> {
     // Build dependency map
@@ -294,4 +313,14 @@ mod tests {
         assert!(!is_valid_duration("m5"));
         assert!(!is_valid_duration("abc"));
     }
+
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172800)));
+        assert_eq!(parse_duration("5"), None);
+    }
 }
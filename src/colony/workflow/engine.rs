@@ -0,0 +1,789 @@
+//! Workflow execution engine
+//!
+//! Walks a workflow definition level by level (see [`topological_sort`]) and
+//! hands each step to its agent as a task in the colony task queue, together
+//! with a `task` message in the agent's inbox. The engine then polls the task
+//! queue until the agent completes (or cancels) the task, recording a
+//! [`StepExecution`] for every step as the run progresses.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use super::definition::{parse_duration, topological_sort, validate_workflow_definition};
+use super::storage::WorkflowStorage;
+use super::types::{
+    BackoffStrategy, StepExecution, StepStatus, WorkflowContext, WorkflowDefinition, WorkflowRun,
+    WorkflowRunStatus, WorkflowStep,
+};
+use crate::colony::messaging::{resolve_recipients, Message, MessageType};
+use crate::colony::state::TaskIdGenerator;
+use crate::colony::tasks::queue::TaskQueue;
+use crate::colony::tasks::{Task, TaskStatus};
+use crate::error::{ColonyError, ColonyResult};
+use crate::utils;
+
+/// Sender ID used for messages created by the engine
+const ENGINE_SENDER: &str = "workflow";
+
+/// Default interval between task queue polls
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default base delay before a failed step is retried
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Executes workflow runs against the colony task queue
+pub struct WorkflowEngine {
+    colony_root: PathBuf,
+    storage: WorkflowStorage,
+    queue: TaskQueue,
+    poll_interval: Duration,
+    retry_delay: Duration,
+}
+
+/// A dispatched step that is waiting on its tasks
+struct ActiveStep {
+    name: String,
+    deadline: Option<Instant>,
+    retry_at: Option<Instant>,
+}
+
+/// Result of checking a step's tasks
+enum StepPoll {
+    Waiting,
+    Completed(Value),
+    Failed(String),
+}
+
+impl WorkflowEngine {
+    /// Create a new engine for the colony at `colony_root`
+    pub fn new(colony_root: &Path) -> Self {
+        Self {
+            colony_root: colony_root.to_path_buf(),
+            storage: WorkflowStorage::new(colony_root),
+            queue: TaskQueue::new(colony_root),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            retry_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+
+    /// Set the interval between task queue polls
+    #[cfg(test)]
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Set the base delay used for retry backoff
+    #[cfg(test)]
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Create and persist a pending run for a workflow
+    pub fn create_run(
+        &self,
+        definition: &WorkflowDefinition,
+        input: Value,
+    ) -> ColonyResult<WorkflowRun> {
+        let run = WorkflowRun {
+            id: TaskIdGenerator::generate("run"),
+            workflow_name: definition.name.clone(),
+            status: WorkflowRunStatus::Pending,
+            input: Some(input),
+            started_at: Utc::now(),
+            completed_at: None,
+            steps: definition
+                .steps
+                .iter()
+                .map(|step| StepExecution {
+                    step_name: step.name.clone(),
+                    status: StepStatus::Pending,
+                    agent: step.agent.clone(),
+                    started_at: None,
+                    completed_at: None,
+                    output: None,
+                    error: None,
+                    attempt: 0,
+                    task_ids: Vec::new(),
+                })
+                .collect(),
+            error: None,
+        };
+
+        self.storage.save_run(&run)?;
+        Ok(run)
+    }
+
+    /// Execute a run until every step has completed, failed or been skipped
+    ///
+    /// Steps whose dependencies did not complete are skipped; independent
+    /// steps keep running. The run ends up `completed` only if every step
+    /// completed, and `cancelled` if it was cancelled while executing.
+    pub async fn execute(
+        &self,
+        definition: &WorkflowDefinition,
+        run: &mut WorkflowRun,
+    ) -> ColonyResult<()> {
+        validate_workflow_definition(definition)?;
+        let levels = topological_sort(definition)?;
+        self.queue.initialize()?;
+
+        let mut context = WorkflowContext::new(
+            run.id.clone(),
+            run.workflow_name.clone(),
+            run.input.clone().unwrap_or(Value::Null),
+        );
+        let mut failed_steps: Vec<String> = Vec::new();
+
+        run.status = WorkflowRunStatus::Running;
+        self.storage.save_run(run)?;
+
+        for level in levels {
+            let mut active = Vec::new();
+
+            for step_name in &level {
+                let step = find_step(definition, step_name)?;
+
+                if !dependencies_completed(step, run) {
+                    let execution = step_execution_mut(run, step_name)?;
+                    execution.status = StepStatus::Skipped;
+                    execution.error = Some("A dependency did not complete".to_string());
+                    utils::warning(&format!("Skipping step '{}'", step_name));
+                    continue;
+                }
+
+                self.dispatch_step(step, run, &context)?;
+                active.push(ActiveStep {
+                    name: step_name.clone(),
+                    deadline: step_deadline(step),
+                    retry_at: None,
+                });
+            }
+            self.storage.save_run(run)?;
+
+            while !active.is_empty() {
+                tokio::time::sleep(self.poll_interval).await;
+
+                if self.is_cancelled(run) {
+                    self.cancel_unfinished_steps(run)?;
+                    run.status = WorkflowRunStatus::Cancelled;
+                    run.completed_at.get_or_insert_with(Utc::now);
+                    self.storage.save_run(run)?;
+                    utils::warning(&format!("Workflow run '{}' was cancelled", run.id));
+                    return Ok(());
+                }
+
+                let mut still_active = Vec::new();
+
+                for mut active_step in active.drain(..) {
+                    let step = find_step(definition, &active_step.name)?;
+
+                    if let Some(retry_at) = active_step.retry_at {
+                        if Instant::now() >= retry_at {
+                            self.dispatch_step(step, run, &context)?;
+                            active_step.retry_at = None;
+                            active_step.deadline = step_deadline(step);
+                        }
+                        still_active.push(active_step);
+                        continue;
+                    }
+
+                    match self.poll_step(step, run, active_step.deadline)? {
+                        StepPoll::Waiting => still_active.push(active_step),
+                        StepPoll::Completed(output) => {
+                            let execution = step_execution_mut(run, &step.name)?;
+                            execution.status = StepStatus::Completed;
+                            execution.completed_at = Some(Utc::now());
+                            execution.output = Some(output.clone());
+                            context.add_step_output(step.name.clone(), output);
+                            utils::success(&format!("Step '{}' completed", step.name));
+                        }
+                        StepPoll::Failed(reason) => {
+                            let max_attempts = step.retry.as_ref().map_or(1, |r| r.max_attempts);
+                            let execution = step_execution_mut(run, &step.name)?;
+                            let attempt = execution.attempt;
+                            let task_ids = execution.task_ids.clone();
+                            execution.error = Some(reason.clone());
+
+                            if attempt < max_attempts {
+                                execution.status = StepStatus::Retrying;
+                                let delay = self.backoff_delay(step, attempt);
+                                active_step.retry_at = Some(Instant::now() + delay);
+                                utils::warning(&format!(
+                                    "Step '{}' failed ({}), retrying in {}s (attempt {}/{})",
+                                    step.name,
+                                    reason,
+                                    delay.as_secs(),
+                                    attempt + 1,
+                                    max_attempts
+                                ));
+                                still_active.push(active_step);
+                            } else {
+                                execution.status = StepStatus::Failed;
+                                execution.completed_at = Some(Utc::now());
+                                utils::warning(&format!("Step '{}' failed: {}", step.name, reason));
+                                failed_steps.push(step.name.clone());
+                                self.run_error_handler(definition, step, run, &context)?;
+                            }
+
+                            self.cancel_tasks(&task_ids)?;
+                        }
+                    }
+                }
+
+                active = still_active;
+                self.storage.save_run(run)?;
+            }
+        }
+
+        if failed_steps.is_empty() {
+            run.status = WorkflowRunStatus::Completed;
+        } else {
+            run.status = WorkflowRunStatus::Failed;
+            run.error = Some(format!("Failed steps: {}", failed_steps.join(", ")));
+        }
+        run.completed_at = Some(Utc::now());
+        self.storage.save_run(run)?;
+
+        Ok(())
+    }
+
+    /// Hand a step (or each of its parallel instances) to its agent
+    fn dispatch_step(
+        &self,
+        step: &WorkflowStep,
+        run: &mut WorkflowRun,
+        context: &WorkflowContext,
+    ) -> ColonyResult<()> {
+        let output_dir = self.storage.run_output_dir(run);
+        std::fs::create_dir_all(&output_dir)?;
+
+        let instances = step.parallel.unwrap_or(1);
+        let mut task_ids = Vec::new();
+
+        for index in 0..instances {
+            let batch_id = (instances > 1).then_some(index + 1);
+            let output_path = output_dir.join(output_file_name(&step.name, batch_id));
+            let title = match batch_id {
+                Some(batch) => format!("[{}] {} #{}", run.workflow_name, step.name, batch),
+                None => format!("[{}] {}", run.workflow_name, step.name),
+            };
+            let instructions = render_instructions(&step.instructions, context, batch_id);
+
+            let agent = self.instance_agent(&step.agent, index)?;
+            let task_id = self.create_agent_task(run, &agent, &title, |task_id| {
+                format!(
+                    "{}\n\nWhen finished, write your result to {} and run `colony tasks complete {}`.",
+                    instructions,
+                    output_path.display(),
+                    task_id
+                )
+            })?;
+            task_ids.push(task_id);
+        }
+
+        let execution = step_execution_mut(run, &step.name)?;
+        execution.attempt += 1;
+        execution.status = StepStatus::Running;
+        execution.started_at.get_or_insert_with(Utc::now);
+        execution.task_ids = task_ids;
+
+        utils::info(&format!(
+            "Dispatched step '{}' to agent '{}'",
+            step.name, step.agent
+        ));
+
+        Ok(())
+    }
+
+    /// Agent that runs instance `index` of a step addressed to `agent`. A
+    /// replica set's ID hands the instances to its replicas in turn.
+    fn instance_agent(&self, agent: &str, index: u32) -> ColonyResult<String> {
+        let agents = resolve_recipients(&self.colony_root, agent)?;
+        Ok(agents[index as usize % agents.len()].clone())
+    }
+
+    /// Create a task assigned to `agent` and notify the agent about it
+    fn create_agent_task(
+        &self,
+        run: &WorkflowRun,
+        agent: &str,
+        title: &str,
+        description: impl FnOnce(&str) -> String,
+    ) -> ColonyResult<String> {
+        let task_id = TaskIdGenerator::generate("wf");
+        let description = description(&task_id);
+
+        let mut task = Task::new(task_id.clone(), title.to_string(), description.clone());
+        task.assigned_to = Some(agent.to_string());
+        task.tags = vec![
+            "workflow".to_string(),
+            format!("workflow:{}", run.workflow_name),
+            format!("run:{}", run.id),
+        ];
+        self.queue.create_task(task)?;
+
+        let message = Message::new(
+            ENGINE_SENDER,
+            agent,
+            format!(
+                "New workflow task {}: {}\n\n{}",
+                task_id, title, description
+            ),
+            MessageType::Task,
+        );
+        message.save(&self.colony_root)?;
+
+        Ok(task_id)
+    }
+
+    /// Check the tasks belonging to a dispatched step
+    fn poll_step(
+        &self,
+        step: &WorkflowStep,
+        run: &WorkflowRun,
+        deadline: Option<Instant>,
+    ) -> ColonyResult<StepPoll> {
+        let execution = step_execution(run, &step.name)?;
+        let mut tasks = Vec::new();
+
+        for task_id in &execution.task_ids {
+            match self.queue.load_task(task_id)? {
+                Some(task) if task.status == TaskStatus::Cancelled => {
+                    return Ok(StepPoll::Failed(format!(
+                        "Task '{}' was cancelled",
+                        task_id
+                    )));
+                }
+                Some(task) => tasks.push(task),
+                None => {
+                    return Ok(StepPoll::Failed(format!("Task '{}' was deleted", task_id)));
+                }
+            }
+        }
+
+        if tasks.iter().all(|t| t.status == TaskStatus::Completed) {
+            return Ok(StepPoll::Completed(self.collect_output(step, run, &tasks)));
+        }
+
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return Ok(StepPoll::Failed(format!(
+                "Timed out after {}",
+                step.timeout.as_deref().unwrap_or("timeout")
+            )));
+        }
+
+        Ok(StepPoll::Waiting)
+    }
+
+    /// Build a step's output from the files its agents wrote
+    ///
+    /// Falls back to a summary of the completed task when no output file
+    /// exists. Parallel steps produce an array with one entry per instance.
+    fn collect_output(&self, step: &WorkflowStep, run: &WorkflowRun, tasks: &[Task]) -> Value {
+        let output_dir = self.storage.run_output_dir(run);
+        let parallel = tasks.len() > 1;
+
+        let mut outputs: Vec<Value> = tasks
+            .iter()
+            .enumerate()
+            .map(|(index, task)| {
+                let batch_id = parallel.then_some(index as u32 + 1);
+                let path = output_dir.join(output_file_name(&step.name, batch_id));
+                read_output_file(&path).unwrap_or_else(|| {
+                    json!({
+                        "task_id": task.id,
+                        "completed_by": task.claimed_by,
+                    })
+                })
+            })
+            .collect();
+
+        if outputs.len() == 1 {
+            outputs.remove(0)
+        } else {
+            Value::Array(outputs)
+        }
+    }
+
+    /// Dispatch the error handler referenced by a failed step's `on_failure`
+    fn run_error_handler(
+        &self,
+        definition: &WorkflowDefinition,
+        step: &WorkflowStep,
+        run: &WorkflowRun,
+        context: &WorkflowContext,
+    ) -> ColonyResult<()> {
+        let Some(handler_name) = &step.on_failure else {
+            return Ok(());
+        };
+
+        let handler = definition
+            .error_handling
+            .iter()
+            .flatten()
+            .find(|h| &h.step == handler_name);
+
+        let Some(handler) = handler else {
+            utils::warning(&format!(
+                "Error handler '{}' for step '{}' is not defined",
+                handler_name, step.name
+            ));
+            return Ok(());
+        };
+
+        let instructions = render_instructions(
+            &handler.instructions.replace("{{failed_step}}", &step.name),
+            context,
+            None,
+        );
+        let title = format!("[{}] {}", run.workflow_name, handler.step);
+        let agent = self.instance_agent(&handler.agent, 0)?;
+        self.create_agent_task(run, &agent, &title, |_| instructions)?;
+
+        utils::info(&format!(
+            "Dispatched error handler '{}' to agent '{}'",
+            handler.step, handler.agent
+        ));
+
+        Ok(())
+    }
+
+    /// Check whether the run was cancelled (e.g. by `colony workflow cancel`)
+    fn is_cancelled(&self, run: &WorkflowRun) -> bool {
+        self.storage
+            .load_run(&run.id)
+            .is_ok_and(|stored| stored.status == WorkflowRunStatus::Cancelled)
+    }
+
+    /// Cancel the tasks of every step that has not finished yet
+    fn cancel_unfinished_steps(&self, run: &mut WorkflowRun) -> ColonyResult<()> {
+        for execution in &mut run.steps {
+            if matches!(
+                execution.status,
+                StepStatus::Pending | StepStatus::Running | StepStatus::Retrying
+            ) {
+                self.cancel_tasks(&execution.task_ids)?;
+                execution.status = StepStatus::Skipped;
+                execution.error = Some("Workflow run cancelled".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Cancel queued tasks that have not completed yet
+    fn cancel_tasks(&self, task_ids: &[String]) -> ColonyResult<()> {
+        for task_id in task_ids {
+            if let Some(mut task) = self.queue.load_task(task_id)? {
+                if !matches!(task.status, TaskStatus::Completed | TaskStatus::Cancelled) {
+                    task.cancel();
+                    self.queue.update_task(&task)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delay before the next attempt of a step, based on its backoff strategy
+    fn backoff_delay(&self, step: &WorkflowStep, attempt: u32) -> Duration {
+        let strategy = step.retry.as_ref().and_then(|r| r.backoff.as_ref());
+        match strategy {
+            Some(BackoffStrategy::Exponential) => {
+                self.retry_delay * 2u32.pow(attempt.saturating_sub(1).min(10))
+            }
+            Some(BackoffStrategy::Linear) => self.retry_delay * attempt.max(1),
+            Some(BackoffStrategy::Fixed) | None => self.retry_delay,
+        }
+    }
+}
+
+/// Render `{{...}}` variables in step instructions
+///
+/// Supported variables are `{{input}}`, `{{input.<path>}}`,
+/// `{{steps.<name>.output}}`, `{{steps.<name>.output.<path>}}`, `{{run_id}}`,
+/// `{{workflow}}` and `{{batch_id}}` (for parallel steps). Unknown variables
+/// are left as-is.
+pub fn render_instructions(
+    template: &str,
+    context: &WorkflowContext,
+    batch_id: Option<u32>,
+) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let Some(end) = after.find("}}") else {
+            rendered.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let key = after[..end].trim();
+        match resolve_variable(key, context, batch_id) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+fn resolve_variable(key: &str, context: &WorkflowContext, batch_id: Option<u32>) -> Option<String> {
+    match key {
+        "batch_id" => return batch_id.map(|b| b.to_string()),
+        "run_id" => return Some(context.run_id.clone()),
+        "workflow" => return Some(context.workflow_name.clone()),
+        "input" => return Some(value_to_string(&context.input)),
+        _ => {}
+    }
+
+    if let Some(path) = key.strip_prefix("input.") {
+        return lookup_path(&context.input, path).map(value_to_string);
+    }
+
+    let step_ref = key.strip_prefix("steps.")?;
+    let (step_name, path) = step_ref.split_once(".output")?;
+    let output = context.get_step_output(step_name)?;
+
+    match path.strip_prefix('.') {
+        Some(path) => lookup_path(output, path).map(value_to_string),
+        None if path.is_empty() => Some(value_to_string(output)),
+        None => None,
+    }
+}
+
+fn lookup_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, segment| match current {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// File name an agent writes a step's output to
+fn output_file_name(step_name: &str, batch_id: Option<u32>) -> String {
+    match batch_id {
+        Some(batch) => format!("{}-{}.output", step_name, batch),
+        None => format!("{}.output", step_name),
+    }
+}
+
+/// Read an output file, parsing it as JSON when possible
+fn read_output_file(path: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let trimmed = content.trim();
+    Some(serde_json::from_str(trimmed).unwrap_or_else(|_| Value::String(trimmed.to_string())))
+}
+
+fn step_deadline(step: &WorkflowStep) -> Option<Instant> {
+    step.timeout
+        .as_deref()
+        .and_then(parse_duration)
+        .map(|timeout| Instant::now() + timeout)
+}
+
+fn dependencies_completed(step: &WorkflowStep, run: &WorkflowRun) -> bool {
+    step.depends_on.iter().flatten().all(|dep| {
+        run.steps
+            .iter()
+            .any(|s| &s.step_name == dep && s.status == StepStatus::Completed)
+    })
+}
+
+fn find_step<'a>(definition: &'a WorkflowDefinition, name: &str) -> ColonyResult<&'a WorkflowStep> {
+    definition
+        .steps
+        .iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| ColonyError::Colony(format!("Step '{}' not found in workflow", name)))
+}
+
+fn step_execution<'a>(run: &'a WorkflowRun, name: &str) -> ColonyResult<&'a StepExecution> {
+    run.steps
+        .iter()
+        .find(|s| s.step_name == name)
+        .ok_or_else(|| ColonyError::Colony(format!("Step '{}' not found in run", name)))
+}
+
+fn step_execution_mut<'a>(
+    run: &'a mut WorkflowRun,
+    name: &str,
+) -> ColonyResult<&'a mut StepExecution> {
+    run.steps
+        .iter_mut()
+        .find(|s| s.step_name == name)
+        .ok_or_else(|| ColonyError::Colony(format!("Step '{}' not found in run", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn definition(yaml: &str) -> WorkflowDefinition {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_render_instructions() {
+        let mut context = WorkflowContext::new(
+            "run-1".to_string(),
+            "pipeline".to_string(),
+            json!({"date_range": "2024-01", "limits": {"max": 5}}),
+        );
+        context.add_step_output("fetch".to_string(), json!({"rows": 42}));
+
+        let rendered = render_instructions(
+            "{{workflow}}: Fetch {{input.date_range}} (max {{ input.limits.max }}), rows={{steps.fetch.output.rows}}, batch {{batch_id}}, {{unknown}}",
+            &context,
+            Some(2),
+        );
+
+        assert_eq!(
+            rendered,
+            "pipeline: Fetch 2024-01 (max 5), rows=42, batch 2, {{unknown}}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_runs_steps_in_order() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path().to_path_buf();
+        let workflow = definition(
+            r#"
+name: pipeline
+description: null
+steps:
+  - name: fetch
+    agent: fetcher
+    instructions: "Fetch {{input.source}}"
+  - name: report
+    agent: reporter
+    depends_on: [fetch]
+    instructions: "Report on {{steps.fetch.output}}"
+"#,
+        );
+
+        let engine = WorkflowEngine::new(&colony_root)
+            .with_poll_interval(Duration::from_millis(10))
+            .with_retry_delay(Duration::from_millis(10));
+        let mut run = engine
+            .create_run(&workflow, json!({"source": "db"}))
+            .unwrap();
+        let output_dir = WorkflowStorage::new(&colony_root).run_output_dir(&run);
+
+        // Simulated agents: complete every pending task, writing fetch's output
+        let worker_root = colony_root.clone();
+        let worker = tokio::spawn(async move {
+            let queue = TaskQueue::new(&worker_root);
+            loop {
+                for mut task in queue.load_tasks_by_status(&TaskStatus::Pending).unwrap() {
+                    if task.title.ends_with("fetch") {
+                        assert!(task.description.starts_with("Fetch db"));
+                        std::fs::write(output_dir.join("fetch.output"), "42 rows").unwrap();
+                    } else {
+                        assert!(task.description.starts_with("Report on 42 rows"));
+                    }
+                    let agent = task.assigned_to.clone().unwrap();
+                    task.claim(&agent);
+                    task.complete();
+                    queue.update_task(&task).unwrap();
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        engine.execute(&workflow, &mut run).await.unwrap();
+        worker.abort();
+
+        assert_eq!(run.status, WorkflowRunStatus::Completed);
+        assert!(run.steps.iter().all(|s| s.status == StepStatus::Completed));
+        assert_eq!(run.steps[0].output, Some(json!("42 rows")));
+
+        let stored = WorkflowStorage::new(&colony_root)
+            .load_run(&run.id)
+            .unwrap();
+        assert_eq!(stored.status, WorkflowRunStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_task_fails_step_and_skips_dependents() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path().to_path_buf();
+        let workflow = definition(
+            r#"
+name: pipeline
+description: null
+steps:
+  - name: build
+    agent: builder
+    instructions: "Build it"
+  - name: deploy
+    agent: deployer
+    depends_on: [build]
+    instructions: "Deploy it"
+"#,
+        );
+
+        let engine =
+            WorkflowEngine::new(&colony_root).with_poll_interval(Duration::from_millis(10));
+        let mut run = engine.create_run(&workflow, Value::Null).unwrap();
+
+        let worker_root = colony_root.clone();
+        let worker = tokio::spawn(async move {
+            let queue = TaskQueue::new(&worker_root);
+            loop {
+                for mut task in queue.load_tasks_by_status(&TaskStatus::Pending).unwrap() {
+                    task.cancel();
+                    queue.update_task(&task).unwrap();
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        engine.execute(&workflow, &mut run).await.unwrap();
+        worker.abort();
+
+        assert_eq!(run.status, WorkflowRunStatus::Failed);
+        assert_eq!(run.steps[0].status, StepStatus::Failed);
+        assert_eq!(run.steps[1].status, StepStatus::Skipped);
+    }
+
+    #[test]
+    fn test_instance_agent() {
+        let dir = tempdir().unwrap();
+        let colony_root = dir.path();
+        let sets = std::collections::BTreeMap::from([(
+            "worker".to_string(),
+            vec!["worker-1".to_string(), "worker-2".to_string()],
+        )]);
+        crate::colony::messaging::write_groups(colony_root, &sets).unwrap();
+        let engine = WorkflowEngine::new(colony_root);
+
+        // A replica set's instances go to its replicas in turn
+        let agents: Vec<String> = (0..3)
+            .map(|index| engine.instance_agent("worker", index).unwrap())
+            .collect();
+        assert_eq!(agents, vec!["worker-1", "worker-2", "worker-1"]);
+
+        assert_eq!(engine.instance_agent("worker-2", 1).unwrap(), "worker-2");
+        assert_eq!(engine.instance_agent("fetcher", 1).unwrap(), "fetcher");
+    }
+}
@@ -1,11 +1,13 @@
 pub mod definition;
+pub mod engine;
 pub mod storage;
 pub mod types;
 
 pub use definition::topological_sort;
+pub use engine::WorkflowEngine;
 pub use storage::WorkflowStorage;
 pub use types::{
     StepStatus,
-    WorkflowDefinition, WorkflowRun, WorkflowRunStatus,
+    WorkflowDefinition, WorkflowRunStatus,
     WorkflowTrigger,
 };
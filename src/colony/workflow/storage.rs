@@ -125,6 +125,11 @@ impl WorkflowStorage {
         Ok(())
     }
 
+    /// Directory holding step outputs for a workflow run
+    pub fn run_output_dir(&self, run: &WorkflowRun) -> PathBuf {
+        self.runs_dir.join(&run.workflow_name).join(&run.id)
+    }
+
     /// Load a workflow run by ID
     pub fn load_run(&self, run_id: &str) -> ColonyResult<WorkflowRun> {
         // Search through all workflow run directories
@@ -128,6 +128,9 @@ pub struct StepExecution {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     pub attempt: u32,
+    /// Task queue IDs created for this step (one per parallel instance)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub task_ids: Vec<String>,
 }
 
 /// Status of a step execution
@@ -1,8 +1,8 @@
-use crate::colony::state::TaskIdGenerator;
+use crate::colony::ColonyConfig;
 use crate::colony::workflow::{
-    topological_sort, WorkflowRun, WorkflowRunStatus, WorkflowStorage,
+    topological_sort, WorkflowEngine, WorkflowRunStatus, WorkflowStorage,
 };
-use crate::error::ColonyResult;
+use crate::error::{ColonyError, ColonyResult};
 use crate::utils;
 use chrono::Utc;
 use colored::Colorize;
@@ -144,47 +144,79 @@ pub fn show_workflow(name: &str) -> ColonyResult<()> {
 }
 
 /// Run a workflow
-pub fn run_workflow(name: &str, input_json: Option<&str>) -> ColonyResult<()> {
+///
+/// Each step is handed to its agent as a task, and the command waits until
+/// every step has completed, failed or been skipped.
+pub async fn run_workflow(name: &str, input_json: Option<&str>) -> ColonyResult<()> {
     let colony_root = Path::new(".colony");
     let storage = WorkflowStorage::new(colony_root);
 
     // Load workflow definition
     let workflow = storage.load_workflow(name)?;
 
+    // Make sure every step targets an agent that exists in the colony: a
+    // replica like `worker-2`, or a replica set's ID like `worker`, whose
+    // replicas take the step's instances in turn
+    let config_path = &crate::colony::config::config_path();
+    if config_path.exists() {
+        let config = ColonyConfig::load(config_path)?;
+        let agents = config.expanded_agents();
+        let replica_sets = config.replica_sets();
+        let exists =
+            |id: &String| agents.iter().any(|a| &a.id == id) || replica_sets.contains_key(id);
+        let step_agents = workflow.steps.iter().map(|s| &s.agent);
+        let handler_agents = workflow.error_handling.iter().flatten().map(|h| &h.agent);
+        for agent in step_agents.chain(handler_agents) {
+            if !exists(agent) {
+                return Err(ColonyError::Colony(format!(
+                    "Workflow '{}' references unknown agent '{}'",
+                    workflow.name, agent
+                )));
+            }
+        }
+    }
+
     // Parse input if provided
     let input = if let Some(input_str) = input_json {
         serde_json::from_str(input_str)
-            .map_err(|e| crate::error::ColonyError::Colony(format!("Invalid input JSON: {}", e)))?
+            .map_err(|e| ColonyError::Colony(format!("Invalid input JSON: {}", e)))?
     } else {
         serde_json::Value::Null
     };
 
     // Create workflow run
-    let run_id = TaskIdGenerator::generate("run");
-    let run = WorkflowRun {
-        id: run_id.clone(),
-        workflow_name: workflow.name.clone(),
-        status: WorkflowRunStatus::Pending,
-        input: Some(input),
-        started_at: Utc::now(),
-        completed_at: None,
-        steps: vec![],
-        error: None,
-    };
-
-    // Save the run
-    storage.save_run(&run)?;
+    let engine = WorkflowEngine::new(colony_root);
+    let mut run = engine.create_run(&workflow, input)?;
 
-    utils::success(&format!("Workflow run created: {}", run_id));
+    utils::success(&format!("Workflow run created: {}", run.id));
     println!();
-    println!("Run ID: {}", run_id.bold());
+    println!("Run ID: {}", run.id.bold());
     println!("Workflow: {}", workflow.name);
-    println!("Status: {}", "pending".yellow());
     println!();
-    println!("Note: Workflow execution engine coming soon!");
-    println!("For now, you can manually execute steps based on the workflow definition.");
+    println!(
+        "Use 'colony workflow status {}' in another terminal to check progress",
+        run.id
+    );
+    println!();
+
+    engine.execute(&workflow, &mut run).await?;
+
     println!();
-    println!("Use 'colony workflow status {}' to check progress", run_id);
+    match run.status {
+        WorkflowRunStatus::Completed => {
+            utils::success(&format!("Workflow '{}' completed", workflow.name))
+        }
+        WorkflowRunStatus::Cancelled => {
+            utils::warning(&format!("Workflow '{}' was cancelled", workflow.name))
+        }
+        _ => {
+            return Err(ColonyError::Colony(format!(
+                "Workflow '{}' failed: {}",
+                workflow.name,
+                run.error.as_deref().unwrap_or("unknown error")
+            )));
+        }
+    }
 
     Ok(())
 }
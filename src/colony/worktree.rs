@@ -6,6 +6,14 @@ use crate::error::ColonyResult;
 /// Length of shortened SHA for display purposes (standard git short SHA)
 const SHORT_SHA_LENGTH: usize = 8;
 
+/// Branch a worktree is checked out on
+///
+/// Each worktree gets its own branch, `colony/<name>`, so it doesn't conflict
+/// with existing branches.
+pub fn branch_name(worktree_name: &str) -> String {
+    format!("colony/{}", worktree_name)
+}
+
 /// Create a Git worktree for an agent
 pub fn create_worktree(agent_id: &str, base_path: &Path) -> ColonyResult<PathBuf> {
     let worktree_path = base_path.join("worktrees").join(agent_id);
@@ -49,9 +57,7 @@ pub fn create_worktree(agent_id: &str, base_path: &Path) -> ColonyResult<PathBuf
         .trim()
         .to_string();
 
-    // Create a unique branch name for this worktree to avoid conflicts with existing branches
-    // Format: colony/<agent_id>
-    let new_branch_name = format!("colony/{}", agent_id);
+    let new_branch_name = branch_name(agent_id);
 
     crate::utils::info(&format!(
         "Creating worktree with new branch '{}' from commit {}",
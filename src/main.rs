@@ -12,6 +12,14 @@ use error::ColonyResult;
 #[command(author, version, about = "Multi-agent orchestration for Claude Code on tmux", long_about = None)]
 #[command(propagate_version = true)]
 struct Cli {
+    /// Configuration file to use instead of ./colony.yml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<std::path::PathBuf>,
+
+    /// Apply the colony.<PROFILE>.yml overlay
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -19,13 +27,29 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize a new colony configuration
-    Init,
+    Init {
+        /// Create the colony from a blueprint: a built-in name, a path or a git URL
+        #[arg(long)]
+        blueprint: Option<String>,
+    },
 
     /// Start all agents in the colony (requires tmux)
     Start {
         /// Don't automatically attach to the tmux session after starting
         #[arg(long)]
         no_attach: bool,
+
+        /// Run the supervisor in the background to relaunch agents that exit
+        #[arg(long)]
+        supervise: bool,
+
+        /// Run agents as child processes on their own PTYs instead of in tmux
+        #[arg(long, conflicts_with_all = ["no_attach", "supervise"])]
+        headless: bool,
+
+        /// Write each agent's command, prompt and settings to .colony/dry-run/ without starting anything
+        #[arg(long, conflicts_with_all = ["no_attach", "supervise", "headless"])]
+        dry_run: bool,
     },
 
     /// Attach to the tmux session to watch agents work
@@ -56,6 +80,23 @@ enum Commands {
     /// Check colony system health
     Health,
 
+    /// Run the nudge scheduler (started automatically by 'colony start')
+    Nudge,
+
+    /// Push new messages into agent panes (started automatically by 'colony start')
+    Deliver,
+
+    /// Watch agent panes and relaunch agents that exit
+    Supervise {
+        /// Seconds between checks
+        #[arg(long, default_value_t = colony::supervise::DEFAULT_INTERVAL_SECS)]
+        interval: u64,
+
+        /// Maximum number of restarts per agent before it is marked as failed
+        #[arg(long, default_value_t = colony::supervise::DEFAULT_MAX_RESTARTS)]
+        max_restarts: u32,
+    },
+
     /// Broadcast a message to all agents
     Broadcast {
         /// Message to broadcast
@@ -68,6 +109,30 @@ enum Commands {
         agent_id: Option<String>,
     },
 
+    /// Restart a single agent without restarting the colony
+    Restart {
+        /// Agent ID to restart
+        agent_id: String,
+    },
+
+    /// Change how many replicas of an agent run, applying it to the running colony
+    Scale {
+        /// Agent ID with replicas
+        agent_id: String,
+        /// Number of replicas to run
+        replicas: u32,
+        /// Apply the change without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Reconcile the running colony with an edited colony.yml
+    Apply {
+        /// Apply the plan without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
     /// View agent logs
     Logs {
         /// Agent ID to view logs for (omit to list all)
@@ -138,6 +203,12 @@ enum Commands {
         #[command(subcommand)]
         command: MetricsCommands,
     },
+
+    /// Inspect the colony configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -150,6 +221,96 @@ enum MessageCommands {
 
     /// List all messages in the system
     All,
+
+    /// Show the conversation a message belongs to, as a tree of replies
+    Thread {
+        /// ID of any message in the thread
+        message_id: String,
+    },
+
+    /// Send a message
+    Send {
+        /// Agent ID, "all", group:<name>, role:<role> or tag:<tag>
+        to: String,
+        /// Message content
+        message: String,
+
+        /// Agent sending the message
+        #[arg(long, default_value = "operator")]
+        from: String,
+
+        /// Message type: info, task, question, answer, completed or error
+        #[arg(long = "type", default_value = "info")]
+        message_type: colony::messaging::MessageType,
+
+        /// Directory the message was sent from, recorded with its Git branch
+        #[arg(long)]
+        project_dir: Option<std::path::PathBuf>,
+    },
+
+    /// Reply to a message, in the same thread
+    Reply {
+        /// ID of the message to reply to
+        message_id: String,
+        /// Message content
+        message: String,
+
+        /// Agent sending the reply
+        #[arg(long, default_value = "operator")]
+        from: String,
+
+        /// Directory the reply was sent from, recorded with its Git branch
+        #[arg(long)]
+        project_dir: Option<std::path::PathBuf>,
+    },
+
+    /// Show an agent its unread messages and mark them read
+    Read {
+        /// Agent whose inbox to read
+        agent_id: String,
+
+        /// Show every message, not just unread ones
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Mark a message read on an agent's behalf
+    Ack {
+        /// Agent whose inbox holds the message
+        agent_id: String,
+        /// ID of the message
+        message_id: String,
+    },
+
+    /// Show the messages an agent sent and who has read them
+    Sent {
+        /// Agent whose sent messages to show
+        agent_id: String,
+    },
+
+    /// Search message content
+    Search {
+        /// Words that must all appear in the message
+        query: String,
+
+        /// Only messages from this agent
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Only messages from the last duration (e.g. 30m, 2h, 1d) or since an RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Maximum number of messages to show, most recent first
+        #[arg(long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Write every message to a directory as one JSON file per message
+    Export {
+        /// Directory to write the messages to
+        output: std::path::PathBuf,
+    },
 }
 
 #[derive(Subcommand)]
@@ -170,7 +331,7 @@ enum AuthCommands {
 
         /// AWS profile (for bedrock method)
         #[arg(long)]
-        profile: Option<String>,
+        aws_profile: Option<String>,
     },
 
     /// Show authentication status
@@ -528,16 +689,62 @@ enum TemplateCommands {
         name: String,
     },
 
-    /// Install a built-in template
+    /// Install a template from a built-in name, a git URL or a tarball path
     Install {
+        /// Built-in template name, git URL or tarball path
+        source: String,
+
+        /// Set a template parameter (repeatable); parameters not set are asked for
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+    },
+
+    /// Save an agent from colony.yml as a template
+    Create {
         /// Template name
         name: String,
+
+        /// ID of the agent to save
+        #[arg(long)]
+        from_agent: String,
+
+        /// Template version (semantic version)
+        #[arg(long, default_value = "1.0.0")]
+        template_version: String,
+
+        /// Template description
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// Install newer versions of templates from their recorded sources
+    Update {
+        /// Template name (all installed templates if omitted)
+        name: Option<String>,
     },
 
     /// List built-in templates
     Builtin,
 }
 
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show the configuration with colony.local.yml and the profile merged in
+    Show {
+        /// Show every value, including defaults and template settings, with
+        /// the file it came from
+        #[arg(long)]
+        resolved: bool,
+    },
+
+    /// Check colony.yml and its overlays, reporting every problem with its
+    /// file and line
+    Validate,
+
+    /// Print the JSON Schema of colony.yml, for editor autocompletion
+    Schema,
+}
+
 #[derive(Subcommand)]
 enum MetricsCommands {
     /// List all registered metrics
@@ -590,6 +797,11 @@ async fn main() {
 async fn run() -> ColonyResult<()> {
     let cli = Cli::parse();
 
+    colony::config::set_options(colony::config::ConfigOptions {
+        path: cli.config,
+        profile: cli.profile,
+    });
+
     // Initialize telemetry if config exists
     let telemetry_client = init_telemetry().await;
 
@@ -600,19 +812,24 @@ async fn run() -> ColonyResult<()> {
     let start_time = std::time::Instant::now();
 
     let result = match cli.command {
-        Commands::Init => colony::init::run().await,
-        Commands::Start { no_attach } => colony::start::run(no_attach).await,
+        Commands::Init { blueprint } => colony::init::run(blueprint).await,
+        Commands::Start {
+            no_attach,
+            supervise,
+            headless,
+            dry_run,
+        } => colony::start::run(no_attach, supervise, headless, dry_run).await,
         Commands::Attach => colony::attach::run().await,
         Commands::Auth { command } => match command {
             AuthCommands::Login {
                 method,
                 api_key,
                 region,
-                profile,
+                aws_profile,
             } => match method {
                 AuthMethod::OAuth => colony::auth_cmd::login_oauth().await,
                 AuthMethod::ApiKey => colony::auth_cmd::login_api_key(api_key).await,
-                AuthMethod::Bedrock => colony::auth_cmd::login_bedrock(region, profile).await,
+                AuthMethod::Bedrock => colony::auth_cmd::login_bedrock(region, aws_profile).await,
                 AuthMethod::VertexAi => {
                     println!("Vertex AI authentication not yet implemented");
                     Ok(())
@@ -628,7 +845,7 @@ async fn run() -> ColonyResult<()> {
             RelayCommands::Disconnect => colony::relay_cmd::disconnect().await,
         },
         Commands::Tui => {
-            let config_path = std::path::Path::new("colony.yml");
+            let config_path = &colony::config::config_path();
             colony::tui::run_tui(config_path).map_err(crate::error::ColonyError::Colony)?;
             Ok(())
         }
@@ -641,8 +858,21 @@ async fn run() -> ColonyResult<()> {
         }
         Commands::Status => colony::status::run().await,
         Commands::Health => colony::health::run().await,
+        Commands::Nudge => colony::nudge::run().await,
+        Commands::Deliver => colony::delivery::run().await,
+        Commands::Supervise {
+            interval,
+            max_restarts,
+        } => colony::supervise::run(interval, max_restarts).await,
         Commands::Broadcast { message } => colony::broadcast::run(message).await,
         Commands::Stop { agent_id } => colony::stop::run(agent_id).await,
+        Commands::Restart { agent_id } => colony::restart::run(agent_id).await,
+        Commands::Scale {
+            agent_id,
+            replicas,
+            yes,
+        } => colony::scale::run(agent_id, replicas, yes).await,
+        Commands::Apply { yes } => colony::apply::run(yes).await,
         Commands::Logs {
             agent_id,
             level,
@@ -667,6 +897,46 @@ async fn run() -> ColonyResult<()> {
                 colony::messages_cmd::list_messages(agent_id).await
             }
             MessageCommands::All => colony::messages_cmd::list_all_messages().await,
+            MessageCommands::Thread { message_id } => {
+                colony::messages_cmd::show_thread(message_id).await
+            }
+            MessageCommands::Send {
+                to,
+                message,
+                from,
+                message_type,
+                project_dir,
+            } => {
+                colony::messages_cmd::send_message(from, to, message, message_type, project_dir)
+                    .await
+            }
+            MessageCommands::Reply {
+                message_id,
+                message,
+                from,
+                project_dir,
+            } => {
+                colony::messages_cmd::reply_to_message(from, message_id, message, project_dir).await
+            }
+            MessageCommands::Read { agent_id, all } => {
+                colony::messages_cmd::read_messages(agent_id, all).await
+            }
+            MessageCommands::Ack {
+                agent_id,
+                message_id,
+            } => colony::messages_cmd::ack_message(agent_id, message_id).await,
+            MessageCommands::Sent { agent_id } => {
+                colony::messages_cmd::sent_messages(agent_id).await
+            }
+            MessageCommands::Search {
+                query,
+                from,
+                since,
+                limit,
+            } => colony::messages_cmd::search_messages(query, from, since, limit).await,
+            MessageCommands::Export { output } => {
+                colony::messages_cmd::export_messages(output).await
+            }
         },
         Commands::Tasks { command } => match command {
             TaskCommands::List { status, compact } => {
@@ -755,7 +1025,7 @@ async fn run() -> ColonyResult<()> {
                 colony::workflow_cmd::show_workflow(&name)
             }
             WorkflowOrchestratorCommands::Run { name, input } => {
-                colony::workflow_cmd::run_workflow(&name, input.as_deref())
+                colony::workflow_cmd::run_workflow(&name, input.as_deref()).await
             }
             WorkflowOrchestratorCommands::Status { run_id } => {
                 colony::workflow_cmd::show_run_status(&run_id)
@@ -776,7 +1046,23 @@ async fn run() -> ColonyResult<()> {
         Commands::Template { command } => match command {
             TemplateCommands::List => colony::template_cmd::list_templates(),
             TemplateCommands::Show { name } => colony::template_cmd::show_template(&name),
-            TemplateCommands::Install { name } => colony::template_cmd::install_template(&name),
+            TemplateCommands::Install { source, set } => {
+                colony::template_cmd::install_template(&source, &set)
+            }
+            TemplateCommands::Create {
+                name,
+                from_agent,
+                template_version,
+                description,
+            } => colony::template_cmd::create_template(
+                &name,
+                &from_agent,
+                &template_version,
+                description,
+            ),
+            TemplateCommands::Update { name } => {
+                colony::template_cmd::update_templates(name.as_deref())
+            }
             TemplateCommands::Builtin => colony::template_cmd::list_builtin(),
         },
         Commands::Metrics { command } => match command {
@@ -793,6 +1079,11 @@ async fn run() -> ColonyResult<()> {
                 colony::metrics_cmd::record_sample(&name, value)
             }
         },
+        Commands::Config { command } => match command {
+            ConfigCommands::Show { resolved } => colony::config_cmd::show(resolved),
+            ConfigCommands::Validate => colony::config_cmd::validate(),
+            ConfigCommands::Schema => colony::config_cmd::schema(),
+        },
     };
 
     // Track command completion/error
@@ -816,7 +1107,7 @@ async fn run() -> ColonyResult<()> {
 
 /// Initialize telemetry client if config exists and telemetry is enabled
 async fn init_telemetry() -> Option<colony::telemetry::TelemetryClient> {
-    let config_path = std::path::Path::new("colony.yml");
+    let config_path = &colony::config::config_path();
 
     if !config_path.exists() {
         return None;
@@ -837,7 +1128,7 @@ async fn init_telemetry() -> Option<colony::telemetry::TelemetryClient> {
 /// Get a human-readable command name for telemetry
 fn get_command_name(command: &Commands) -> String {
     match command {
-        Commands::Init => "init".to_string(),
+        Commands::Init { .. } => "init".to_string(),
         Commands::Start { .. } => "start".to_string(),
         Commands::Attach => "attach".to_string(),
         Commands::Auth { .. } => "auth".to_string(),
@@ -847,8 +1138,14 @@ fn get_command_name(command: &Commands) -> String {
         Commands::Dashboard => "dashboard".to_string(),
         Commands::Status => "status".to_string(),
         Commands::Health => "health".to_string(),
+        Commands::Nudge => "nudge".to_string(),
+        Commands::Deliver => "deliver".to_string(),
+        Commands::Supervise { .. } => "supervise".to_string(),
         Commands::Broadcast { .. } => "broadcast".to_string(),
         Commands::Stop { .. } => "stop".to_string(),
+        Commands::Restart { .. } => "restart".to_string(),
+        Commands::Scale { .. } => "scale".to_string(),
+        Commands::Apply { .. } => "apply".to_string(),
         Commands::Logs { .. } => "logs".to_string(),
         Commands::Destroy => "destroy".to_string(),
         Commands::Messages { .. } => "messages".to_string(),
@@ -858,5 +1155,6 @@ fn get_command_name(command: &Commands) -> String {
         Commands::Plugin { .. } => "plugin".to_string(),
         Commands::Template { .. } => "template".to_string(),
         Commands::Metrics { .. } => "metrics".to_string(),
+        Commands::Config { .. } => "config".to_string(),
     }
 }